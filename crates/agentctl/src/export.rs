@@ -0,0 +1,332 @@
+use anyhow::{Context, Result};
+use osmoda_ledger::chain::{ChainWalker, Event};
+use osmoda_ledger::export::{self, EXPORT_FORMAT, EXPORT_VERSION};
+use osmoda_ledger::archive;
+use osmoda_ledger::signing::{self, SignedCheckpoint};
+use rusqlite::params;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+use crate::verify::label;
+use crate::{open_ledger, parse_public_key};
+
+#[derive(Debug, Deserialize)]
+struct ExportIncident {
+    id: String,
+    name: String,
+    status: String,
+    created_at: String,
+    resolved_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExportStep {
+    incident_id: String,
+    step_number: i64,
+    action: String,
+    result: String,
+    receipt_id: Option<String>,
+    timestamp: String,
+}
+
+/// A parsed export plus the number of problems found while verifying it.
+struct VerifiedExport {
+    events: Vec<Event>,
+    incidents: Vec<ExportIncident>,
+    steps: Vec<ExportStep>,
+    checkpoints_checked: Option<u64>,
+    errors: u64,
+}
+
+pub fn cmd_export(state_dir: &Path, out: Option<&Path>) -> Result<()> {
+    let conn = open_ledger(&state_dir.to_path_buf())?;
+
+    let mut writer: Box<dyn Write> = match out {
+        Some(path) => Box::new(std::io::BufWriter::new(
+            std::fs::File::create(path)
+                .with_context(|| format!("Failed to create {}", path.display()))?,
+        )),
+        None => Box::new(std::io::BufWriter::new(std::io::stdout().lock())),
+    };
+
+    let archive_dir = state_dir.join(archive::ARCHIVE_DIR);
+    let summary = export::write_jsonl(&conn, Some(&archive_dir), &mut writer)?;
+    writer.flush()?;
+
+    if let Some(path) = out {
+        eprintln!(
+            "Exported {} events, {} incidents, {} steps to {}",
+            summary.event_count,
+            summary.incident_count,
+            summary.step_count,
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+pub fn cmd_verify_export(file: &Path, pubkey: Option<&str>, checkpoint_every: i64) -> Result<()> {
+    let verified = verify_export_file(file, pubkey, checkpoint_every)?;
+
+    if verified.errors > 0 {
+        eprintln!(
+            "EXPORT INVALID: {} error(s) found in {} events!",
+            verified.errors,
+            verified.events.len()
+        );
+        std::process::exit(1);
+    }
+
+    println!(
+        "Export verified: {} events (chain intact from genesis), {} incidents, {} steps attested.",
+        verified.events.len(),
+        verified.incidents.len(),
+        verified.steps.len()
+    );
+    match verified.checkpoints_checked {
+        Some(n) => println!("Signed checkpoints verified: {n}."),
+        None => println!("Signed checkpoints NOT checked: no trusted public key (pass --pubkey)."),
+    }
+    Ok(())
+}
+
+pub fn cmd_import(state_dir: &Path, file: &Path) -> Result<()> {
+    let verified = verify_export_file(file, None, signing::DEFAULT_SIGNED_CHECKPOINT_EVERY)?;
+    if verified.errors > 0 {
+        anyhow::bail!(
+            "Refusing to import: export failed verification with {} error(s)",
+            verified.errors
+        );
+    }
+
+    std::fs::create_dir_all(state_dir)
+        .with_context(|| format!("Failed to create {}", state_dir.display()))?;
+    let mut conn = open_ledger(&state_dir.to_path_buf())?;
+
+    // Same base tables as agentd ledger.rs; agentd adds the rest (FTS, checkpoints) on first start.
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            ts TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+            type TEXT NOT NULL,
+            actor TEXT NOT NULL,
+            payload TEXT NOT NULL,
+            prev_hash TEXT NOT NULL,
+            hash TEXT NOT NULL,
+            verified_actor TEXT,
+            payload_digest TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS incidents (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'open',
+            created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+            resolved_at TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS incident_steps (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            incident_id TEXT NOT NULL REFERENCES incidents(id),
+            step_number INTEGER NOT NULL,
+            action TEXT NOT NULL,
+            result TEXT NOT NULL,
+            receipt_id TEXT,
+            timestamp TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now'))
+        );",
+    )?;
+
+    let existing: i64 = conn.query_row("SELECT COUNT(*) FROM events", [], |row| row.get(0))?;
+    if existing > 0 {
+        anyhow::bail!(
+            "Refusing to import: ledger at {} already has {existing} events. Import into a fresh state directory.",
+            state_dir.display()
+        );
+    }
+
+    let tx = conn.transaction()?;
+    for e in &verified.events {
+        tx.execute(
+            "INSERT INTO events (id, ts, type, actor, payload, prev_hash, hash, verified_actor, payload_digest)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![e.id, e.ts, e.event_type, e.actor, e.payload, e.prev_hash, e.hash, e.verified_actor, e.payload_digest],
+        )?;
+    }
+    for i in &verified.incidents {
+        tx.execute(
+            "INSERT INTO incidents (id, name, status, created_at, resolved_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![i.id, i.name, i.status, i.created_at, i.resolved_at],
+        )?;
+    }
+    for s in &verified.steps {
+        tx.execute(
+            "INSERT INTO incident_steps (incident_id, step_number, action, result, receipt_id, timestamp) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![s.incident_id, s.step_number, s.action, s.result, s.receipt_id, s.timestamp],
+        )?;
+    }
+    tx.commit()?;
+
+    println!(
+        "Imported {} events, {} incidents, {} steps into {}.",
+        verified.events.len(),
+        verified.incidents.len(),
+        verified.steps.len(),
+        state_dir.display()
+    );
+    println!("Start agentd to rebuild the search index and resume signed checkpoints.");
+    Ok(())
+}
+
+/// Parse and verify an export file. Problems are printed to stderr and counted;
+/// only unreadable or structurally unusable files return Err.
+fn verify_export_file(file: &Path, pubkey: Option<&str>, checkpoint_every: i64) -> Result<VerifiedExport> {
+    let trusted = pubkey.map(|key| key.trim().to_lowercase());
+    if let Some(ref key) = trusted {
+        parse_public_key(key)?;
+    }
+    let reader = std::io::BufReader::new(
+        std::fs::File::open(file).with_context(|| format!("Failed to open {}", file.display()))?,
+    );
+
+    let mut events: Vec<Event> = Vec::new();
+    let mut incidents: Vec<ExportIncident> = Vec::new();
+    let mut steps: Vec<ExportStep> = Vec::new();
+    let mut checkpoints: Vec<SignedCheckpoint> = Vec::new();
+    let mut footer: Option<Value> = None;
+    let mut errors = 0u64;
+
+    let mut created: HashMap<String, String> = HashMap::new();
+    let mut stepped: HashMap<(String, i64), (String, String)> = HashMap::new();
+
+    for (n, line) in reader.lines().enumerate() {
+        let lineno = n + 1;
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let value: Value = serde_json::from_str(&line)
+            .with_context(|| format!("Line {lineno} is not valid JSON"))?;
+        let kind = value.get("kind").and_then(|k| k.as_str()).unwrap_or("");
+
+        if lineno == 1 {
+            if kind != "header" || value.get("format").and_then(|f| f.as_str()) != Some(EXPORT_FORMAT) {
+                anyhow::bail!("Not an osModa ledger export (missing header line)");
+            }
+            let version = value.get("version").and_then(|v| v.as_i64()).unwrap_or(0);
+            if version != EXPORT_VERSION {
+                anyhow::bail!("Unsupported export version {version} (expected {EXPORT_VERSION})");
+            }
+            continue;
+        }
+        if footer.is_some() {
+            eprintln!("TRAILING DATA at line {lineno}: content after footer");
+            errors += 1;
+            continue;
+        }
+
+        match kind {
+            "event" => {
+                let e: Event = serde_json::from_value(value)
+                    .with_context(|| format!("Line {lineno}: malformed event"))?;
+
+                // Index incident audit events so incident rows can be attested
+                if e.event_type == "incident.create" || e.event_type == "incident.step" {
+                    let p: Value = serde_json::from_str(&e.payload).unwrap_or(json!({}));
+                    let incident_id = p.get("incident_id").and_then(|v| v.as_str()).unwrap_or("").to_string();
+                    if e.event_type == "incident.create" {
+                        let name = p.get("name").and_then(|v| v.as_str()).unwrap_or("").to_string();
+                        created.insert(incident_id, name);
+                    } else {
+                        let step = p.get("step_number").and_then(|v| v.as_i64()).unwrap_or(0);
+                        let action = p.get("action").and_then(|v| v.as_str()).unwrap_or("").to_string();
+                        let result = p.get("result").and_then(|v| v.as_str()).unwrap_or("").to_string();
+                        stepped.insert((incident_id, step), (action, result));
+                    }
+                }
+
+                events.push(e);
+            }
+            "incident" => incidents.push(
+                serde_json::from_value(value).with_context(|| format!("Line {lineno}: malformed incident"))?,
+            ),
+            "incident_step" => steps.push(
+                serde_json::from_value(value).with_context(|| format!("Line {lineno}: malformed incident step"))?,
+            ),
+            "checkpoint" => checkpoints.push(
+                serde_json::from_value(value).with_context(|| format!("Line {lineno}: malformed checkpoint"))?,
+            ),
+            "footer" => footer = Some(value),
+            other => {
+                eprintln!("UNKNOWN RECORD at line {lineno}: kind '{other}'");
+                errors += 1;
+            }
+        }
+    }
+
+    // Signed checkpoints must reference hashes in this chain; signatures and coverage need a trusted key
+    let checkpoint_count = checkpoints.len();
+    let mut walker = ChainWalker::default();
+    walker.expect_checkpoints(checkpoints, trusted.as_deref());
+    walker.require_checkpoint_every(checkpoint_every);
+    for e in &events {
+        walker.check(e);
+    }
+    let chain = walker.finish(true, 0);
+    for issue in &chain.issues {
+        eprintln!("{} at event #{}: {}", label(issue.check), issue.event_id, issue.detail);
+    }
+    errors += chain.issue_count;
+
+    // Footer guards against truncation
+    match footer {
+        None => {
+            eprintln!("TRUNCATED: export has no footer line");
+            errors += 1;
+        }
+        Some(f) => {
+            let count = |k: &str| f.get(k).and_then(|v| v.as_i64()).unwrap_or(-1);
+            let head = f.get("head_hash").and_then(|v| v.as_str()).unwrap_or("");
+            if count("event_count") != events.len() as i64
+                || count("incident_count") != incidents.len() as i64
+                || count("step_count") != steps.len() as i64
+                || count("checkpoint_count") != checkpoint_count as i64
+            {
+                eprintln!("COUNT MISMATCH: footer counts do not match the records in the file");
+                errors += 1;
+            }
+            if head != chain.head_hash {
+                eprintln!("HEAD MISMATCH: footer head_hash={head}, chain ends at {}", chain.head_hash);
+                errors += 1;
+            }
+        }
+    }
+
+    // Incident tables are not hash-chained; each row must be attested by a chained event
+    for i in &incidents {
+        if created.get(&i.id) != Some(&i.name) {
+            eprintln!("UNATTESTED INCIDENT {}: no matching incident.create event", i.id);
+            errors += 1;
+        }
+    }
+    for s in &steps {
+        let key = (s.incident_id.clone(), s.step_number);
+        if stepped.get(&key) != Some(&(s.action.clone(), s.result.clone())) {
+            eprintln!(
+                "UNATTESTED STEP {}#{}: no matching incident.step event",
+                s.incident_id, s.step_number
+            );
+            errors += 1;
+        }
+    }
+
+    Ok(VerifiedExport {
+        checkpoints_checked: chain.signed_checkpoints_checked,
+        events,
+        incidents,
+        steps,
+        errors,
+    })
+}
@@ -0,0 +1,44 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+
+use crate::agentd_get;
+
+/// Fetch the postmortem agentd renders at GET /incident/{id}/report, so the CLI and
+/// the API never disagree about what a report contains.
+pub fn cmd_report(socket: &Path, id: &str, out: Option<&Path>) -> Result<()> {
+    let (status, markdown) = agentd_get(socket, &format!("/incident/{}/report", path_segment(id)))?;
+    match status {
+        200 => {}
+        404 => anyhow::bail!("incident {id} not found"),
+        _ => anyhow::bail!("agentd returned HTTP {status} for the report on incident {id}: {markdown}"),
+    }
+
+    match out {
+        Some(path) => std::fs::write(path, markdown)
+            .with_context(|| format!("Failed to write {}", path.display()))?,
+        None => print!("{markdown}"),
+    }
+    Ok(())
+}
+
+/// Percent-encode everything but RFC 3986 unreserved characters.
+fn path_segment(text: &str) -> String {
+    text.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_segment_escapes_separators() {
+        assert_eq!(path_segment("inc-42_a.b"), "inc-42_a.b");
+        assert_eq!(path_segment("../ledger/export"), "..%2Fledger%2Fexport");
+        assert_eq!(path_segment("a b?"), "a%20b%3F");
+    }
+}
@@ -1,8 +1,15 @@
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
+use osmoda_ledger::chain::{self, Event, Tombstone};
+use osmoda_ledger::query::{self, EventFilter, StatsBucket, StatsGroup};
+use osmoda_ledger::merkle::{self, MerkleCheckpoint};
+use osmoda_ledger::signing;
 use rusqlite::Connection;
-use sha2::{Digest, Sha256};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+mod export;
+mod incident;
+mod verify;
 
 #[derive(Parser)]
 #[command(name = "agentctl", about = "osModa CLI — query events and verify ledger integrity")]
@@ -27,34 +34,223 @@ enum Commands {
         #[arg(long, default_value = "20")]
         last: u32,
 
-        /// Filter by event type
+        /// Filter by event type (a trailing `*` matches a prefix, e.g. `switch.*`)
         #[arg(long)]
         r#type: Option<String>,
 
         /// Filter by actor
         #[arg(long)]
         actor: Option<String>,
+
+        /// Only events at or after this time (RFC 3339 or YYYY-MM-DD, UTC if no offset)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Only events before this time (RFC 3339 or YYYY-MM-DD, UTC if no offset)
+        #[arg(long)]
+        until: Option<String>,
+
+        /// Only events with an id greater than this (page forward)
+        #[arg(long)]
+        after_id: Option<i64>,
+
+        /// Only events with an id smaller than this (page backward)
+        #[arg(long)]
+        before_id: Option<i64>,
+
+        /// Sort and print order. Without it, the newest events are shown oldest-first.
+        #[arg(long, value_enum)]
+        order: Option<Order>,
+    },
+
+    /// Verify the integrity of the hash-chained ledger and its signed checkpoints
+    VerifyLedger {
+        /// Trusted agentd signing key (hex). Defaults to <state-dir>/ledger-signing.pub,
+        /// which anyone able to rewrite the ledger can replace — pin the key for real audits.
+        #[arg(long)]
+        pubkey: Option<String>,
+
+        /// agentd's --checkpoint-every-events: fail when the head is further than this past
+        /// the newest valid signed checkpoint
+        #[arg(long, default_value_t = signing::DEFAULT_SIGNED_CHECKPOINT_EVERY)]
+        checkpoint_every: i64,
+
+        /// Rehash every event instead of starting from agentd's signed verification watermark
+        #[arg(long)]
+        full: bool,
+
+        /// Print a structured report (first broken event, failed check, untrusted range) as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Verify a Merkle inclusion proof (from GET /ledger/proof/{id}) offline
+    VerifyProof {
+        /// Path to the proof JSON file
+        proof: PathBuf,
+
+        /// Trusted checkpoint root (hex). Without it the proof's root must carry a valid
+        /// signature by the ledger key.
+        #[arg(long)]
+        root: Option<String>,
+
+        /// Trusted agentd signing key (hex) for the checkpoint root. Defaults to
+        /// <state-dir>/ledger-signing.pub.
+        #[arg(long)]
+        pubkey: Option<String>,
     },
 
-    /// Verify the integrity of the hash-chained ledger
-    VerifyLedger,
+    /// Export, verify and import portable ledger archives
+    Ledger {
+        #[command(subcommand)]
+        command: LedgerCommands,
+    },
+
+    /// Incident workspaces
+    Incident {
+        #[command(subcommand)]
+        command: IncidentCommands,
+    },
+
+    /// Show ledger statistics: counts per group, optionally as a time histogram
+    Stats {
+        /// Count events per type, actor or verified actor
+        #[arg(long, value_enum, default_value = "type")]
+        group_by: GroupBy,
 
-    /// Show ledger statistics
-    Stats,
+        /// Also split counts into hour/day/week buckets (UTC, weeks start Monday)
+        #[arg(long, value_enum)]
+        bucket: Option<Bucket>,
+
+        /// Only this event type; a trailing `*` matches a prefix (e.g. approval.*)
+        #[arg(long)]
+        r#type: Option<String>,
+
+        /// Only events at or after this time (RFC 3339 or YYYY-MM-DD, UTC if no offset)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Only events before this time (RFC 3339 or YYYY-MM-DD, UTC if no offset)
+        #[arg(long)]
+        until: Option<String>,
+    },
 
     /// Query agentd health endpoint
     Health,
 }
 
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum Order {
+    Asc,
+    Desc,
+}
+
+/// `--group-by` values, one per StatsGroup.
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum GroupBy {
+    Type,
+    Actor,
+    VerifiedActor,
+}
+
+/// `--bucket` values, one per StatsBucket.
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum Bucket {
+    Hour,
+    Day,
+    Week,
+}
+
+/// Filters for `agentctl events`, mirroring agentd's EventFilter.
+struct EventsArgs {
+    last: u32,
+    event_type: Option<String>,
+    actor: Option<String>,
+    since: Option<String>,
+    until: Option<String>,
+    after_id: Option<i64>,
+    before_id: Option<i64>,
+    order: Option<Order>,
+}
+
+#[derive(Subcommand)]
+enum LedgerCommands {
+    /// Write events, incidents and incident steps as JSONL with chain hashes
+    Export {
+        /// Output file (defaults to stdout)
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+
+    /// Verify a JSONL export standalone (no ledger database needed)
+    VerifyExport {
+        /// Path to the JSONL export
+        file: PathBuf,
+
+        /// Trusted agentd signing key (hex) for the embedded signed checkpoints
+        #[arg(long)]
+        pubkey: Option<String>,
+
+        /// agentd's --checkpoint-every-events: with --pubkey, fail when the head is further
+        /// than this past the newest valid signed checkpoint
+        #[arg(long, default_value_t = signing::DEFAULT_SIGNED_CHECKPOINT_EVERY)]
+        checkpoint_every: i64,
+    },
+
+    /// Import a verified JSONL export into a fresh state directory (before agentd first starts)
+    Import {
+        /// Path to the JSONL export
+        file: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum IncidentCommands {
+    /// Print agentd's Markdown postmortem: timeline, linked receipts and the affected actors' events
+    Report {
+        /// Incident id
+        id: String,
+
+        /// Output file (defaults to stdout)
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+}
+
 fn main() -> Result<()> {
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Events { last, r#type, actor } => {
-            cmd_events(&cli.state_dir, last, r#type, actor)
+        Commands::Events { last, r#type, actor, since, until, after_id, before_id, order } => {
+            let args = EventsArgs {
+                last,
+                event_type: r#type,
+                actor,
+                since,
+                until,
+                after_id,
+                before_id,
+                order,
+            };
+            cmd_events(&cli.state_dir, args)
+        }
+        Commands::VerifyLedger { pubkey, checkpoint_every, full, json } => {
+            verify::cmd_verify_ledger(&cli.state_dir, pubkey, checkpoint_every, full, json)
+        }
+        Commands::VerifyProof { proof, root, pubkey } => cmd_verify_proof(&cli.state_dir, &proof, root, pubkey),
+        Commands::Ledger { command } => match command {
+            LedgerCommands::Export { out } => export::cmd_export(&cli.state_dir, out.as_deref()),
+            LedgerCommands::VerifyExport { file, pubkey, checkpoint_every } => {
+                export::cmd_verify_export(&file, pubkey.as_deref(), checkpoint_every)
+            }
+            LedgerCommands::Import { file } => export::cmd_import(&cli.state_dir, &file),
+        },
+        Commands::Incident { command } => match command {
+            IncidentCommands::Report { id, out } => incident::cmd_report(&cli.socket, &id, out.as_deref()),
+        },
+        Commands::Stats { group_by, bucket, r#type, since, until } => {
+            cmd_stats(&cli.state_dir, group_by, bucket, r#type, since, until)
         }
-        Commands::VerifyLedger => cmd_verify_ledger(&cli.state_dir),
-        Commands::Stats => cmd_stats(&cli.state_dir),
         Commands::Health => cmd_health(&cli.socket),
     }
 }
@@ -66,30 +262,28 @@ fn open_ledger(state_dir: &PathBuf) -> Result<Connection> {
     Ok(conn)
 }
 
-fn cmd_events(
-    state_dir: &PathBuf,
-    last: u32,
-    type_filter: Option<String>,
-    actor_filter: Option<String>,
-) -> Result<()> {
+fn cmd_events(state_dir: &PathBuf, args: EventsArgs) -> Result<()> {
     let conn = open_ledger(state_dir)?;
 
-    let mut query = String::from(
-        "SELECT id, ts, type, actor, payload, hash FROM events WHERE 1=1",
+    let filter = EventFilter {
+        event_type: args.event_type,
+        actor: args.actor,
+        since: args.since,
+        until: args.until,
+        after_id: args.after_id,
+        before_id: args.before_id,
+        ..Default::default()
+    };
+    let (conditions, mut params) = query::filter_conditions(&filter)?;
+    let order = match args.order {
+        Some(Order::Asc) => "ASC",
+        Some(Order::Desc) | None => "DESC",
+    };
+    let query = format!(
+        "SELECT id, ts, type, actor, payload, hash FROM events WHERE 1=1{conditions} ORDER BY id {order} LIMIT ?{}",
+        params.len() + 1
     );
-    let mut params: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
-
-    if let Some(ref t) = type_filter {
-        query.push_str(" AND type = ?");
-        params.push(Box::new(t.clone()));
-    }
-    if let Some(ref a) = actor_filter {
-        query.push_str(" AND actor = ?");
-        params.push(Box::new(a.clone()));
-    }
-
-    query.push_str(" ORDER BY id DESC LIMIT ?");
-    params.push(Box::new(last));
+    params.push(Box::new(args.last));
 
     let param_refs: Vec<&dyn rusqlite::types::ToSql> = params.iter().map(|p| p.as_ref()).collect();
     let mut stmt = conn.prepare(&query)?;
@@ -105,7 +299,9 @@ fn cmd_events(
     })?;
 
     let mut events: Vec<_> = rows.collect::<Result<Vec<_>, _>>()?;
-    events.reverse();
+    if args.order.is_none() {
+        events.reverse();
+    }
 
     for (id, ts, event_type, actor, payload, hash) in &events {
         println!("#{id} [{ts}] {event_type} by {actor}");
@@ -128,106 +324,145 @@ fn cmd_events(
     Ok(())
 }
 
-fn cmd_verify_ledger(state_dir: &PathBuf) -> Result<()> {
-    let conn = open_ledger(state_dir)?;
-
-    let mut stmt =
-        conn.prepare("SELECT id, ts, type, actor, payload, prev_hash, hash FROM events ORDER BY id ASC")?;
-
-    let rows = stmt.query_map([], |row| {
-        Ok((
-            row.get::<_, i64>(0)?,
-            row.get::<_, String>(1)?,
-            row.get::<_, String>(2)?,
-            row.get::<_, String>(3)?,
-            row.get::<_, String>(4)?,
-            row.get::<_, String>(5)?,
-            row.get::<_, String>(6)?,
-        ))
-    })?;
-
-    let mut expected_prev_hash = "0".repeat(64); // matches GENESIS_PREV_HASH in agentd ledger
-    let mut count = 0u64;
-    let mut errors = 0u64;
-
-    for row in rows {
-        let (id, ts, event_type, actor, payload, prev_hash, stored_hash) = row?;
-        count += 1;
+/// Parse a hex-encoded Ed25519 public key.
+fn parse_public_key(hex_key: &str) -> Result<ed25519_dalek::VerifyingKey> {
+    let key_bytes: [u8; 32] = hex::decode(hex_key.trim())
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .context("Trusted public key must be 32 bytes of hex")?;
+    ed25519_dalek::VerifyingKey::from_bytes(&key_bytes).context("Invalid Ed25519 public key")
+}
 
-        // Verify prev_hash links correctly
-        if prev_hash != expected_prev_hash {
-            eprintln!(
-                "CHAIN BREAK at event #{id}: expected prev_hash={expected_prev_hash}, got {prev_hash}"
-            );
-            errors += 1;
+fn cmd_verify_proof(state_dir: &Path, proof_path: &Path, root: Option<String>, pubkey: Option<String>) -> Result<()> {
+    let data = std::fs::read_to_string(proof_path)
+        .with_context(|| format!("Failed to read proof at {}", proof_path.display()))?;
+    let proof: serde_json::Value = serde_json::from_str(&data).context("Proof is not valid JSON")?;
+    let part = |key: &str| proof.get(key).cloned().with_context(|| format!("Proof is missing '{key}'"));
+
+    let event: Event = serde_json::from_value(part("event")?).context("Proof event is malformed")?;
+    let path: Vec<merkle::ProofStep> = serde_json::from_value(part("path")?).context("Proof path is malformed")?;
+    let checkpoint: MerkleCheckpoint =
+        serde_json::from_value(part("checkpoint")?).context("Proof checkpoint is malformed")?;
+
+    // 1. The payload must match its digest, and the event body hash to the claimed hash
+    let redacted = match check_proof_event(&event) {
+        Ok(redacted) => redacted,
+        Err(problem) => {
+            eprintln!("PROOF INVALID: event #{} {problem}", event.id);
+            std::process::exit(1);
         }
-
-        // Recompute hash (pipe-delimited to match agentd ledger format)
-        let hash_input = format!("{id}|{ts}|{event_type}|{actor}|{payload}|{prev_hash}");
-        let mut hasher = Sha256::new();
-        hasher.update(hash_input.as_bytes());
-        let computed_hash = hex::encode(hasher.finalize());
-
-        if computed_hash != stored_hash {
-            eprintln!(
-                "HASH MISMATCH at event #{id}: computed={computed_hash}, stored={stored_hash}"
-            );
-            errors += 1;
+    };
+
+    // 2. Fold the audit path up to a root
+    let folded_root = merkle::fold_proof(&event.hash, &path).context("Invalid sibling hash in path")?;
+
+    // 3. Compare against a trusted root: explicit, or the proof's own root if the ledger key signed it
+    let trusted_root = match root {
+        Some(r) => r.to_lowercase(),
+        None => {
+            let trusted = verify::trusted_public_key(state_dir, pubkey)
+                .context("No trusted key to check the checkpoint root against; pass --pubkey or --root")?;
+            parse_public_key(&trusted)?;
+            if let Some(problem) = checkpoint.signature_problem(&trusted) {
+                eprintln!("PROOF INVALID: {problem}; pass a root you trust with --root");
+                std::process::exit(1);
+            }
+            checkpoint.root.to_lowercase()
         }
+    };
 
-        expected_prev_hash = stored_hash;
-    }
-
-    if errors == 0 {
-        println!("Ledger verified: {count} events, all hashes valid, chain intact.");
-    } else {
-        eprintln!("LEDGER CORRUPTION: {errors} error(s) found in {count} events!");
+    if folded_root != trusted_root {
+        eprintln!("PROOF INVALID: path folds to {folded_root}, trusted root is {trusted_root}");
         std::process::exit(1);
     }
 
+    println!(
+        "Proof valid: event #{} is included in checkpoint #{} (events {}..={}), root {trusted_root}",
+        event.id, checkpoint.id, checkpoint.first_event_id, checkpoint.last_event_id,
+    );
+    if redacted {
+        println!("Its payload has been redacted: only the digest of the original is proven.");
+    }
     Ok(())
 }
 
-fn cmd_stats(state_dir: &PathBuf) -> Result<()> {
-    let conn = open_ledger(state_dir)?;
-
-    let total: i64 = conn.query_row("SELECT COUNT(*) FROM events", [], |row| row.get(0))?;
+/// Check a proof's event body on its own. The hash commits to the payload digest rather
+/// than the payload, so the payload must hash to that digest (or be a redaction tombstone
+/// for it). Returns whether the payload is redacted, or the reason the proof is invalid.
+fn check_proof_event(event: &Event) -> std::result::Result<bool, String> {
+    let mut redacted = false;
+    if let Some(ref digest) = event.payload_digest {
+        if chain::payload_digest(&event.payload) != *digest {
+            if Tombstone::parse(&event.payload).is_none_or(|t| t.payload_digest != *digest) {
+                return Err(format!("payload does not match its committed digest {digest}"));
+            }
+            redacted = true;
+        }
+    }
 
-    let first_ts: Option<String> = conn
-        .query_row("SELECT ts FROM events ORDER BY id ASC LIMIT 1", [], |row| {
-            row.get(0)
-        })
-        .ok();
+    let computed = chain::event_hash(event);
+    if computed != event.hash {
+        return Err(format!("hash mismatch (computed={computed}, claimed={})", event.hash));
+    }
+    Ok(redacted)
+}
 
-    let last_ts: Option<String> = conn
-        .query_row(
-            "SELECT ts FROM events ORDER BY id DESC LIMIT 1",
-            [],
-            |row| row.get(0),
-        )
-        .ok();
+fn cmd_stats(
+    state_dir: &PathBuf,
+    group_by: GroupBy,
+    bucket: Option<Bucket>,
+    event_type: Option<String>,
+    since: Option<String>,
+    until: Option<String>,
+) -> Result<()> {
+    let conn = open_ledger(state_dir)?;
+    let filter = EventFilter { event_type, since, until, ..Default::default() };
+    let group_by = match group_by {
+        GroupBy::Type => StatsGroup::Type,
+        GroupBy::Actor => StatsGroup::Actor,
+        GroupBy::VerifiedActor => StatsGroup::VerifiedActor,
+    };
+    let bucket = bucket.map(|bucket| match bucket {
+        Bucket::Hour => StatsBucket::Hour,
+        Bucket::Day => StatsBucket::Day,
+        Bucket::Week => StatsBucket::Week,
+    });
+    let stats = query::stats(&conn, &filter, group_by, bucket)?;
 
     println!("Ledger statistics:");
-    println!("  Total events: {total}");
-    if let Some(ts) = first_ts {
+    println!("  Total events: {}", stats.total);
+    if let Some(ts) = stats.first_ts {
         println!("  First event:  {ts}");
     }
-    if let Some(ts) = last_ts {
+    if let Some(ts) = stats.last_ts {
         println!("  Last event:   {ts}");
     }
 
-    // Event type breakdown
-    let mut stmt = conn.prepare(
-        "SELECT type, COUNT(*) as cnt FROM events GROUP BY type ORDER BY cnt DESC",
-    )?;
-    let type_rows = stmt.query_map([], |row| {
-        Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
-    })?;
+    let label = match group_by {
+        StatsGroup::Type => "type",
+        StatsGroup::Actor => "actor",
+        StatsGroup::VerifiedActor => "verified actor",
+    };
+    println!("  By {label}:");
+    for group in &stats.groups {
+        println!("    {}: {}", group.key, group.count);
+    }
+
+    if let Some(bucket) = bucket {
+        let name = match bucket {
+            StatsBucket::Hour => "hour",
+            StatsBucket::Day => "day",
+            StatsBucket::Week => "week",
+        };
+        println!("  Per {name} (by {label}):");
+        for b in &stats.histogram {
+            let counts: Vec<String> = b.counts.iter().map(|(k, n)| format!("{k}={n}")).collect();
+            println!("    {}  total={}  {}", b.start, b.total, counts.join(" "));
+        }
+    }
 
-    println!("  By type:");
-    for row in type_rows {
-        let (event_type, cnt) = row?;
-        println!("    {event_type}: {cnt}");
+    if stats.archived > 0 {
+        println!("  Archived events (not counted above): {}", stats.archived);
     }
 
     // DB file size
@@ -240,7 +475,17 @@ fn cmd_stats(state_dir: &PathBuf) -> Result<()> {
     Ok(())
 }
 
-fn cmd_health(socket: &PathBuf) -> Result<()> {
+fn cmd_health(socket: &Path) -> Result<()> {
+    let (_, body) = agentd_get(socket, "/health")?;
+    let val: serde_json::Value = serde_json::from_str(&body)
+        .with_context(|| format!("Failed to parse health response: {body}"))?;
+
+    println!("{}", serde_json::to_string_pretty(&val)?);
+    Ok(())
+}
+
+/// GET `path` from agentd over its Unix socket, returning the status code and body.
+fn agentd_get(socket: &Path, path: &str) -> Result<(u16, String)> {
     use std::io::{Read, Write};
     use std::os::unix::net::UnixStream;
 
@@ -255,22 +500,68 @@ fn cmd_health(socket: &PathBuf) -> Result<()> {
         .with_context(|| format!("Failed to connect to agentd at {}", socket.display()))?;
 
     // HTTP/1.0 so server closes connection after response (no keep-alive needed)
-    stream.write_all(b"GET /health HTTP/1.0\r\nHost: localhost\r\n\r\n")
-        .context("Failed to send health request")?;
+    stream.write_all(format!("GET {path} HTTP/1.0\r\nHost: localhost\r\n\r\n").as_bytes())
+        .with_context(|| format!("Failed to send request for {path}"))?;
 
     let mut response = String::new();
     stream.read_to_string(&mut response)
-        .context("Failed to read health response")?;
-
-    // Strip HTTP headers — body starts after first blank line
+        .with_context(|| format!("Failed to read response for {path}"))?;
+
+    // Status line is `HTTP/1.x <code> <reason>`; body starts after first blank line
+    let status = response
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .with_context(|| format!("Malformed HTTP response from agentd for {path}"))?;
     let body = response
         .split_once("\r\n\r\n")
         .map(|(_, b)| b)
         .unwrap_or(&response);
+    Ok((status, body.to_string()))
+}
 
-    let val: serde_json::Value = serde_json::from_str(body)
-        .with_context(|| format!("Failed to parse health response: {body}"))?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proof_event(payload: &str) -> Event {
+        let mut event = Event {
+            id: 7,
+            ts: "2026-01-01T00:00:00.000Z".to_string(),
+            event_type: "test.event".to_string(),
+            actor: "tester".to_string(),
+            payload: payload.to_string(),
+            prev_hash: "00".to_string(),
+            hash: String::new(),
+            verified_actor: None,
+            payload_digest: Some(chain::payload_digest(payload)),
+        };
+        event.hash = chain::event_hash(&event);
+        event
+    }
 
-    println!("{}", serde_json::to_string_pretty(&val)?);
-    Ok(())
+    #[test]
+    fn test_proof_rejects_tampered_payload() {
+        let event = proof_event("rebooted web-1");
+        assert_eq!(check_proof_event(&event), Ok(false));
+
+        // The hash only covers the digest, so a swapped payload must be caught against it
+        let mut tampered = event.clone();
+        tampered.payload = "rebooted db-1".into();
+        let problem = check_proof_event(&tampered).unwrap_err();
+        assert!(problem.contains("does not match its committed digest"), "{problem}");
+
+        let mut redacted = event.clone();
+        redacted.payload = serde_json::to_string(&Tombstone {
+            redacted: true,
+            payload_digest: event.payload_digest.clone().unwrap(),
+            redaction_event_id: 9,
+        })
+        .unwrap();
+        assert_eq!(check_proof_event(&redacted), Ok(true));
+
+        let mut forged = event;
+        forged.actor = "someone-else".into();
+        assert!(check_proof_event(&forged).unwrap_err().contains("hash mismatch"));
+    }
 }
@@ -0,0 +1,155 @@
+use anyhow::Result;
+use osmoda_ledger::chain::{self, VerifyCheck};
+use osmoda_ledger::{archive, signing};
+use rusqlite::Connection;
+use std::path::{Path, PathBuf};
+
+use crate::{open_ledger, parse_public_key};
+
+pub fn label(check: VerifyCheck) -> &'static str {
+    match check {
+        VerifyCheck::PrevHashMismatch => "CHAIN BREAK",
+        VerifyCheck::HashMismatch => "HASH MISMATCH",
+        VerifyCheck::IdGap => "ID GAP",
+        VerifyCheck::NonMonotonicTimestamp => "TIMESTAMP REGRESSION",
+        VerifyCheck::SegmentMismatch => "SEGMENT MISMATCH",
+        VerifyCheck::WatermarkMismatch => "WATERMARK MISMATCH",
+        VerifyCheck::PayloadDigestMismatch => "PAYLOAD MISMATCH",
+        VerifyCheck::CheckpointMismatch => "CHECKPOINT MISMATCH",
+        VerifyCheck::CheckpointMissing => "CHECKPOINT MISSING",
+    }
+}
+
+pub fn cmd_verify_ledger(
+    state_dir: &PathBuf,
+    pubkey: Option<String>,
+    checkpoint_every: i64,
+    full: bool,
+    json: bool,
+) -> Result<()> {
+    let conn = open_ledger(state_dir)?;
+    let trusted = trusted_public_key(state_dir, pubkey);
+    if let Some(ref key) = trusted {
+        parse_public_key(key)?;
+    }
+
+    // Start from the watermark only when its signature checks out against the trusted key
+    let watermark = if full {
+        None
+    } else {
+        trusted_watermark(&conn, trusted.as_deref(), json)?
+    };
+    let from_id = watermark.as_ref().map(|w| w.0).unwrap_or(0);
+
+    let archive_dir = state_dir.join(archive::ARCHIVE_DIR);
+    let anchor = watermark.as_ref().map(|(id, hash)| (*id, hash.as_str()));
+    let mut walker = chain::walk(&conn, Some(&archive_dir), anchor, trusted.as_deref())?;
+    walker.require_checkpoint_every(checkpoint_every);
+    let report = walker.finish(watermark.is_none(), from_id);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        if !report.ok {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    for issue in &report.issues {
+        eprintln!("{} at event #{}: {}", label(issue.check), issue.event_id, issue.detail);
+    }
+
+    let count = report.events_checked;
+    if report.ok {
+        match watermark {
+            Some((wm_id, _)) => println!(
+                "Ledger verified from signed watermark #{wm_id}: {count} events, all hashes valid, chain intact (use --full to rehash everything)."
+            ),
+            None => println!("Ledger verified: {count} events, all hashes valid, chain intact."),
+        }
+        match report.signed_checkpoints_checked {
+            Some(n) => println!("Signed checkpoints verified: {n}."),
+            None => println!("Signed checkpoints NOT checked: no trusted public key (pass --pubkey)."),
+        }
+    } else {
+        eprintln!("LEDGER CORRUPTION: {} error(s) found in {count} events!", report.issue_count);
+        if let (Some(first), Some(range)) = (&report.first_broken, &report.untrusted) {
+            eprintln!(
+                "First broken event: #{} ({}). Events #{}..#{} can no longer be trusted.",
+                first.event_id,
+                label(first.check),
+                range.first_event_id,
+                range.last_event_id
+            );
+        }
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// The trusted agentd signing key: `--pubkey`, else <state-dir>/ledger-signing.pub,
+/// with a warning that the file is only as trustworthy as the ledger next to it.
+pub fn trusted_public_key(state_dir: &Path, pubkey: Option<String>) -> Option<String> {
+    if let Some(key) = pubkey {
+        return Some(key.trim().to_lowercase());
+    }
+    let path = state_dir.join("ledger-signing.pub");
+    let key = std::fs::read_to_string(&path).ok()?;
+    eprintln!(
+        "WARNING: no --pubkey given. Trusting {}, which sits next to the ledger: \
+         whoever can rewrite the ledger can replace it. Pass the key you recorded when agentd was set up.",
+        path.display()
+    );
+    Some(key.trim().to_lowercase())
+}
+
+/// agentd's "verified up to" watermark as (event id, hash), if it is signed by the trusted key.
+/// Without a trusted key the watermark cannot be authenticated, so it is ignored.
+fn trusted_watermark(conn: &Connection, trusted: Option<&str>, quiet: bool) -> Result<Option<(i64, String)>> {
+    let Some(trusted) = trusted else {
+        return Ok(None);
+    };
+    let has_table: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'verify_state'",
+        [],
+        |row| row.get(0),
+    )?;
+    if has_table == 0 {
+        return Ok(None);
+    }
+
+    let row = conn.query_row(
+        "SELECT event_id, hash, verified_at, public_key, signature FROM verify_state WHERE id = 1",
+        [],
+        |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, Option<String>>(4)?,
+            ))
+        },
+    );
+    let (event_id, hash, verified_at, public_key, signature) = match row {
+        Ok(r) => r,
+        Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    let (Some(public_key), Some(signature)) = (public_key, signature) else {
+        if !quiet {
+            eprintln!("Verification watermark is unsigned — falling back to a full verify.");
+        }
+        return Ok(None);
+    };
+    let message = signing::watermark_message(event_id, &hash, &verified_at);
+    if public_key.to_lowercase() != trusted || !signing::verify_signature(trusted, &message, &signature) {
+        if !quiet {
+            eprintln!("Verification watermark is not signed by the trusted key — falling back to a full verify.");
+        }
+        return Ok(None);
+    }
+    Ok(Some((event_id, hash)))
+}
@@ -114,12 +114,11 @@ pub async fn agent_card_generate_handler(
     })?;
 
     // Log to ledger
-    let ledger = state.ledger.lock().await;
-    if let Err(e) = ledger.append(
+    if let Err(e) = state.ledger.append(
         "agent.card.generate",
         "agentd",
         &json!({"name": card.name, "services": card.services.len()}).to_string(),
-    ) {
+    ).await {
         tracing::warn!(error = %e, "failed to log agent card generation");
     }
 
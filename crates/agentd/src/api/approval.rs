@@ -1,9 +1,15 @@
-use axum::extract::{Path, State};
+use std::time::Duration;
+
+use axum::extract::{ConnectInfo, Path, Query, State};
 use axum::http::StatusCode;
 use axum::Json;
 use serde::{Deserialize, Serialize};
 
-use crate::state::SharedState;
+use crate::approval::{ApprovalGate, ApprovalStatus, ApprovalVote, PendingApproval, Requester, TokenError, VoteError};
+use crate::peer::PeerIdentity;
+use crate::policy::{Decision, BUILTIN_RULE};
+use crate::sandbox::CapabilityToken;
+use crate::state::{AppState, SharedState};
 
 #[derive(Debug, Deserialize)]
 pub struct ApprovalRequest {
@@ -11,6 +17,8 @@ pub struct ApprovalRequest {
     pub actor: Option<String>,
     pub reason: String,
     pub ttl_secs: Option<i64>,
+    /// Path the operation acts on, for policy rules that match by path.
+    pub target: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -18,6 +26,32 @@ pub struct ApprovalDecision {
     pub decided_by: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ExecuteRequest {
+    pub command: String,
+    pub target: Option<String>,
+    pub actor: Option<String>,
+    /// The approved request's execution token.
+    pub approval_token: CapabilityToken,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExecuteResponse {
+    pub approval_id: String,
+    pub token_id: String,
+    pub status: String,
+}
+
+/// Longest a single `/approval/{id}/wait` call may block.
+const MAX_WAIT_SECS: u64 = 300;
+const DEFAULT_WAIT_SECS: u64 = 30;
+
+#[derive(Debug, Deserialize)]
+pub struct WaitQuery {
+    /// Seconds to wait for a decision (default 30, capped at 300).
+    pub timeout: Option<u64>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct ApprovalResponse {
     pub id: String,
@@ -30,10 +64,22 @@ pub struct ApprovalResponse {
     pub decided_at: Option<String>,
     pub decided_by: Option<String>,
     pub is_destructive: bool,
+    /// Policy rule that decided the request (`builtin`, `default` or a rule name).
+    pub rule: Option<String>,
+    pub required_approvals: u32,
+    pub approver_group: Option<String>,
+    pub eligible_approvers: Vec<String>,
+    pub approvers: Vec<String>,
+    pub votes: Vec<ApprovalVote>,
+    pub target: Option<String>,
+    /// Single-use token for running exactly the approved command and target; shown
+    /// only to the requester's verified identity once the request is approved, until
+    /// it is used or expires.
+    pub execution_token: Option<CapabilityToken>,
 }
 
-impl From<crate::approval::PendingApproval> for ApprovalResponse {
-    fn from(a: crate::approval::PendingApproval) -> Self {
+impl From<PendingApproval> for ApprovalResponse {
+    fn from(a: PendingApproval) -> Self {
         Self {
             id: a.id,
             command: a.command,
@@ -45,13 +91,59 @@ impl From<crate::approval::PendingApproval> for ApprovalResponse {
             decided_at: a.decided_at,
             decided_by: a.decided_by,
             is_destructive: true,
+            rule: a.rule,
+            required_approvals: a.required_approvals,
+            approver_group: a.approver_group,
+            eligible_approvers: a.eligible_approvers,
+            approvers: a.approvers,
+            votes: a.votes,
+            target: a.target,
+            execution_token: None,
         }
     }
 }
 
-/// POST /approval/request — request approval for a destructive operation.
+/// `approval` as a response, with its execution token if it has an unused one and
+/// `peer` is the requester.
+fn with_execution_token(gate: &ApprovalGate, peer: &PeerIdentity, approval: PendingApproval) -> ApprovalResponse {
+    let execution_token = if approval.status == ApprovalStatus::Approved && approval.held_by(peer.principal().as_deref()) {
+        gate.execution_token(&approval.id).unwrap_or_else(|e| {
+            tracing::warn!(approval_id = %approval.id, error = %e, "failed to load execution token");
+            None
+        })
+    } else {
+        None
+    };
+    ApprovalResponse { execution_token, ..approval.into() }
+}
+
+/// Mint the execution token for a request that has just been approved; the requester
+/// collects it from `GET /approval/{id}`. Without the sandbox engine there is nothing
+/// to sign it with, so none is issued.
+async fn issue_execution_token(state: &AppState, gate: &ApprovalGate, approval: &PendingApproval) -> anyhow::Result<()> {
+    let Some(engine) = state.sandbox_engine.as_ref() else {
+        return Ok(());
+    };
+    let token = gate.issue_execution_token(engine, approval)?;
+    let payload = serde_json::json!({
+        "token_id": token.id,
+        "granted_to": token.granted_to,
+        "permissions": token.permissions,
+        "ttl_secs": crate::approval::EXECUTION_TOKEN_TTL_SECS,
+        "approval_id": approval.id,
+    });
+    if let Err(e) = state.ledger.append("capability.mint", "agentd", &payload.to_string()).await {
+        tracing::error!(approval_id = %approval.id, error = %e, "failed to log execution token to ledger");
+    }
+    Ok(())
+}
+
+/// POST /approval/request — ask the gate whether an operation may run. The approval
+/// policy (or the built-in destructive check) either allows it outright, denies it, or
+/// records a pending approval that needs one or more approvers.
 pub async fn approval_request_handler(
     State(state): State<SharedState>,
+    ConnectInfo(peer): ConnectInfo<PeerIdentity>,
     Json(req): Json<ApprovalRequest>,
 ) -> Result<(StatusCode, Json<ApprovalResponse>), (StatusCode, Json<serde_json::Value>)> {
     let gate = state.approval_gate.as_ref().ok_or_else(|| {
@@ -62,41 +154,103 @@ pub async fn approval_request_handler(
     })?;
 
     let actor = req.actor.as_deref().unwrap_or("agent");
-    let is_destructive = gate.is_destructive(&req.command);
-
-    if !is_destructive {
-        // Not destructive — return immediately with auto-approved status
-        return Ok((
-            StatusCode::OK,
-            Json(ApprovalResponse {
-                id: String::new(),
-                command: req.command,
-                actor: actor.to_string(),
-                reason: req.reason,
-                status: "auto_approved".to_string(),
-                created_at: chrono::Utc::now().to_rfc3339(),
-                expires_at: String::new(),
-                decided_at: Some(chrono::Utc::now().to_rfc3339()),
-                decided_by: Some("system".to_string()),
-                is_destructive: false,
-            }),
+    let verified_actor = peer.actor();
+    if !peer.may_claim(actor) {
+        tracing::warn!(claimed = actor, verified = %verified_actor, pid = ?peer.pid, "rejected forged approval actor");
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(serde_json::json!({"error": format!("actor '{actor}' is reserved for that daemon; this connection is '{verified_actor}'")})),
         ));
     }
 
-    match gate.request_approval(&req.command, actor, &req.reason, req.ttl_secs) {
+    let decided = gate.decide(&req.command, peer.principal().as_deref(), req.target.as_deref());
+
+    // Record which rule allowed or denied outright; pending approvals record it themselves
+    match decided.decision {
+        Decision::Allow | Decision::Deny if decided.rule != BUILTIN_RULE => {
+            let payload = serde_json::json!({
+                "command": req.command,
+                "target": req.target,
+                "decision": decided.decision.as_str(),
+                "rule": decided.rule,
+            });
+            if let Err(e) = state.ledger.append_as(
+                "approval.policy",
+                actor,
+                Some(&verified_actor),
+                &payload.to_string(),
+            ).await {
+                tracing::error!(error = %e, "failed to log approval policy decision to ledger");
+            }
+        }
+        _ => {}
+    }
+
+    match decided.decision {
+        Decision::Allow => {
+            // Allowed — return immediately with auto-approved status
+            return Ok((
+                StatusCode::OK,
+                Json(ApprovalResponse {
+                    id: String::new(),
+                    command: req.command,
+                    actor: actor.to_string(),
+                    reason: req.reason,
+                    status: "auto_approved".to_string(),
+                    created_at: chrono::Utc::now().to_rfc3339(),
+                    expires_at: String::new(),
+                    decided_at: Some(chrono::Utc::now().to_rfc3339()),
+                    decided_by: Some("system".to_string()),
+                    is_destructive: false,
+                    rule: Some(decided.rule),
+                    required_approvals: 0,
+                    approver_group: None,
+                    eligible_approvers: Vec::new(),
+                    approvers: Vec::new(),
+                    votes: Vec::new(),
+                    target: req.target,
+                    execution_token: None,
+                }),
+            ));
+        }
+        Decision::Deny => {
+            return Err((
+                StatusCode::FORBIDDEN,
+                Json(serde_json::json!({
+                    "error": format!("denied by approval policy rule '{}'", decided.rule),
+                    "rule": decided.rule,
+                })),
+            ));
+        }
+        Decision::RequireApproval { .. } => {}
+    }
+
+    let principal = peer.principal();
+    let requester = Requester { actor, principal: principal.as_deref() };
+    match gate.request_approval_for(&decided, &req.command, req.target.as_deref(), requester, &req.reason, req.ttl_secs) {
         Ok(approval) => {
             // Log to ledger
             let payload = serde_json::json!({
                 "approval_id": approval.id,
                 "command": approval.command,
                 "reason": approval.reason,
+                "rule": approval.rule,
+                "required_approvals": approval.required_approvals,
+                "approver_group": approval.approver_group,
+                "target": approval.target,
             });
-            let ledger = state.ledger.lock().await;
-            let _ = ledger.append(
+            if let Err(e) = state.ledger.append_as(
                 "approval.requested",
                 actor,
+                Some(&verified_actor),
                 &payload.to_string(),
-            );
+            ).await {
+                tracing::error!(approval_id = %approval.id, error = %e, "failed to log approval request to ledger");
+            }
+
+            if let Some(notifier) = &state.approval_notifier {
+                notifier.notify_requested(&state.ledger, &approval);
+            }
 
             Ok((StatusCode::CREATED, Json(approval.into())))
         }
@@ -127,9 +281,12 @@ pub async fn approval_pending_handler(
     }
 }
 
-/// POST /approval/{id}/approve — approve a pending request.
+/// POST /approval/{id}/approve — approve a pending request. Requests that need several
+/// approvers stay pending (and log `approval.vote`) until enough distinct eligible ones
+/// approve within the expiry window.
 pub async fn approval_approve_handler(
     State(state): State<SharedState>,
+    ConnectInfo(peer): ConnectInfo<PeerIdentity>,
     Path(id): Path<String>,
     Json(decision): Json<ApprovalDecision>,
 ) -> Result<Json<ApprovalResponse>, (StatusCode, Json<serde_json::Value>)> {
@@ -141,34 +298,64 @@ pub async fn approval_approve_handler(
     })?;
 
     let decided_by = decision.decided_by.as_deref().unwrap_or("user");
+    let principal = verify_approver(&peer, decided_by)?;
+    let verified_by = peer.actor();
 
-    match gate.approve(&id, decided_by) {
+    match gate.approve(&id, decided_by, &principal) {
         Ok(approval) => {
             // Log to ledger
+            let event_type = if approval.status == ApprovalStatus::Approved {
+                "approval.approved"
+            } else {
+                "approval.vote"
+            };
             let payload = serde_json::json!({
                 "approval_id": id,
                 "command": approval.command,
                 "decided_by": decided_by,
+                "rule": approval.rule,
+                "approver_group": approval.approver_group,
+                "approvers": approval.approvers,
+                "required_approvals": approval.required_approvals,
             });
-            let ledger = state.ledger.lock().await;
-            let _ = ledger.append(
-                "approval.approved",
+            let logged = state.ledger.append_as(
+                event_type,
                 decided_by,
+                Some(&verified_by),
                 &payload.to_string(),
-            );
+            ).await;
+            if let Err(e) = logged {
+                tracing::error!(approval_id = %id, error = %e, "failed to log {event_type} to ledger");
+                // A vote that is not on the ledger does not count
+                if let Err(e) = gate.retract_approval(&id, &principal) {
+                    tracing::error!(approval_id = %id, error = %e, "failed to retract unlogged approval vote");
+                }
+                return Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(serde_json::json!({"error": "failed to record the approval in the ledger; the vote was not counted"})),
+                ));
+            }
 
+            if approval.status == ApprovalStatus::Approved {
+                if let Err(e) = issue_execution_token(&state, gate, &approval).await {
+                    tracing::error!(approval_id = %id, error = %e, "failed to issue execution token");
+                    return Err((
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(serde_json::json!({"error": format!("approved, but failed to issue the execution token: {e}")})),
+                    ));
+                }
+            }
             Ok(Json(approval.into()))
         }
-        Err(e) => Err((
-            StatusCode::NOT_FOUND,
-            Json(serde_json::json!({"error": e.to_string()})),
-        )),
+        Err(e) => Err(vote_error(e)),
     }
 }
 
-/// POST /approval/{id}/deny — deny a pending request.
+/// POST /approval/{id}/deny — deny a pending request. One denial from an eligible
+/// approver (or the requester) denies it.
 pub async fn approval_deny_handler(
     State(state): State<SharedState>,
+    ConnectInfo(peer): ConnectInfo<PeerIdentity>,
     Path(id): Path<String>,
     Json(decision): Json<ApprovalDecision>,
 ) -> Result<Json<ApprovalResponse>, (StatusCode, Json<serde_json::Value>)> {
@@ -180,33 +367,168 @@ pub async fn approval_deny_handler(
     })?;
 
     let decided_by = decision.decided_by.as_deref().unwrap_or("user");
+    let principal = verify_approver(&peer, decided_by)?;
+    let verified_by = peer.actor();
 
-    match gate.deny(&id, decided_by) {
+    match gate.deny(&id, decided_by, &principal) {
         Ok(approval) => {
             let payload = serde_json::json!({
                 "approval_id": id,
                 "command": approval.command,
                 "decided_by": decided_by,
+                "rule": approval.rule,
+                "approver_group": approval.approver_group,
+                "approvers": approval.approvers,
             });
-            let ledger = state.ledger.lock().await;
-            let _ = ledger.append(
+            if let Err(e) = state.ledger.append_as(
                 "approval.denied",
                 decided_by,
+                Some(&verified_by),
                 &payload.to_string(),
-            );
+            ).await {
+                tracing::error!(approval_id = %id, error = %e, "failed to log approval denial to ledger");
+            }
 
             Ok(Json(approval.into()))
         }
-        Err(e) => Err((
-            StatusCode::NOT_FOUND,
-            Json(serde_json::json!({"error": e.to_string()})),
-        )),
+        Err(e) => Err(vote_error(e)),
+    }
+}
+
+/// The principal votes from this connection are recorded under, or 403 if it claims
+/// a daemon name it is not or agentd cannot tell who it is.
+fn verify_approver(peer: &PeerIdentity, decided_by: &str) -> Result<String, (StatusCode, Json<serde_json::Value>)> {
+    let verified_by = peer.actor();
+    if !peer.may_claim(decided_by) {
+        tracing::warn!(claimed = decided_by, verified = %verified_by, pid = ?peer.pid, "rejected forged approver");
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(serde_json::json!({"error": format!("approver '{decided_by}' is reserved for that daemon; this connection is '{verified_by}'")})),
+        ));
+    }
+    peer.principal().ok_or_else(|| {
+        (
+            StatusCode::FORBIDDEN,
+            Json(serde_json::json!({"error": "cannot verify who this connection is; approvals need a known uid"})),
+        )
+    })
+}
+
+fn vote_error(e: VoteError) -> (StatusCode, Json<serde_json::Value>) {
+    let status = match &e {
+        VoteError::NotFound => StatusCode::NOT_FOUND,
+        VoteError::SelfApproval(_) | VoteError::UnverifiedRequester | VoteError::NotEligible { .. } => {
+            StatusCode::FORBIDDEN
+        }
+        VoteError::NotPending(_) | VoteError::Expired | VoteError::AlreadyVoted(_) => StatusCode::CONFLICT,
+        VoteError::Storage(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, Json(serde_json::json!({"error": e.to_string()})))
+}
+
+/// Use up `token` to run `command` on `target` for the connection `peer`, and log
+/// `approval.executed`. Fails unless the token was issued for exactly this command
+/// and target and `peer` is the approval's requester.
+pub(crate) async fn spend_execution_token(
+    state: &AppState,
+    gate: &ApprovalGate,
+    peer: &PeerIdentity,
+    actor: &str,
+    token: &CapabilityToken,
+    command: &str,
+    target: Option<&str>,
+) -> Result<PendingApproval, (StatusCode, Json<serde_json::Value>)> {
+    let engine = state.sandbox_engine.as_ref().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({"error": "sandbox engine not enabled; execution tokens cannot be verified"})),
+        )
+    })?;
+    let Some(principal) = peer.principal() else {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(serde_json::json!({"error": "cannot verify who this connection is; execution tokens need a known uid"})),
+        ));
+    };
+    let approval = gate
+        .consume_execution_token(engine, token, command, target, &principal)
+        .map_err(|e| {
+            let status = match &e {
+                TokenError::Storage(_) => StatusCode::INTERNAL_SERVER_ERROR,
+                TokenError::Consumed(_) => StatusCode::CONFLICT,
+                _ => StatusCode::FORBIDDEN,
+            };
+            (status, Json(serde_json::json!({"error": e.to_string()})))
+        })?;
+
+    let payload = serde_json::json!({
+        "approval_id": approval.id,
+        "command": command,
+        "target": target,
+        "token_id": token.id,
+    });
+    // The token is spent either way; an execution the ledger cannot show must not run
+    state.ledger.append_as(
+        "approval.executed",
+        actor,
+        Some(&peer.actor()),
+        &payload.to_string(),
+    ).await.map_err(|e| {
+        tracing::error!(approval_id = %approval.id, error = %e, "failed to log approval execution to ledger");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": "failed to record the execution in the ledger; the token is used up"})),
+        )
+    })?;
+    Ok(approval)
+}
+
+/// POST /approval/execute — spend an execution token on a command the caller runs
+/// itself (the bridge's `shell_exec`), rather than in the sandbox. The command is
+/// still checked against the policy, so one denied since the approval is refused.
+pub async fn approval_execute_handler(
+    State(state): State<SharedState>,
+    ConnectInfo(peer): ConnectInfo<PeerIdentity>,
+    Json(req): Json<ExecuteRequest>,
+) -> Result<Json<ExecuteResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let gate = state.approval_gate.as_ref().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({"error": "approval gate not enabled"})),
+        )
+    })?;
+
+    let actor = req.actor.as_deref().unwrap_or("agent");
+    if !peer.may_claim(actor) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(serde_json::json!({"error": format!("actor '{actor}' is reserved for that daemon; this connection is '{}'", peer.actor())})),
+        ));
     }
+    let decided = gate.decide(&req.command, peer.principal().as_deref(), req.target.as_deref());
+    if decided.decision == Decision::Deny {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(serde_json::json!({
+                "error": format!("denied by approval policy rule '{}'", decided.rule),
+                "rule": decided.rule,
+            })),
+        ));
+    }
+
+    let approval =
+        spend_execution_token(&state, gate, &peer, actor, &req.approval_token, &req.command, req.target.as_deref()).await?;
+    Ok(Json(ExecuteResponse {
+        approval_id: approval.id,
+        token_id: req.approval_token.id,
+        status: "consumed".to_string(),
+    }))
 }
 
 /// GET /approval/{id} — check status of an approval request.
 pub async fn approval_check_handler(
     State(state): State<SharedState>,
+    ConnectInfo(peer): ConnectInfo<PeerIdentity>,
     Path(id): Path<String>,
 ) -> Result<Json<ApprovalResponse>, (StatusCode, Json<serde_json::Value>)> {
     let gate = state.approval_gate.as_ref().ok_or_else(|| {
@@ -217,7 +539,37 @@ pub async fn approval_check_handler(
     })?;
 
     match gate.check_approval(&id) {
-        Ok(Some(approval)) => Ok(Json(approval.into())),
+        Ok(Some(approval)) => Ok(Json(with_execution_token(gate, &peer, approval))),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({"error": "approval not found"})),
+        )),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": e.to_string()})),
+        )),
+    }
+}
+
+/// GET /approval/{id}/wait?timeout= — block until the request is approved, denied or
+/// expires, or the timeout passes. Returns the request as it then stands, so a
+/// `pending` status means the caller should wait again.
+pub async fn approval_wait_handler(
+    State(state): State<SharedState>,
+    ConnectInfo(peer): ConnectInfo<PeerIdentity>,
+    Path(id): Path<String>,
+    Query(params): Query<WaitQuery>,
+) -> Result<Json<ApprovalResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let gate = state.approval_gate.as_ref().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({"error": "approval gate not enabled"})),
+        )
+    })?;
+
+    let timeout = Duration::from_secs(params.timeout.unwrap_or(DEFAULT_WAIT_SECS).min(MAX_WAIT_SECS));
+    match gate.wait_for_decision(&id, timeout).await {
+        Ok(Some(approval)) => Ok(Json(with_execution_token(gate, &peer, approval))),
         Ok(None) => Err((
             StatusCode::NOT_FOUND,
             Json(serde_json::json!({"error": "approval not found"})),
@@ -35,8 +35,7 @@ pub async fn backup_create_handler(
 
     // First, checkpoint the SQLite WAL for consistent snapshot
     {
-        let ledger = state.ledger.lock().await;
-        if let Err(e) = ledger.flush() {
+        if let Err(e) = state.ledger.write(|ledger| ledger.flush()).await {
             tracing::warn!(error = %e, "WAL checkpoint failed before backup, continuing anyway");
         }
     }
@@ -73,8 +72,7 @@ pub async fn backup_create_handler(
 
     // Log backup event
     {
-        let ledger = state.ledger.lock().await;
-        let _ = ledger.append(
+        if let Err(e) = state.ledger.append(
             "backup.create",
             "agentd",
             &serde_json::json!({
@@ -82,7 +80,9 @@ pub async fn backup_create_handler(
                 "path": backup_path,
                 "size_bytes": size_bytes,
             }).to_string(),
-        );
+        ).await {
+            tracing::error!(error = %e, "failed to log backup to ledger");
+        }
     }
 
     // Prune old backups (keep MAX_BACKUPS most recent)
@@ -176,15 +176,16 @@ pub async fn backup_restore_handler(
 
     // Log restore intent
     {
-        let ledger = state.ledger.lock().await;
-        let _ = ledger.append(
+        if let Err(e) = state.ledger.append(
             "backup.restore",
             "agentd",
             &serde_json::json!({
                 "backup_id": body.backup_id,
                 "path": backup_path,
             }).to_string(),
-        );
+        ).await {
+            tracing::error!(error = %e, "failed to log backup restore to ledger");
+        }
     }
 
     // Extract backup over the state directory
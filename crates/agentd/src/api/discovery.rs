@@ -101,13 +101,12 @@ pub async fn system_discover_handler(
 
     // Log to ledger
     {
-        let ledger = state.ledger.lock().await;
         let payload = serde_json::to_string(&json!({
             "services_found": services.len(),
             "listening_ports": total_listening_ports,
             "systemd_services": total_systemd_services,
         })).unwrap_or_default();
-        if let Err(e) = ledger.append("system.discover", "agentd", &payload) {
+        if let Err(e) = state.ledger.append("system.discover", "agentd", &payload).await {
             tracing::error!(error = %e, "failed to log discovery to ledger");
         }
     }
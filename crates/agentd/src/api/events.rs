@@ -1,35 +1,71 @@
+use std::convert::Infallible;
+
 use axum::extract::{Query, State};
+use axum::http::HeaderMap;
+use axum::response::sse::{self, KeepAlive, Sse};
 use axum::Json;
 use serde::Deserialize;
 use serde_json::Value;
+use tokio::sync::{broadcast, mpsc};
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::{Stream, StreamExt};
 
-use crate::ledger::EventFilter;
+use crate::ledger::{normalize_timestamp, Event, EventFilter, SortOrder};
+use crate::schema;
 use crate::state::SharedState;
 
+/// Events fetched per database read while a stream catches up.
+const STREAM_CATCH_UP_PAGE: i64 = 500;
+
 /// Query parameters for the events log endpoint.
 #[derive(Debug, Deserialize)]
 pub struct EventsQuery {
+    /// Exact type, or a prefix when it ends in `*` (e.g. `switch.*`).
     #[serde(rename = "type")]
     pub event_type: Option<String>,
     pub actor: Option<String>,
+    pub verified_actor: Option<String>,
     pub limit: Option<i64>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub after_id: Option<i64>,
+    pub before_id: Option<i64>,
+    pub order: Option<SortOrder>,
 }
 
+/// GET /events/log — filtered, cursor-paginated ledger events.
+/// Page by passing the last returned id as `before_id` (desc) or `after_id` (asc).
 pub async fn events_log_handler(
     State(state): State<SharedState>,
     Query(params): Query<EventsQuery>,
-) -> Result<Json<Vec<Value>>, axum::http::StatusCode> {
-    let ledger = state.ledger.lock().await;
+) -> Result<Json<Vec<Value>>, (axum::http::StatusCode, Json<Value>)> {
+    for ts in [&params.since, &params.until].into_iter().flatten() {
+        if let Err(e) = normalize_timestamp(ts) {
+            return Err((
+                axum::http::StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({"error": e.to_string()})),
+            ));
+        }
+    }
 
     let filter = EventFilter {
         event_type: params.event_type,
         actor: params.actor,
+        verified_actor: params.verified_actor,
         limit: Some(params.limit.unwrap_or(50)),
+        since: params.since,
+        until: params.until,
+        after_id: params.after_id,
+        before_id: params.before_id,
+        order: params.order,
     };
 
-    let events = ledger.query(&filter).map_err(|e| {
+    let events = state.ledger.read(move |ledger| ledger.query(&filter)).await.map_err(|e| {
         tracing::error!(error = %e, "failed to query events");
-        axum::http::StatusCode::INTERNAL_SERVER_ERROR
+        (
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": "failed to query events"})),
+        )
     })?;
 
     let events_json: Vec<Value> = events
@@ -43,9 +79,159 @@ pub async fn events_log_handler(
                 "payload": e.payload,
                 "prev_hash": e.prev_hash,
                 "hash": e.hash,
+                "verified_actor": e.verified_actor,
             })
         })
         .collect();
 
     Ok(Json(events_json))
 }
+
+/// GET /events/schemas — payload schemas for known event types, plus any
+/// types present in the ledger that have no schema (allowed, but flagged).
+pub async fn events_schemas_handler(
+    State(state): State<SharedState>,
+) -> Result<Json<Value>, axum::http::StatusCode> {
+    let unregistered = state.ledger.read(|ledger| ledger.unregistered_types()).await.map_err(|e| {
+        tracing::error!(error = %e, "failed to list unregistered event types");
+        axum::http::StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(serde_json::json!({
+        "schemas": schema::REGISTRY,
+        "unregistered_types": unregistered
+            .into_iter()
+            .map(|(event_type, count)| serde_json::json!({"type": event_type, "count": count}))
+            .collect::<Vec<_>>(),
+    })))
+}
+
+/// Query parameters for the live event stream.
+#[derive(Debug, Deserialize)]
+pub struct StreamQuery {
+    /// Only stream events whose type starts with this prefix (`switch.` or `switch.*`).
+    #[serde(rename = "type")]
+    pub event_type: Option<String>,
+    /// Only stream events whose actor starts with this prefix.
+    pub actor: Option<String>,
+    /// Resume after this ledger id; the `Last-Event-ID` header takes precedence.
+    pub last_event_id: Option<i64>,
+}
+
+/// Prefix filter shared by the catch-up and live phases of a stream.
+struct StreamFilter {
+    type_prefix: Option<String>,
+    actor_prefix: Option<String>,
+}
+
+impl StreamFilter {
+    fn new(params: &StreamQuery) -> Self {
+        let prefix = |p: &Option<String>| p.as_ref().map(|v| v.trim_end_matches('*').to_string());
+        Self {
+            type_prefix: prefix(&params.event_type),
+            actor_prefix: prefix(&params.actor),
+        }
+    }
+
+    fn matches(&self, event: &Event) -> bool {
+        self.type_prefix.as_ref().is_none_or(|p| event.event_type.starts_with(p.as_str()))
+            && self.actor_prefix.as_ref().is_none_or(|p| event.actor.starts_with(p.as_str()))
+    }
+}
+
+/// GET /events/stream — server-sent events for every ledger append.
+///
+/// Each SSE `id` is the ledger event id. Reconnecting with `Last-Event-ID`
+/// replays everything after that id from the ledger before switching to live
+/// delivery, so a consumer never misses an event.
+pub async fn events_stream_handler(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Query(params): Query<StreamQuery>,
+) -> Result<Sse<impl Stream<Item = Result<sse::Event, Infallible>>>, axum::http::StatusCode> {
+    let filter = StreamFilter::new(&params);
+    let resume_from = headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<i64>().ok())
+        .or(params.last_event_id);
+
+    // Subscribe before reading the head so nothing falls between them; events seen
+    // both ways are skipped by id
+    let live = state.ledger.subscribe();
+    let cursor = match resume_from {
+        Some(id) => id,
+        None => state.ledger.read(|ledger| ledger.head_id()).await.map_err(|e| {
+            tracing::error!(error = %e, "failed to read ledger head for stream");
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR
+        })?,
+    };
+
+    let (tx, rx) = mpsc::channel::<Event>(64);
+    tokio::spawn(stream_events(state, filter, cursor, live, tx));
+
+    let stream = ReceiverStream::new(rx).map(|event| {
+        let data = serde_json::to_string(&event).unwrap_or_default();
+        Ok(sse::Event::default().id(event.id.to_string()).data(data))
+    });
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+/// Feed one subscriber: replay from the ledger after `cursor`, then forward live
+/// appends. Falls back to the ledger whenever the live channel lags or skips ids.
+async fn stream_events(
+    state: SharedState,
+    filter: StreamFilter,
+    mut cursor: i64,
+    mut live: broadcast::Receiver<Event>,
+    tx: mpsc::Sender<Event>,
+) {
+    loop {
+        // Catch up from the ledger
+        loop {
+            let page = state
+                .ledger
+                .read(move |ledger| ledger.events_after(cursor, STREAM_CATCH_UP_PAGE))
+                .await;
+            let page = match page {
+                Ok(p) => p,
+                Err(e) => {
+                    tracing::warn!(error = %e, cursor, "event stream catch-up failed");
+                    return;
+                }
+            };
+            if page.is_empty() {
+                break;
+            }
+            for event in page {
+                cursor = event.id;
+                if filter.matches(&event) && tx.send(event).await.is_err() {
+                    return;
+                }
+            }
+        }
+
+        // Forward live appends until the channel lags or the client goes away
+        loop {
+            let received = tokio::select! {
+                r = live.recv() => r,
+                _ = tx.closed() => return,
+            };
+            match received {
+                Ok(event) if event.id <= cursor => continue,
+                Ok(event) if event.id != cursor + 1 => break,
+                Ok(event) => {
+                    cursor = event.id;
+                    if filter.matches(&event) && tx.send(event).await.is_err() {
+                        return;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::debug!(skipped, "event stream lagged, catching up from ledger");
+                    break;
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            }
+        }
+    }
+}
@@ -0,0 +1,302 @@
+use std::io::Write;
+
+use axum::body::{Body, Bytes};
+use axum::extract::{ConnectInfo, Path, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::IntoResponse;
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+use crate::approval::ApprovalStatus;
+use crate::ledger::{
+    normalize_timestamp, Event, EventFilter, InclusionProof, LedgerSegment, LedgerStats, MerkleCheckpoint, Redaction,
+    SignedCheckpoint, StatsBucket, StatsGroup, VerifyReport,
+};
+use crate::peer::PeerIdentity;
+use crate::state::SharedState;
+
+/// Bytes buffered per chunk of a streamed export.
+const EXPORT_CHUNK_BYTES: usize = 64 * 1024;
+/// Chunks queued ahead of a slow client before the exporting reader waits.
+const EXPORT_CHUNKS_IN_FLIGHT: usize = 8;
+
+/// GET /ledger/proof/{id} — Merkle inclusion proof for a single event.
+pub async fn ledger_proof_handler(
+    State(state): State<SharedState>,
+    Path(id): Path<i64>,
+) -> Result<Json<InclusionProof>, (StatusCode, Json<serde_json::Value>)> {
+    let proof = state
+        .ledger
+        .read(move |ledger| match ledger.get_event(id)? {
+            Some(_) => ledger.inclusion_proof(id).map(Some),
+            None => Ok(None),
+        })
+        .await;
+
+    match proof {
+        Ok(Some(Some(proof))) => Ok(Json(proof)),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({"error": "event not found"})),
+        )),
+        Ok(Some(None)) => Err((
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({"error": "event not yet covered by a checkpoint"})),
+        )),
+        Err(e) => {
+            tracing::error!(error = %e, event_id = id, "failed to build inclusion proof");
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": "failed to build inclusion proof"})),
+            ))
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CheckpointsQuery {
+    pub limit: Option<i64>,
+}
+
+/// GET /ledger/checkpoints — list sealed Merkle checkpoints (newest first).
+pub async fn ledger_checkpoints_handler(
+    State(state): State<SharedState>,
+    Query(params): Query<CheckpointsQuery>,
+) -> Result<Json<Vec<MerkleCheckpoint>>, StatusCode> {
+    let limit = params.limit.unwrap_or(50).min(500);
+
+    state.ledger.read(move |ledger| ledger.merkle_checkpoints(limit)).await.map(Json).map_err(|e| {
+        tracing::error!(error = %e, "failed to list merkle checkpoints");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+/// GET /ledger/signed-checkpoints — list Ed25519-signed head checkpoints (newest first).
+pub async fn ledger_signed_checkpoints_handler(
+    State(state): State<SharedState>,
+    Query(params): Query<CheckpointsQuery>,
+) -> Result<Json<Vec<SignedCheckpoint>>, StatusCode> {
+    let limit = params.limit.unwrap_or(50).min(500);
+
+    state.ledger.read(move |ledger| ledger.signed_checkpoints(limit)).await.map(Json).map_err(|e| {
+        tracing::error!(error = %e, "failed to list signed checkpoints");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+/// GET /ledger/segments — archived ledger segments (oldest first).
+pub async fn ledger_segments_handler(
+    State(state): State<SharedState>,
+) -> Result<Json<Vec<LedgerSegment>>, StatusCode> {
+    state.ledger.read(|ledger| ledger.segments()).await.map(Json).map_err(|e| {
+        tracing::error!(error = %e, "failed to list ledger segments");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StatsQuery {
+    /// type (default), actor or verified_actor.
+    #[serde(default)]
+    pub group_by: StatsGroup,
+    /// hour, day or week; omit for totals only.
+    pub bucket: Option<StatsBucket>,
+    /// Exact type, or a prefix when it ends in `*`.
+    #[serde(rename = "type")]
+    pub event_type: Option<String>,
+    pub actor: Option<String>,
+    pub verified_actor: Option<String>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+}
+
+/// GET /ledger/stats — event counts grouped by type/actor, optionally as a time histogram.
+pub async fn ledger_stats_handler(
+    State(state): State<SharedState>,
+    Query(params): Query<StatsQuery>,
+) -> Result<Json<LedgerStats>, (StatusCode, Json<serde_json::Value>)> {
+    for ts in [&params.since, &params.until].into_iter().flatten() {
+        if let Err(e) = normalize_timestamp(ts) {
+            return Err((StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": e.to_string()}))));
+        }
+    }
+
+    let filter = EventFilter {
+        event_type: params.event_type,
+        actor: params.actor,
+        verified_actor: params.verified_actor,
+        since: params.since,
+        until: params.until,
+        ..Default::default()
+    };
+
+    let (group_by, bucket) = (params.group_by, params.bucket);
+    state.ledger.read(move |ledger| ledger.stats(&filter, group_by, bucket)).await.map(Json).map_err(|e| {
+        tracing::error!(error = %e, "failed to compute ledger stats");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": "failed to compute ledger stats"})),
+        )
+    })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VerifyQuery {
+    /// Rehash from genesis instead of the last verified watermark.
+    #[serde(default)]
+    pub full: bool,
+}
+
+/// GET /ledger/verify — verify the chain and report where it breaks, if anywhere.
+pub async fn ledger_verify_handler(
+    State(state): State<SharedState>,
+    Query(params): Query<VerifyQuery>,
+) -> Result<Json<VerifyReport>, StatusCode> {
+    state.ledger.verify_report(params.full).await.map(Json).map_err(|e| {
+        tracing::error!(error = %e, "failed to verify ledger");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RedactRequest {
+    /// Approved request for the command `ledger.redact.<id>`.
+    pub approval_id: String,
+    pub reason: String,
+    pub actor: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RedactResponse {
+    /// The event as it now reads, with its tombstone payload.
+    pub event: Event,
+    /// The `ledger.redact` event recording the redaction.
+    pub redaction: Event,
+}
+
+/// POST /ledger/redact/{id} — replace an event's payload with a tombstone.
+/// Requires an approval for `ledger.redact.<id>` that has been approved.
+pub async fn ledger_redact_handler(
+    State(state): State<SharedState>,
+    ConnectInfo(peer): ConnectInfo<PeerIdentity>,
+    Path(id): Path<i64>,
+    Json(req): Json<RedactRequest>,
+) -> Result<Json<RedactResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let error = |status: StatusCode, msg: String| (status, Json(serde_json::json!({"error": msg})));
+
+    let gate = state.approval_gate.as_ref().ok_or_else(|| {
+        error(StatusCode::SERVICE_UNAVAILABLE, "approval gate not enabled".to_string())
+    })?;
+    let approval = match gate.check_approval(&req.approval_id) {
+        Ok(Some(approval)) => approval,
+        Ok(None) => return Err(error(StatusCode::FORBIDDEN, "approval not found".to_string())),
+        Err(e) => {
+            tracing::error!(error = %e, "failed to look up approval for redaction");
+            return Err(error(StatusCode::INTERNAL_SERVER_ERROR, "failed to look up approval".to_string()));
+        }
+    };
+    let expected_command = format!("ledger.redact.{id}");
+    if approval.command != expected_command {
+        return Err(error(
+            StatusCode::FORBIDDEN,
+            format!("approval is for '{}', not '{expected_command}'", approval.command),
+        ));
+    }
+    if approval.status != ApprovalStatus::Approved {
+        return Err(error(StatusCode::FORBIDDEN, format!("approval is {}", approval.status)));
+    }
+
+    let actor = req.actor.as_deref().unwrap_or("agent");
+    if !peer.may_claim(actor) {
+        return Err(error(
+            StatusCode::FORBIDDEN,
+            format!("actor '{actor}' is reserved for that daemon; this connection is '{}'", peer.actor()),
+        ));
+    }
+
+    let (actor, verified_actor) = (actor.to_string(), peer.actor());
+    let result = state
+        .ledger
+        .write(move |ledger| ledger.redact(id, &req.reason, Some(&approval.id), &actor, Some(&verified_actor)))
+        .await;
+    match result {
+        Ok(Redaction::Redacted { target, redaction }) => Ok(Json(RedactResponse { event: *target, redaction: *redaction })),
+        Ok(Redaction::NotFound) => Err(error(StatusCode::NOT_FOUND, "event not found".to_string())),
+        Ok(Redaction::Legacy) => Err(error(
+            StatusCode::CONFLICT,
+            "event predates payload digests; its hash covers the raw payload".to_string(),
+        )),
+        Ok(Redaction::AlreadyRedacted) => Err(error(StatusCode::CONFLICT, "event is already redacted".to_string())),
+        Err(e) => {
+            tracing::error!(error = %e, event_id = id, "failed to redact event");
+            Err(error(StatusCode::INTERNAL_SERVER_ERROR, "failed to redact event".to_string()))
+        }
+    }
+}
+
+/// GET /ledger/export — full audit history (events, incidents, steps, checkpoints) as JSONL.
+/// Streamed from a pooled reader as it is written; a failure part-way aborts the response,
+/// which leaves the export without its footer.
+pub async fn ledger_export_handler(State(state): State<SharedState>) -> impl IntoResponse {
+    let (tx, rx) = mpsc::channel(EXPORT_CHUNKS_IN_FLIGHT);
+    let failed = tx.clone();
+
+    tokio::spawn(async move {
+        let exported = state
+            .ledger
+            .read(move |ledger| {
+                let mut out = std::io::BufWriter::with_capacity(EXPORT_CHUNK_BYTES, BodyWriter(tx));
+                let summary = ledger.export_jsonl(&mut out)?;
+                out.flush()?;
+                Ok(summary)
+            })
+            .await;
+
+        let summary = match exported {
+            Ok(summary) => summary,
+            Err(_) if failed.is_closed() => {
+                tracing::info!("ledger export abandoned by the client");
+                return;
+            }
+            Err(e) => {
+                tracing::error!(error = %e, "failed to export ledger");
+                let _ = failed.send(Err(std::io::Error::other("ledger export failed"))).await;
+                return;
+            }
+        };
+        drop(failed);
+
+        if let Err(e) = state.ledger.append(
+            "ledger.export",
+            "agentd",
+            &serde_json::json!({
+                "event_count": summary.event_count,
+                "incident_count": summary.incident_count,
+                "head_hash": summary.head_hash,
+            })
+            .to_string(),
+        ).await {
+            tracing::error!(error = %e, "failed to log ledger export to ledger");
+        }
+    });
+
+    ([(header::CONTENT_TYPE, "application/x-ndjson")], Body::from_stream(ReceiverStream::new(rx)))
+}
+
+/// Hands export output from the blocking reader to the response body.
+struct BodyWriter(mpsc::Sender<std::io::Result<Bytes>>);
+
+impl Write for BodyWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0
+            .blocking_send(Ok(Bytes::copy_from_slice(buf)))
+            .map_err(|_| std::io::Error::new(std::io::ErrorKind::BrokenPipe, "export client went away"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
@@ -1,11 +1,56 @@
-use axum::extract::State;
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use axum::extract::{ConnectInfo, Query, State};
+use axum::http::StatusCode;
 use axum::Json;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
-use crate::ledger::EventFilter;
+use crate::embedding;
+use crate::ledger::{self, Event, ForgetTarget, Ledger, MemoryOptions, RecallFilter};
+use crate::peer::PeerIdentity;
 use crate::state::SharedState;
 
+// ── Namespaces ──
+
+/// Resolve a requested namespace: `agent` (default), `agent:<id>`, `session:<id>` or
+/// `project:<id>`, where `<id>` is 1-64 of `[A-Za-z0-9._-]`.
+fn parse_namespace(requested: Option<&str>) -> Result<String, (StatusCode, Json<serde_json::Value>)> {
+    let namespace = requested.unwrap_or(ledger::DEFAULT_MEMORY_NAMESPACE);
+    let valid_id = |id: &str| {
+        (1..=64).contains(&id.len())
+            && id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+    };
+    let valid = match namespace.split_once(':') {
+        None => namespace == "agent",
+        Some((scope, id)) => matches!(scope, "agent" | "session" | "project") && valid_id(id),
+    };
+    if valid {
+        Ok(namespace.to_string())
+    } else {
+        Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": format!("invalid namespace '{namespace}': expected agent, agent:<id>, session:<id> or project:<id>")})),
+        ))
+    }
+}
+
+fn memory_options(
+    namespace: Option<&str>,
+    ttl_secs: Option<i64>,
+    pinned: Option<bool>,
+) -> Result<MemoryOptions, (StatusCode, Json<serde_json::Value>)> {
+    if ttl_secs.is_some_and(|ttl| ttl <= 0) {
+        return Err((StatusCode::BAD_REQUEST, Json(json!({"error": "ttl_secs must be positive"}))));
+    }
+    Ok(MemoryOptions {
+        namespace: parse_namespace(namespace)?,
+        ttl_secs,
+        pinned: pinned.unwrap_or(false),
+    })
+}
+
 // ── POST /memory/ingest ──
 
 #[derive(Debug, Deserialize)]
@@ -15,24 +60,39 @@ pub struct MemoryIngestRequest {
     pub category: Option<String>,
     pub tags: Option<Vec<String>>,
     pub metadata: Option<serde_json::Value>,
+    /// Memory store namespace (default `agent`).
+    pub namespace: Option<String>,
+    /// Expire from recall after this many seconds (default: never).
+    pub ttl_secs: Option<i64>,
+    pub pinned: Option<bool>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct MemoryIngestResponse {
     pub id: i64,
     pub status: String,
+    pub namespace: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<String>,
 }
 
+/// The claimed `source` becomes the event actor; the connecting process's verified
+/// identity is recorded alongside it. Reserved daemon names (e.g. `osmoda-keyd`) can
+/// only be claimed by that daemon's own binary.
 pub async fn memory_ingest_handler(
     State(state): State<SharedState>,
+    ConnectInfo(peer): ConnectInfo<PeerIdentity>,
     Json(body): Json<MemoryIngestRequest>,
-) -> Result<Json<MemoryIngestResponse>, axum::http::StatusCode> {
+) -> Result<Json<MemoryIngestResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let options = memory_options(body.namespace.as_deref(), body.ttl_secs, body.pinned)?;
     let payload = serde_json::to_string(&json!({
         "source": body.source,
         "content": body.content,
         "category": body.category,
         "tags": body.tags,
         "metadata": body.metadata,
+        "namespace": options.namespace,
+        "ttl_secs": options.ttl_secs,
     }))
     .unwrap_or_default();
 
@@ -41,27 +101,106 @@ pub async fn memory_ingest_handler(
         .as_deref()
         .unwrap_or("unknown");
 
-    let ledger = state.ledger.lock().await;
-    let event = ledger
-        .append("memory.ingest", actor, &payload)
+    let verified_actor = peer.actor();
+    if !peer.may_claim(actor) {
+        tracing::warn!(claimed = actor, verified = %verified_actor, pid = ?peer.pid, "rejected forged event actor");
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(json!({"error": format!("actor '{actor}' is reserved for that daemon; this connection is '{verified_actor}'")})),
+        ));
+    }
+
+    let actor = actor.to_string();
+    let (event, record) = state
+        .ledger
+        .write(move |l| l.remember("memory.ingest", &actor, Some(&verified_actor), &payload, &options))
+        .await
         .map_err(|e| {
             tracing::error!(error = %e, "failed to ingest memory event");
-            axum::http::StatusCode::INTERNAL_SERVER_ERROR
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "failed to ingest memory event"})))
         })?;
 
+    // The event is already durable; a missing vector is filled in by the startup backfill
+    if let Some(text) = embedding::ingest_text(&event.payload) {
+        let model = state.embedder.model_id().to_string();
+        let stored = match embedding::embed_blocking(&state.embedder, text).await {
+            Ok(vector) => state.ledger.write(move |l| l.store_vector(event.id, &model, &vector)).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = stored {
+            tracing::warn!(error = %e, event_id = event.id, "failed to embed ingested memory");
+        }
+    }
+
     Ok(Json(MemoryIngestResponse {
         id: event.id,
         status: "stored".to_string(),
+        namespace: record.namespace,
+        expires_at: record.expires_at,
     }))
 }
 
 // ── POST /memory/recall ──
 
+/// Weight of vector similarity in the fused relevance when the request does not set one.
+const DEFAULT_SEMANTIC_WEIGHT: f64 = 0.5;
+
+/// Vector-only candidates below this cosine similarity are noise, not matches.
+const MIN_SIMILARITY: f32 = 0.3;
+
+/// Relevance multiplier for memories a consolidated entry has replaced, so the
+/// consolidated entry outranks the duplicates it stands for.
+const SUPERSEDED_WEIGHT: f64 = 0.5;
+
 #[derive(Debug, Deserialize)]
 pub struct MemoryRecallRequest {
     pub query: String,
     pub max_results: Option<usize>,
+    /// Prefix of the event timestamp (e.g. `2026-03`). Prefer `since`/`until`.
     pub timeframe: Option<String>,
+    /// 0.0 = keyword (BM25) only, 1.0 = vector similarity only. Default 0.5.
+    pub semantic_weight: Option<f64>,
+    /// Memory store namespace to recall from (default `agent`). Other namespaces'
+    /// memories are never returned; non-memory events are shared system history.
+    pub namespace: Option<String>,
+    /// Only events at or after this RFC 3339 timestamp (or `YYYY-MM-DD`).
+    pub since: Option<String>,
+    /// Only events strictly before this RFC 3339 timestamp (or `YYYY-MM-DD`).
+    pub until: Option<String>,
+    pub category: Option<String>,
+    /// Tagged with at least one of these.
+    #[serde(default)]
+    pub tags_any: Vec<String>,
+    /// Tagged with all of these.
+    #[serde(default)]
+    pub tags_all: Vec<String>,
+    /// The ingesting source (event actor).
+    pub source: Option<String>,
+    /// Drop chunks whose fused relevance is below this.
+    pub min_relevance: Option<f64>,
+}
+
+impl MemoryRecallRequest {
+    /// The request's structured filters, with timestamps normalised up front so a
+    /// malformed one is a 400 rather than a failed query.
+    fn filter(&self) -> Result<RecallFilter, (StatusCode, Json<serde_json::Value>)> {
+        let timestamp = |ts: &Option<String>| {
+            ts.as_deref()
+                .map(ledger::normalize_timestamp)
+                .transpose()
+                .map_err(|e| (StatusCode::BAD_REQUEST, Json(json!({"error": e.to_string()}))))
+        };
+        Ok(RecallFilter {
+            since: timestamp(&self.since)?,
+            until: timestamp(&self.until)?,
+            ts_prefix: self.timeframe.clone(),
+            category: self.category.clone(),
+            tags_any: self.tags_any.clone(),
+            tags_all: self.tags_all.clone(),
+            source: self.source.clone(),
+            ..RecallFilter::for_namespace(&parse_namespace(self.namespace.as_deref())?)
+        })
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -71,7 +210,17 @@ pub struct MemoryChunk {
     pub content: String,
     pub category: Option<String>,
     pub tags: Option<Vec<String>>,
+    /// Fused score in 0.0-1.0.
     pub relevance: f64,
+    /// Normalised BM25 (or keyword-overlap) score, when the event matched the keywords.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keyword_score: Option<f64>,
+    /// Cosine similarity to the query, when the event has a vector.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub semantic_score: Option<f64>,
+    /// The consolidated memory replacing this near-duplicate (relevance is down-weighted).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub superseded_by: Option<i64>,
 }
 
 #[derive(Debug, Serialize)]
@@ -79,109 +228,178 @@ pub struct MemoryRecallResponse {
     pub query: String,
     pub chunks: Vec<MemoryChunk>,
     pub total_searched: usize,
+    /// Embedding model used for the semantic half of the ranking.
+    pub embedding_model: String,
 }
 
+/// Hybrid recall: BM25 over the FTS index fused with vector similarity over
+/// memory.ingest embeddings, so a query matches paraphrases as well as keywords.
 pub async fn memory_recall_handler(
     State(state): State<SharedState>,
     Json(body): Json<MemoryRecallRequest>,
-) -> Result<Json<MemoryRecallResponse>, axum::http::StatusCode> {
-    let ledger = state.ledger.lock().await;
-    let max_results = body.max_results.unwrap_or(10).min(100);
-
-    // Try FTS5 first, fall back to keyword scan if it fails
-    let fts_results = ledger.fts_search(&body.query, max_results * 2);
-
-    let chunks = match fts_results {
-        Ok(results) if !results.is_empty() => {
-            let mut chunks: Vec<MemoryChunk> = Vec::new();
-            // Normalize BM25 scores to 0.0-1.0 range
-            let max_score = results.iter().map(|(_, s)| *s).fold(0.0_f64, f64::max);
-
-            for (event, score) in &results {
-                if let Some(ref tf) = body.timeframe {
-                    if !event.ts.starts_with(tf) {
-                        continue;
-                    }
-                }
-
-                let relevance = if max_score > 0.0 { score / max_score } else { 0.0 };
-                let parsed: serde_json::Value =
-                    serde_json::from_str(&event.payload).unwrap_or(json!({}));
-
-                let content = parsed.get("content").and_then(|c| c.as_str())
-                    .or_else(|| parsed.get("detail").and_then(|d| d.as_str()))
-                    .or_else(|| parsed.get("summary").and_then(|s| s.as_str()))
-                    .unwrap_or(&event.payload)
-                    .to_string();
-
-                let category = parsed.get("category").and_then(|c| c.as_str()).map(|s| s.to_string());
-                let tags = parsed.get("tags").and_then(|t| {
-                    t.as_array().map(|arr| arr.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
-                });
-
-                chunks.push(MemoryChunk { id: event.id, ts: event.ts.clone(), content, category, tags, relevance });
+) -> Result<Json<MemoryRecallResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let filter = body.filter()?;
+    let model = state.embedder.model_id().to_string();
+    let weight = body.semantic_weight.unwrap_or(DEFAULT_SEMANTIC_WEIGHT).clamp(0.0, 1.0);
+    let query_vector = if weight > 0.0 {
+        match embedding::embed_blocking(&state.embedder, body.query.clone()).await {
+            Ok(v) => Some(v),
+            Err(e) => {
+                tracing::warn!(error = %e, "failed to embed recall query, using keywords only");
+                None
             }
-            chunks.truncate(max_results);
-            chunks
-        }
-        Ok(_) => Vec::new(), // FTS5 returned nothing
-        Err(e) => {
-            // FTS5 failed — fall back to keyword scan
-            tracing::warn!(error = %e, "FTS5 search failed, falling back to keyword scan");
-            keyword_scan_fallback(&ledger, &body, max_results)?
         }
+    } else {
+        None
     };
 
-    let total_searched = ledger.event_count().unwrap_or(0) as usize;
+    let search_model = model.clone();
+    let (body, chunks, total_searched) = state
+        .ledger
+        .read(move |ledger| {
+            let semantic = query_vector.map(|v| (search_model.as_str(), v));
+            let chunks = recall_chunks(ledger, &body, &filter, semantic, weight)?;
+            let total_searched = ledger.event_count().unwrap_or(0) as usize;
+            Ok((body, chunks, total_searched))
+        })
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "memory recall failed");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "memory recall failed"})))
+        })?;
 
     Ok(Json(MemoryRecallResponse {
         query: body.query,
         chunks,
         total_searched,
+        embedding_model: model,
     }))
 }
 
-/// Fallback keyword scan when FTS5 is unavailable.
-fn keyword_scan_fallback(
-    ledger: &crate::ledger::Ledger,
+/// Rank events by `(1 - weight) * keyword + weight * similarity`. Keyword scores come from
+/// FTS5 (normalised to the best hit), or a keyword scan if FTS5 fails; similarity from
+/// the stored vectors of `semantic`'s model. An event found only one way scores 0 on the other.
+/// `filter` is applied inside each query, so `max_results` counts only matching events.
+fn recall_chunks(
+    ledger: &Ledger,
     body: &MemoryRecallRequest,
-    max_results: usize,
-) -> Result<Vec<MemoryChunk>, axum::http::StatusCode> {
-    let all_events = ledger
-        .query(&EventFilter { event_type: None, actor: None, limit: Some(5000) })
-        .map_err(|e| { tracing::error!(error = %e, "keyword fallback query failed"); axum::http::StatusCode::INTERNAL_SERVER_ERROR })?;
-
-    let query_lower = body.query.to_lowercase();
-    let query_terms: Vec<&str> = query_lower.split_whitespace().collect();
-    let mut chunks: Vec<MemoryChunk> = Vec::new();
+    filter: &RecallFilter,
+    semantic: Option<(&str, Vec<f32>)>,
+    weight: f64,
+) -> anyhow::Result<Vec<MemoryChunk>> {
+    let max_results = body.max_results.unwrap_or(10).min(100);
+    let mut candidates: BTreeMap<i64, (Event, Option<f64>, Option<f64>)> = BTreeMap::new();
+
+    if weight < 1.0 {
+        // Try FTS5 first, fall back to keyword scan if it fails
+        let keyword_hits = match ledger.fts_search(&body.query, Some(filter), max_results * 2) {
+            Ok(results) => {
+                // Normalize BM25 scores to 0.0-1.0 range
+                let max_score = results.iter().map(|(_, s)| *s).fold(0.0_f64, f64::max);
+                results
+                    .into_iter()
+                    .map(|(event, score)| (event, if max_score > 0.0 { score / max_score } else { 0.0 }))
+                    .collect()
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "FTS5 search failed, falling back to keyword scan");
+                keyword_scan_fallback(ledger, body, filter, max_results * 2)?
+            }
+        };
+        for (event, score) in keyword_hits {
+            candidates.insert(event.id, (event, Some(score), None));
+        }
+    }
 
-    for event in &all_events {
-        if let Some(ref tf) = body.timeframe {
-            if !event.ts.starts_with(tf) { continue; }
+    if let Some((model, query)) = semantic {
+        for (event_id, similarity) in ledger.vector_search(model, filter, &query, max_results * 2)? {
+            if similarity < MIN_SIMILARITY {
+                break;
+            }
+            if let Some(candidate) = candidates.get_mut(&event_id) {
+                candidate.2 = Some(similarity as f64);
+            } else if let Some(event) = ledger.get_event(event_id)? {
+                candidates.insert(event_id, (event, None, Some(similarity as f64)));
+            }
         }
-        let payload_lower = event.payload.to_lowercase();
-        let matching = query_terms.iter().filter(|t| payload_lower.contains(**t)).count();
-        if matching == 0 { continue; }
-
-        let relevance = matching as f64 / query_terms.len() as f64;
-        let parsed: serde_json::Value = serde_json::from_str(&event.payload).unwrap_or(json!({}));
-        let content = parsed.get("content").and_then(|c| c.as_str())
-            .or_else(|| parsed.get("detail").and_then(|d| d.as_str()))
-            .or_else(|| parsed.get("summary").and_then(|s| s.as_str()))
-            .unwrap_or(&event.payload).to_string();
-        let category = parsed.get("category").and_then(|c| c.as_str()).map(|s| s.to_string());
-        let tags = parsed.get("tags").and_then(|t| {
-            t.as_array().map(|arr| arr.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
-        });
-
-        chunks.push(MemoryChunk { id: event.id, ts: event.ts.clone(), content, category, tags, relevance });
     }
 
-    chunks.sort_by(|a, b| b.relevance.partial_cmp(&a.relevance).unwrap_or(std::cmp::Ordering::Equal).then(b.id.cmp(&a.id)));
+    let ids: Vec<i64> = candidates.keys().copied().collect();
+    let records = ledger.memory_records(&ids)?;
+
+    let min_relevance = body.min_relevance.unwrap_or(0.0);
+    let mut chunks: Vec<MemoryChunk> = candidates
+        .into_values()
+        .map(|(event, keyword, semantic)| {
+            let mut relevance = (1.0 - weight) * keyword.unwrap_or(0.0) + weight * semantic.unwrap_or(0.0);
+            let superseded_by = records.get(&event.id).and_then(|m| m.superseded_by);
+            if superseded_by.is_some() {
+                relevance *= SUPERSEDED_WEIGHT;
+            }
+            MemoryChunk { superseded_by, ..to_chunk(event, relevance, keyword, semantic) }
+        })
+        .filter(|chunk| chunk.relevance >= min_relevance)
+        .collect();
+
+    chunks.sort_by(|a, b| b.relevance.total_cmp(&a.relevance).then(b.id.cmp(&a.id)));
     chunks.truncate(max_results);
     Ok(chunks)
 }
 
+fn to_chunk(event: Event, relevance: f64, keyword_score: Option<f64>, semantic_score: Option<f64>) -> MemoryChunk {
+    let parsed: serde_json::Value = serde_json::from_str(&event.payload).unwrap_or(json!({}));
+    let content = parsed.get("content").and_then(|c| c.as_str())
+        .or_else(|| parsed.get("detail").and_then(|d| d.as_str()))
+        .or_else(|| parsed.get("summary").and_then(|s| s.as_str()))
+        .unwrap_or(&event.payload)
+        .to_string();
+    let category = parsed.get("category").and_then(|c| c.as_str()).map(|s| s.to_string());
+    let tags = parsed.get("tags").and_then(|t| {
+        t.as_array().map(|arr| arr.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
+    });
+
+    MemoryChunk {
+        id: event.id,
+        ts: event.ts,
+        content,
+        category,
+        tags,
+        relevance,
+        keyword_score,
+        semantic_score,
+        superseded_by: None,
+    }
+}
+
+/// Fallback keyword scan when FTS5 is unavailable: events scored by the share of
+/// query terms their payload contains.
+fn keyword_scan_fallback(
+    ledger: &Ledger,
+    body: &MemoryRecallRequest,
+    filter: &RecallFilter,
+    limit: usize,
+) -> anyhow::Result<Vec<(Event, f64)>> {
+    let all_events = ledger
+        .recall_scan(filter, 5000)
+        .context("keyword fallback query failed")?;
+
+    let query_lower = body.query.to_lowercase();
+    let query_terms: Vec<&str> = query_lower.split_whitespace().collect();
+
+    let mut hits: Vec<(Event, f64)> = all_events
+        .into_iter()
+        .filter_map(|event| {
+            let payload_lower = event.payload.to_lowercase();
+            let matching = query_terms.iter().filter(|t| payload_lower.contains(**t)).count();
+            (matching > 0).then(|| (event, matching as f64 / query_terms.len() as f64))
+        })
+        .collect();
+
+    hits.sort_by(|a, b| b.1.total_cmp(&a.1).then(b.0.id.cmp(&a.0.id)));
+    hits.truncate(limit);
+    Ok(hits)
+}
+
 // ── POST /memory/store ──
 
 #[derive(Debug, Deserialize)]
@@ -190,34 +408,201 @@ pub struct MemoryStoreRequest {
     pub detail: Option<String>,
     pub category: Option<String>,
     pub tags: Option<Vec<String>>,
+    /// Memory store namespace (default `agent`).
+    pub namespace: Option<String>,
+    /// Expire from recall after this many seconds (default: never).
+    pub ttl_secs: Option<i64>,
+    pub pinned: Option<bool>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct MemoryStoreResponse {
     pub id: i64,
+    pub namespace: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<String>,
 }
 
 pub async fn memory_store_handler(
     State(state): State<SharedState>,
     Json(body): Json<MemoryStoreRequest>,
-) -> Result<Json<MemoryStoreResponse>, axum::http::StatusCode> {
+) -> Result<Json<MemoryStoreResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let options = memory_options(body.namespace.as_deref(), body.ttl_secs, body.pinned)?;
     let payload = serde_json::to_string(&json!({
         "summary": body.summary,
         "detail": body.detail,
         "category": body.category,
         "tags": body.tags,
+        "namespace": options.namespace,
+        "ttl_secs": options.ttl_secs,
     }))
     .unwrap_or_default();
 
-    let ledger = state.ledger.lock().await;
-    let event = ledger
-        .append("memory.store", "agentd", &payload)
+    let (event, record) = state
+        .ledger
+        .write(move |l| l.remember("memory.store", "agentd", None, &payload, &options))
+        .await
         .map_err(|e| {
             tracing::error!(error = %e, "failed to store memory event");
-            axum::http::StatusCode::INTERNAL_SERVER_ERROR
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "failed to store memory event"})))
+        })?;
+
+    Ok(Json(MemoryStoreResponse { id: event.id, namespace: record.namespace, expires_at: record.expires_at }))
+}
+
+// ── GET /memory/list ──
+
+#[derive(Debug, Deserialize)]
+pub struct MemoryListParams {
+    pub namespace: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MemoryEntry {
+    #[serde(flatten)]
+    pub record: ledger::MemoryRecord,
+    pub event_type: String,
+    pub content: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MemoryListResponse {
+    pub namespace: String,
+    pub memories: Vec<MemoryEntry>,
+}
+
+/// Live (unexpired or pinned) memories in one namespace, newest first.
+pub async fn memory_list_handler(
+    State(state): State<SharedState>,
+    Query(params): Query<MemoryListParams>,
+) -> Result<Json<MemoryListResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let namespace = parse_namespace(params.namespace.as_deref())?;
+    let limit = params.limit.unwrap_or(100).clamp(1, 1000);
+
+    let list_namespace = namespace.clone();
+    let memories = state
+        .ledger
+        .read(move |l| l.list_memories(&list_namespace, limit))
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "failed to list memories");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "failed to list memories"})))
+        })?;
+
+    let memories = memories
+        .into_iter()
+        .map(|(record, event)| {
+            let event_type = event.event_type.clone();
+            let content = to_chunk(event, 0.0, None, None).content;
+            MemoryEntry { record, event_type, content }
+        })
+        .collect();
+    Ok(Json(MemoryListResponse { namespace, memories }))
+}
+
+// ── POST /memory/pin ──
+
+#[derive(Debug, Deserialize)]
+pub struct MemoryPinRequest {
+    pub event_id: i64,
+    pub pinned: bool,
+    pub actor: Option<String>,
+}
+
+/// Pin a memory (never expires, survives a namespace-wide forget) or unpin it.
+pub async fn memory_pin_handler(
+    State(state): State<SharedState>,
+    ConnectInfo(peer): ConnectInfo<PeerIdentity>,
+    Json(body): Json<MemoryPinRequest>,
+) -> Result<Json<ledger::MemoryRecord>, (StatusCode, Json<serde_json::Value>)> {
+    let actor = body.actor.unwrap_or_else(|| "agent".to_string());
+    let verified_actor = peer.actor();
+    if !peer.may_claim(&actor) {
+        tracing::warn!(claimed = %actor, verified = %verified_actor, pid = ?peer.pid, "rejected forged event actor");
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(json!({"error": format!("actor '{actor}' is reserved for that daemon; this connection is '{verified_actor}'")})),
+        ));
+    }
+
+    let record = state
+        .ledger
+        .write(move |l| l.set_memory_pinned(body.event_id, body.pinned, &actor, Some(&verified_actor)))
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "failed to pin memory");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "failed to pin memory"})))
+        })?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, Json(json!({"error": format!("memory {} not found", body.event_id)}))))?;
+
+    Ok(Json(record))
+}
+
+// ── POST /memory/forget ──
+
+#[derive(Debug, Deserialize)]
+pub struct MemoryForgetRequest {
+    /// Forget one memory...
+    pub event_id: Option<i64>,
+    /// ...or every unpinned memory in a namespace.
+    pub namespace: Option<String>,
+    pub reason: String,
+    pub actor: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MemoryForgetResponse {
+    pub forgotten: Vec<i64>,
+    /// The `memory.forget` event recording this call.
+    pub audit_event_id: i64,
+}
+
+/// Remove memories from the store so recall no longer returns them, leaving a
+/// `memory.forget` event behind. The original events stay in the ledger; redact
+/// them as well to remove their content.
+pub async fn memory_forget_handler(
+    State(state): State<SharedState>,
+    ConnectInfo(peer): ConnectInfo<PeerIdentity>,
+    Json(body): Json<MemoryForgetRequest>,
+) -> Result<Json<MemoryForgetResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let target = match (body.event_id, body.namespace.as_deref()) {
+        (Some(id), None) => ForgetTarget::Event(id),
+        (None, Some(namespace)) => ForgetTarget::Namespace(parse_namespace(Some(namespace))?),
+        _ => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "specify exactly one of event_id or namespace"})),
+            ))
+        }
+    };
+    if body.reason.trim().is_empty() {
+        return Err((StatusCode::BAD_REQUEST, Json(json!({"error": "reason is required"}))));
+    }
+
+    let actor = body.actor.unwrap_or_else(|| "agent".to_string());
+    let verified_actor = peer.actor();
+    if !peer.may_claim(&actor) {
+        tracing::warn!(claimed = %actor, verified = %verified_actor, pid = ?peer.pid, "rejected forged event actor");
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(json!({"error": format!("actor '{actor}' is reserved for that daemon; this connection is '{verified_actor}'")})),
+        ));
+    }
+
+    let forgotten = state
+        .ledger
+        .write(move |l| l.forget_memories(&target, &body.reason, &actor, Some(&verified_actor)))
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "failed to forget memories");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "failed to forget memories"})))
         })?;
 
-    Ok(Json(MemoryStoreResponse { id: event.id }))
+    match forgotten {
+        Some((forgotten, audit)) => Ok(Json(MemoryForgetResponse { forgotten, audit_event_id: audit.id })),
+        None => Err((StatusCode::NOT_FOUND, Json(json!({"error": "no matching memories"})))),
+    }
 }
 
 // ── Tests ──
@@ -225,7 +610,8 @@ pub async fn memory_store_handler(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::ledger::Ledger;
+    use crate::embedding::Embedder;
+    use crate::ledger::{EventFilter, Ledger};
 
     fn make_ledger() -> Ledger {
         let dir = tempfile::tempdir().unwrap();
@@ -247,7 +633,7 @@ mod tests {
         let query_terms: Vec<&str> = query_lower.split_whitespace().collect();
 
         // Use keyword_scan_fallback logic directly (simulated)
-        let all_events = ledger.query(&EventFilter { event_type: None, actor: None, limit: Some(5000) }).unwrap();
+        let all_events = ledger.query(&EventFilter { limit: Some(5000), ..Default::default() }).unwrap();
         let matching: Vec<_> = all_events.iter().filter(|e| {
             let payload_lower = e.payload.to_lowercase();
             query_terms.iter().any(|t| payload_lower.contains(*t))
@@ -265,11 +651,187 @@ mod tests {
         ledger.append("memory.store", "agent", r#"{"summary":"stored fact","detail":"user said hello world","category":"test"}"#).unwrap();
 
         // Without type restriction: both events should be searchable
-        let all_events = ledger.query(&EventFilter { event_type: None, actor: None, limit: Some(5000) }).unwrap();
+        let all_events = ledger.query(&EventFilter { limit: Some(5000), ..Default::default() }).unwrap();
         let query = "hello";
         let found: Vec<_> = all_events.iter().filter(|e| e.payload.contains(query)).collect();
         assert_eq!(found.len(), 2, "both memory.ingest and memory.store should be found");
     }
+
+    fn recall(query: &str, semantic_weight: Option<f64>) -> MemoryRecallRequest {
+        MemoryRecallRequest {
+            query: query.to_string(),
+            max_results: None,
+            timeframe: None,
+            semantic_weight,
+            namespace: None,
+            since: None,
+            until: None,
+            category: None,
+            tags_any: Vec::new(),
+            tags_all: Vec::new(),
+            source: None,
+            min_relevance: None,
+        }
+    }
+
+    #[test]
+    fn test_hybrid_recall_matches_paraphrase() {
+        let ledger = make_ledger();
+        let embedder = embedding::HashingEmbedder::default();
+        let model = embedder.model_id();
+
+        for content in [
+            "web server crashed after the 02:00 upgrade",
+            "nightly backup finished in 3 minutes",
+            "nginx config reloaded",
+        ] {
+            let payload = json!({"content": content}).to_string();
+            let event = ledger.append("memory.ingest", "voice", &payload).unwrap();
+            ledger.store_vector(event.id, model, &embedder.embed(content).unwrap()).unwrap();
+        }
+
+        // BM25 alone shares no term with the crash memory
+        let agent = RecallFilter::for_namespace("agent");
+        let keyword_only = recall_chunks(&ledger, &recall("nginx down", Some(0.0)), &agent, None, 0.0).unwrap();
+        assert!(keyword_only.iter().all(|c| !c.content.contains("crashed")));
+
+        let query = embedder.embed("nginx down").unwrap();
+        let chunks = recall_chunks(&ledger, &recall("nginx down", None), &agent, Some((model, query)), 0.5).unwrap();
+        assert_eq!(chunks.len(), 2, "the unrelated backup memory is not returned");
+        // Matching both ways ranks first; the paraphrase is found by similarity alone
+        assert_eq!(chunks[0].content, "nginx config reloaded");
+        assert!(chunks[0].keyword_score.is_some() && chunks[0].semantic_score.is_some());
+        assert_eq!(chunks[1].content, "web server crashed after the 02:00 upgrade");
+        assert!(chunks[1].keyword_score.is_none() && chunks[1].semantic_score.is_some());
+    }
+
+    #[test]
+    fn test_vectors_dropped_with_redacted_payload() {
+        let ledger = make_ledger();
+        let embedder = embedding::HashingEmbedder::default();
+        let model = embedder.model_id();
+
+        let event = ledger.append("memory.ingest", "voice", r#"{"content":"root password is hunter2"}"#).unwrap();
+        ledger.store_vector(event.id, model, &embedder.embed("root password is hunter2").unwrap()).unwrap();
+        assert_eq!(ledger.vector_count(model).unwrap(), 1);
+        assert!(ledger.memory_events_without_vectors(model, 0, 10).unwrap().is_empty());
+
+        ledger.redact(event.id, "secret", None, "admin", None).unwrap();
+        assert_eq!(ledger.vector_count(model).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_recall_filters_apply_before_limit() {
+        let ledger = make_ledger();
+        // Twenty strong keyword matches that every filter below excludes...
+        for i in 0..20 {
+            let payload = json!({"content": format!("disk full disk full on db{i}"), "category": "noise", "tags": ["db"]});
+            ledger.append("memory.ingest", "watch", &payload.to_string()).unwrap();
+        }
+        ledger.append("custom.note", "tester", "disk full, not json").unwrap();
+        // ...and one weak match that passes them all
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let cutoff = ledger::format_ts(chrono::Utc::now());
+        let payload = json!({"content": "disk usage warning on web1", "category": "ops", "tags": ["web", "storage"]});
+        let wanted = ledger.append("memory.ingest", "voice", &payload.to_string()).unwrap();
+
+        let only_wanted = |request: &MemoryRecallRequest| {
+            let chunks = recall_chunks(&ledger, request, &request.filter().unwrap(), None, 0.0).unwrap();
+            chunks.iter().map(|c| c.id).collect::<Vec<_>>()
+        };
+        let mut request = recall("disk full", Some(0.0));
+        request.max_results = Some(1);
+
+        request.category = Some("ops".into());
+        assert_eq!(only_wanted(&request), vec![wanted.id]);
+        request.category = None;
+
+        request.tags_all = vec!["storage".into(), "web".into()];
+        assert_eq!(only_wanted(&request), vec![wanted.id]);
+        request.tags_all = vec!["storage".into(), "db".into()];
+        assert!(only_wanted(&request).is_empty());
+        request.tags_all.clear();
+
+        request.tags_any = vec!["web".into(), "nope".into()];
+        assert_eq!(only_wanted(&request), vec![wanted.id]);
+        request.tags_any.clear();
+
+        request.source = Some("voice".into());
+        assert_eq!(only_wanted(&request), vec![wanted.id]);
+        request.source = None;
+
+        request.since = Some(cutoff.clone());
+        assert_eq!(only_wanted(&request), vec![wanted.id]);
+        request.since = Some("2000-01-01".into());
+        request.until = Some(cutoff);
+        assert!(only_wanted(&request).iter().all(|id| *id != wanted.id));
+
+        request.since = Some("last tuesday".into());
+        assert!(request.filter().is_err());
+        request.since = None;
+        request.until = None;
+
+        // The best keyword hit has relevance 1.0; weaker ones fall under the floor
+        request.max_results = Some(50);
+        request.min_relevance = Some(0.99);
+        let chunks = recall_chunks(&ledger, &request, &request.filter().unwrap(), None, 0.0).unwrap();
+        assert!(!chunks.is_empty() && chunks.iter().all(|c| c.relevance >= 0.99));
+        assert!(chunks.iter().all(|c| c.id != wanted.id));
+    }
+
+    #[test]
+    fn test_recall_down_weights_superseded() {
+        let ledger = make_ledger();
+        let a = ledger.append("memory.ingest", "osmoda-watch", r#"{"content":"redis evicting keys on cache1"}"#).unwrap();
+        let b = ledger.append("memory.ingest", "osmoda-teachd", r#"{"content":"redis evicting keys on cache1!"}"#).unwrap();
+        let merged = ledger
+            .consolidate_memories("agent", &[a.id, b.id], r#"{"content":"redis evicting keys on cache1","consolidates":[1,2]}"#)
+            .unwrap()
+            .unwrap();
+
+        let agent = RecallFilter::for_namespace("agent");
+        let chunks = recall_chunks(&ledger, &recall("redis evicting", Some(0.0)), &agent, None, 0.0).unwrap();
+        assert_eq!(chunks.len(), 3);
+        assert_eq!((chunks[0].id, chunks[0].superseded_by), (merged.id, None));
+        assert!(chunks[1..].iter().all(|c| c.superseded_by == Some(merged.id) && c.relevance <= SUPERSEDED_WEIGHT));
+    }
+
+    #[test]
+    fn test_parse_namespace() {
+        assert_eq!(parse_namespace(None).unwrap(), "agent");
+        for ok in ["agent", "agent:planner", "session:2026-10-16.a_1", "project:osmoda"] {
+            assert_eq!(parse_namespace(Some(ok)).unwrap(), ok);
+        }
+        for bad in ["", "team:x", "session:", "session", "project:a b", "agent:x:y"] {
+            assert!(parse_namespace(Some(bad)).is_err(), "{bad} should be rejected");
+        }
+    }
+
+    #[test]
+    fn test_recall_isolates_namespaces() {
+        let ledger = make_ledger();
+        let remember = |content: &str, namespace: &str| {
+            let options = MemoryOptions { namespace: namespace.to_string(), ttl_secs: None, pinned: false };
+            let payload = json!({"content": content}).to_string();
+            ledger.remember("memory.ingest", "voice", None, &payload, &options).unwrap().0
+        };
+        let mine = remember("deploy key rotated on staging", "session:a");
+        remember("deploy key leaked in session b", "session:b");
+        ledger.append("system.note", "agentd", r#"{"detail":"deploy pipeline paused"}"#).unwrap();
+
+        let mut request = recall("deploy", Some(0.0));
+        request.namespace = Some("session:a".into());
+        let chunks = recall_chunks(&ledger, &request, &request.filter().unwrap(), None, 0.0).unwrap();
+        let contents: Vec<_> = chunks.iter().map(|c| c.content.as_str()).collect();
+        assert_eq!(contents.len(), 2, "own memory plus shared system history: {contents:?}");
+        assert!(contents.contains(&"deploy key rotated on staging"));
+        assert!(contents.contains(&"deploy pipeline paused"));
+
+        ledger.forget_memories(&ForgetTarget::Event(mine.id), "rotated again", "agent", None).unwrap();
+        let chunks = recall_chunks(&ledger, &request, &request.filter().unwrap(), None, 0.0).unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].content, "deploy pipeline paused");
+    }
 }
 
 // ── GET /memory/health ──
@@ -279,21 +841,30 @@ pub struct MemoryHealthResponse {
     pub status: String,
     pub event_count: i64,
     pub state_dir: String,
+    pub embedding_model: String,
+    /// memory.ingest events with a vector from `embedding_model`.
+    pub vector_count: i64,
 }
 
 pub async fn memory_health_handler(
     State(state): State<SharedState>,
 ) -> Result<Json<MemoryHealthResponse>, axum::http::StatusCode> {
-    let ledger = state.ledger.lock().await;
-
-    let event_count = ledger.event_count().map_err(|e| {
-        tracing::error!(error = %e, "failed to count events for memory health");
-        axum::http::StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    let model = state.embedder.model_id().to_string();
+    let count_model = model.clone();
+    let (event_count, vector_count) = state
+        .ledger
+        .read(move |ledger| Ok((ledger.event_count()?, ledger.vector_count(&count_model)?)))
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "failed to count events for memory health");
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR
+        })?;
 
     Ok(Json(MemoryHealthResponse {
         status: "ok".to_string(),
         event_count,
         state_dir: state.state_dir.clone(),
+        embedding_model: model,
+        vector_count,
     }))
 }
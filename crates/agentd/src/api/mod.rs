@@ -4,6 +4,7 @@ pub mod backup;
 pub mod discovery;
 pub mod events;
 pub mod health;
+pub mod ledger;
 pub mod memory;
 pub mod receipts;
 pub mod sandbox;
@@ -1,8 +1,15 @@
-use axum::extract::{Path, Query, State};
+use std::collections::BTreeMap;
+
+use axum::extract::{ConnectInfo, Path, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::IntoResponse;
 use axum::Json;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
+use crate::ledger::{IncidentFields, IncidentFilter, IncidentTransition, IncidentUpdate, NewIncidentStep};
+use crate::peer::PeerIdentity;
+use crate::postmortem;
 use crate::state::SharedState;
 
 // ── Receipts ──
@@ -34,28 +41,26 @@ pub async fn receipts_handler(
     State(state): State<SharedState>,
     Query(params): Query<ReceiptQuery>,
 ) -> Result<Json<Vec<Receipt>>, axum::http::StatusCode> {
-    let ledger = state.ledger.lock().await;
+    let since = match params.since.as_deref().map(crate::ledger::normalize_timestamp) {
+        Some(Ok(ts)) => Some(ts),
+        Some(Err(_)) => return Err(axum::http::StatusCode::BAD_REQUEST),
+        None => None,
+    };
 
     let filter = crate::ledger::EventFilter {
         event_type: params.receipt_type.clone(),
-        actor: None,
         limit: Some(params.limit.unwrap_or(50).min(500)), // Cap at 500
+        since,
+        ..Default::default()
     };
 
-    let events = ledger.query(&filter).map_err(|e| {
+    let events = state.ledger.read(move |ledger| ledger.query(&filter)).await.map_err(|e| {
         tracing::error!(error = %e, "failed to query receipts");
         axum::http::StatusCode::INTERNAL_SERVER_ERROR
     })?;
 
     let mut receipts = Vec::new();
     for event in events {
-        // Apply since filter
-        if let Some(ref since) = params.since {
-            if event.ts < *since {
-                continue;
-            }
-        }
-
         let parsed: serde_json::Value =
             serde_json::from_str(&event.payload).unwrap_or(json!({}));
 
@@ -92,15 +97,30 @@ pub async fn receipts_handler(
 
 // ── Incident Workspaces (backed by dedicated tables) ──
 
+/// Incident severities, most severe first.
+pub const SEVERITIES: &[&str] = &["critical", "high", "medium", "low"];
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IncidentWorkspace {
     pub id: String,
     pub name: String,
     pub status: String,
+    pub severity: String,
+    pub affected_services: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assignee: Option<String>,
     pub steps: Vec<IncidentStep>,
     pub created_at: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub resolved_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolution: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_to_resolve_secs: Option<i64>,
+    pub reopen_count: i64,
+    /// Set on incidents the correlation engine opened (the affected service).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub correlation_key: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -116,34 +136,49 @@ pub struct IncidentStep {
 #[derive(Debug, Deserialize)]
 pub struct CreateIncidentRequest {
     pub name: String,
+    /// critical, high, medium (default) or low.
+    pub severity: Option<String>,
+    #[serde(default)]
+    pub affected_services: Vec<String>,
+    pub assignee: Option<String>,
+}
+
+fn check_severity(severity: Option<&str>) -> Result<(), StatusCode> {
+    match severity {
+        Some(s) if !SEVERITIES.contains(&s) => Err(StatusCode::BAD_REQUEST),
+        _ => Ok(()),
+    }
 }
 
 /// POST /incident/create — create a new incident workspace.
 pub async fn incident_create_handler(
     State(state): State<SharedState>,
+    ConnectInfo(peer): ConnectInfo<PeerIdentity>,
     Json(body): Json<CreateIncidentRequest>,
 ) -> Result<Json<IncidentWorkspace>, axum::http::StatusCode> {
+    check_severity(body.severity.as_deref())?;
     let id = uuid::Uuid::new_v4().to_string();
 
-    let ledger = state.ledger.lock().await;
-
-    // Create in dedicated table
-    ledger.create_incident(&id, &body.name).map_err(|e| {
-        tracing::error!(error = %e, "failed to create incident");
-        axum::http::StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-
-    // Also log to event ledger for auditability
-    let _ = ledger.append(
-        "incident.create",
-        "agentd",
-        &json!({"incident_id": id, "name": body.name}).to_string(),
-    );
-
-    let incident = ledger.get_incident(&id).map_err(|e| {
-        tracing::error!(error = %e, "failed to read back incident");
-        axum::http::StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    let (incident_id, name) = (id.clone(), body.name.clone());
+    let fields = IncidentFields {
+        severity: body.severity.unwrap_or_else(|| IncidentFields::default().severity),
+        affected_services: body.affected_services,
+        assignee: body.assignee.filter(|a| !a.is_empty()),
+        correlation_key: None,
+    };
+    let verified_actor = peer.actor();
+    let incident = state
+        .ledger
+        .write(move |ledger| {
+            // The table row and its incident.create event are written together
+            ledger.create_incident(&incident_id, &name, &fields, Some(&verified_actor))?;
+            ledger.get_incident(&incident_id)
+        })
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "failed to create incident");
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR
+        })?;
 
     let incident = incident.ok_or(axum::http::StatusCode::INTERNAL_SERVER_ERROR)?;
 
@@ -161,56 +196,32 @@ pub struct AddStepRequest {
 /// POST /incident/{id}/step — add a step to an incident workspace (resumable).
 pub async fn incident_step_handler(
     State(state): State<SharedState>,
+    ConnectInfo(peer): ConnectInfo<PeerIdentity>,
     Path(incident_id): Path<String>,
     Json(body): Json<AddStepRequest>,
 ) -> Result<Json<IncidentWorkspace>, axum::http::StatusCode> {
-    let ledger = state.ledger.lock().await;
-
-    // Verify incident exists and get current step count
-    let incident = ledger.get_incident(&incident_id).map_err(|e| {
-        tracing::error!(error = %e, "failed to query incident");
-        axum::http::StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-
-    let incident = incident.ok_or(axum::http::StatusCode::NOT_FOUND)?;
-    let step_number = incident.steps.len() as u32 + 1;
-
-    // Add step
-    ledger
-        .add_incident_step(
-            &incident_id,
-            step_number,
-            &body.action,
-            &body.result,
-            body.receipt_id.as_deref(),
-        )
+    // Numbering, the step and its incident.step event are written in one transaction
+    let verified_actor = peer.actor();
+    let updated = state
+        .ledger
+        .write(move |ledger| {
+            let step = NewIncidentStep {
+                action: &body.action,
+                result: &body.result,
+                receipt_id: body.receipt_id.as_deref(),
+            };
+            if ledger.add_incident_step(&incident_id, &step, Some(&verified_actor))?.is_none() {
+                return Ok(None);
+            }
+            ledger.get_incident(&incident_id)
+        })
+        .await
         .map_err(|e| {
             tracing::error!(error = %e, "failed to add incident step");
             axum::http::StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
-    // Log to event ledger
-    let _ = ledger.append(
-        "incident.step",
-        "agentd",
-        &json!({
-            "incident_id": incident_id,
-            "step_number": step_number,
-            "action": body.action,
-            "result": body.result,
-        })
-        .to_string(),
-    );
-
-    // Return updated incident
-    let updated = ledger.get_incident(&incident_id).map_err(|e| {
-        tracing::error!(error = %e, "failed to read back incident");
-        axum::http::StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-
-    Ok(Json(row_to_workspace(
-        updated.ok_or(axum::http::StatusCode::INTERNAL_SERVER_ERROR)?,
-    )))
+    Ok(Json(row_to_workspace(updated.ok_or(axum::http::StatusCode::NOT_FOUND)?)))
 }
 
 /// GET /incident/{id} — get full incident workspace with all steps.
@@ -218,9 +229,7 @@ pub async fn incident_get_handler(
     State(state): State<SharedState>,
     Path(incident_id): Path<String>,
 ) -> Result<Json<IncidentWorkspace>, axum::http::StatusCode> {
-    let ledger = state.ledger.lock().await;
-
-    let incident = ledger.get_incident(&incident_id).map_err(|e| {
+    let incident = state.ledger.read(move |ledger| ledger.get_incident(&incident_id)).await.map_err(|e| {
         tracing::error!(error = %e, "failed to query incident");
         axum::http::StatusCode::INTERNAL_SERVER_ERROR
     })?;
@@ -229,26 +238,202 @@ pub async fn incident_get_handler(
     Ok(Json(row_to_workspace(incident)))
 }
 
+/// GET /incident/{id}/report — Markdown postmortem: timeline, linked receipts and the
+/// affected actors' ledger events in the incident window.
+pub async fn incident_report_handler(
+    State(state): State<SharedState>,
+    Path(incident_id): Path<String>,
+) -> Result<impl IntoResponse, axum::http::StatusCode> {
+    let report = state
+        .ledger
+        .read(move |ledger| postmortem::gather(ledger, &incident_id))
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "failed to build incident report");
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let report = report.ok_or(axum::http::StatusCode::NOT_FOUND)?;
+    Ok(([(header::CONTENT_TYPE, "text/markdown; charset=utf-8")], postmortem::render(&report)))
+}
+
 #[derive(Debug, Deserialize)]
-pub struct IncidentListQuery {
-    pub status: Option<String>,
+pub struct UpdateIncidentRequest {
+    pub severity: Option<String>,
+    pub affected_services: Option<Vec<String>>,
+    /// Empty string unassigns.
+    pub assignee: Option<String>,
 }
 
-/// GET /incidents — list incident workspaces.
-pub async fn incidents_list_handler(
+/// POST /incident/{id}/update — change severity, affected services or assignee.
+/// Only fields that actually change are recorded (`incident.update`).
+pub async fn incident_update_handler(
     State(state): State<SharedState>,
-    Query(params): Query<IncidentListQuery>,
-) -> Result<Json<Vec<IncidentWorkspace>>, axum::http::StatusCode> {
-    let ledger = state.ledger.lock().await;
+    ConnectInfo(peer): ConnectInfo<PeerIdentity>,
+    Path(incident_id): Path<String>,
+    Json(body): Json<UpdateIncidentRequest>,
+) -> Result<Json<IncidentWorkspace>, axum::http::StatusCode> {
+    check_severity(body.severity.as_deref())?;
+    let update = IncidentUpdate {
+        severity: body.severity,
+        affected_services: body.affected_services,
+        assignee: body.assignee,
+    };
+    let verified_actor = peer.actor();
+    transition(&state, incident_id, move |ledger, id| ledger.update_incident(id, &update, Some(&verified_actor)), true).await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResolveIncidentRequest {
+    /// What fixed it.
+    pub resolution: String,
+}
+
+/// POST /incident/{id}/resolve — resolve an open incident (409 if already resolved).
+pub async fn incident_resolve_handler(
+    State(state): State<SharedState>,
+    ConnectInfo(peer): ConnectInfo<PeerIdentity>,
+    Path(incident_id): Path<String>,
+    Json(body): Json<ResolveIncidentRequest>,
+) -> Result<Json<IncidentWorkspace>, axum::http::StatusCode> {
+    if body.resolution.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let verified_actor = peer.actor();
+    transition(&state, incident_id, move |ledger, id| ledger.resolve_incident(id, &body.resolution, Some(&verified_actor)), false).await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReopenIncidentRequest {
+    pub reason: String,
+}
+
+/// POST /incident/{id}/reopen — reopen a resolved incident (409 if it is open).
+pub async fn incident_reopen_handler(
+    State(state): State<SharedState>,
+    ConnectInfo(peer): ConnectInfo<PeerIdentity>,
+    Path(incident_id): Path<String>,
+    Json(body): Json<ReopenIncidentRequest>,
+) -> Result<Json<IncidentWorkspace>, axum::http::StatusCode> {
+    if body.reason.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let verified_actor = peer.actor();
+    transition(&state, incident_id, move |ledger, id| ledger.reopen_incident(id, &body.reason, Some(&verified_actor)), false).await
+}
+
+/// Run an incident transition on the writer and return the updated workspace.
+/// `Unchanged` is fine for updates (nothing to change) but a conflict for status changes.
+async fn transition<F>(
+    state: &SharedState,
+    incident_id: String,
+    apply: F,
+    unchanged_ok: bool,
+) -> Result<Json<IncidentWorkspace>, axum::http::StatusCode>
+where
+    F: FnOnce(&crate::ledger::Ledger, &str) -> anyhow::Result<IncidentTransition> + Send + 'static,
+{
+    let result = state
+        .ledger
+        .write(move |ledger| {
+            let transition = apply(ledger, &incident_id)?;
+            Ok((transition, ledger.get_incident(&incident_id)?))
+        })
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "incident transition failed");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    match result {
+        (IncidentTransition::NotFound, _) | (_, None) => Err(StatusCode::NOT_FOUND),
+        (IncidentTransition::Unchanged, _) if !unchanged_ok => Err(StatusCode::CONFLICT),
+        (transition, Some(incident)) => {
+            if let IncidentTransition::Applied(event) = transition {
+                tracing::info!(incident_id = %incident.id, event = %event.event_type, status = %incident.status, "incident updated");
+            }
+            Ok(Json(row_to_workspace(incident)))
+        }
+    }
+}
+
+/// Time-to-resolve figures over a set of incidents, in seconds.
+#[derive(Debug, Default, Serialize)]
+pub struct ResolutionMetrics {
+    pub total: usize,
+    pub open: usize,
+    pub resolved: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mean_time_to_resolve_secs: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub median_time_to_resolve_secs: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_time_to_resolve_secs: Option<i64>,
+}
+
+impl ResolutionMetrics {
+    fn of<'a>(incidents: impl Iterator<Item = &'a IncidentWorkspace>) -> Self {
+        let mut metrics = Self::default();
+        let mut times = Vec::new();
+        for incident in incidents {
+            metrics.total += 1;
+            match incident.time_to_resolve_secs {
+                Some(secs) if incident.status == "resolved" => {
+                    metrics.resolved += 1;
+                    times.push(secs);
+                }
+                _ => metrics.open += 1,
+            }
+        }
+        times.sort_unstable();
+        if !times.is_empty() {
+            metrics.mean_time_to_resolve_secs = Some(times.iter().sum::<i64>() / times.len() as i64);
+            metrics.median_time_to_resolve_secs = Some(times[times.len() / 2]);
+            metrics.max_time_to_resolve_secs = times.last().copied();
+        }
+        metrics
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct IncidentMetrics {
+    #[serde(flatten)]
+    pub overall: ResolutionMetrics,
+    pub by_severity: BTreeMap<String, ResolutionMetrics>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IncidentListResponse {
+    pub incidents: Vec<IncidentWorkspace>,
+    /// Over the listed incidents.
+    pub metrics: IncidentMetrics,
+}
 
-    let incidents = ledger
-        .list_incidents(params.status.as_deref())
+/// GET /incidents — list incident workspaces (filter by status, severity, assignee)
+/// with time-to-resolve metrics over the listed ones.
+pub async fn incidents_list_handler(
+    State(state): State<SharedState>,
+    Query(params): Query<IncidentFilter>,
+) -> Result<Json<IncidentListResponse>, axum::http::StatusCode> {
+    let incidents = state
+        .ledger
+        .read(move |ledger| ledger.list_incidents(&params))
+        .await
         .map_err(|e| {
             tracing::error!(error = %e, "failed to list incidents");
             axum::http::StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
-    Ok(Json(incidents.into_iter().map(row_to_workspace).collect()))
+    let incidents: Vec<IncidentWorkspace> = incidents.into_iter().map(row_to_workspace).collect();
+    let by_severity = SEVERITIES
+        .iter()
+        .filter_map(|severity| {
+            let metrics = ResolutionMetrics::of(incidents.iter().filter(|i| i.severity == *severity));
+            (metrics.total > 0).then(|| (severity.to_string(), metrics))
+        })
+        .collect();
+    let metrics = IncidentMetrics { overall: ResolutionMetrics::of(incidents.iter()), by_severity };
+    Ok(Json(IncidentListResponse { incidents, metrics }))
 }
 
 /// Convert a ledger IncidentRow to the API IncidentWorkspace type.
@@ -257,8 +442,15 @@ fn row_to_workspace(row: crate::ledger::IncidentRow) -> IncidentWorkspace {
         id: row.id,
         name: row.name,
         status: row.status,
+        severity: row.severity,
+        affected_services: row.affected_services,
+        assignee: row.assignee,
         created_at: row.created_at,
         resolved_at: row.resolved_at,
+        resolution: row.resolution,
+        time_to_resolve_secs: row.time_to_resolve_secs,
+        reopen_count: row.reopen_count,
+        correlation_key: row.correlation_key,
         steps: row
             .steps
             .into_iter()
@@ -1,9 +1,12 @@
-use axum::extract::State;
+use axum::extract::{ConnectInfo, State};
 use axum::http::StatusCode;
 use axum::Json;
 use serde::{Deserialize, Serialize};
 
-use crate::sandbox::{Ring, SandboxConfig};
+use crate::api::approval::spend_execution_token;
+use crate::peer::PeerIdentity;
+use crate::policy::Decision;
+use crate::sandbox::{CapabilityToken, Ring, SandboxConfig};
 use crate::state::SharedState;
 
 #[derive(Debug, Deserialize)]
@@ -15,6 +18,11 @@ pub struct SandboxExecRequest {
     pub fs_read: Option<Vec<String>>,
     pub fs_write: Option<Vec<String>>,
     pub network: Option<bool>,
+    pub actor: Option<String>,
+    /// Path the command acts on; must match the approval's target.
+    pub target: Option<String>,
+    /// Execution token from the approval, for commands the approval gate holds back.
+    pub approval_token: Option<CapabilityToken>,
 }
 
 #[derive(Debug, Serialize)]
@@ -43,9 +51,12 @@ pub struct VerifyCapabilityResponse {
     pub valid: bool,
 }
 
-/// POST /sandbox/exec — execute a command in a sandbox.
+/// POST /sandbox/exec — execute a command in a sandbox. When the approval gate is
+/// enabled, a command it would hold for approval runs only with the execution token
+/// from an approval of exactly this command and target, which is used up here.
 pub async fn sandbox_exec_handler(
     State(state): State<SharedState>,
+    ConnectInfo(peer): ConnectInfo<PeerIdentity>,
     Json(req): Json<SandboxExecRequest>,
 ) -> Result<Json<SandboxExecResponse>, (StatusCode, Json<serde_json::Value>)> {
     let engine = state.sandbox_engine.as_ref().ok_or_else(|| {
@@ -55,6 +66,43 @@ pub async fn sandbox_exec_handler(
         )
     })?;
 
+    let actor = req.actor.as_deref().unwrap_or("agent");
+    let verified_actor = peer.actor();
+    if !peer.may_claim(actor) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(serde_json::json!({"error": format!("actor '{actor}' is reserved for that daemon; this connection is '{verified_actor}'")})),
+        ));
+    }
+
+    if let Some(gate) = &state.approval_gate {
+        let decided = gate.decide(&req.command, peer.principal().as_deref(), req.target.as_deref());
+        match decided.decision {
+            Decision::Allow => {}
+            Decision::Deny => {
+                return Err((
+                    StatusCode::FORBIDDEN,
+                    Json(serde_json::json!({
+                        "error": format!("denied by approval policy rule '{}'", decided.rule),
+                        "rule": decided.rule,
+                    })),
+                ));
+            }
+            Decision::RequireApproval { .. } => {
+                let Some(token) = &req.approval_token else {
+                    return Err((
+                        StatusCode::FORBIDDEN,
+                        Json(serde_json::json!({
+                            "error": "command requires approval; pass the approved request's execution_token as approval_token",
+                            "rule": decided.rule,
+                        })),
+                    ));
+                };
+                spend_execution_token(&state, gate, &peer, actor, token, &req.command, req.target.as_deref()).await?;
+            }
+        }
+    }
+
     let ring = match req.ring.as_deref() {
         Some("ring1") => Ring::Ring1,
         _ => Ring::Ring2,
@@ -72,13 +120,14 @@ pub async fn sandbox_exec_handler(
 
     // Log the sandbox execution
     {
-        let ledger = state.ledger.lock().await;
         let payload = serde_json::json!({
             "command": req.command,
             "ring": ring.to_string(),
             "network": config.network,
         });
-        let _ = ledger.append("sandbox.exec", "agent", &payload.to_string());
+        if let Err(e) = state.ledger.append_as("sandbox.exec", actor, Some(&verified_actor), &payload.to_string()).await {
+            tracing::error!(error = %e, "failed to log sandbox execution to ledger");
+        }
     }
 
     match engine.spawn_sandboxed(&config, &req.command).await {
@@ -113,14 +162,15 @@ pub async fn capability_mint_handler(
 
     // Log token minting
     {
-        let ledger = state.ledger.lock().await;
         let payload = serde_json::json!({
             "token_id": token.id,
             "granted_to": token.granted_to,
             "permissions": token.permissions,
             "ttl_secs": ttl,
         });
-        let _ = ledger.append("capability.mint", "agent", &payload.to_string());
+        if let Err(e) = state.ledger.append("capability.mint", "agent", &payload.to_string()).await {
+            tracing::error!(error = %e, "failed to log capability mint to ledger");
+        }
     }
 
     Ok(Json(token))
@@ -58,14 +58,13 @@ pub async fn system_query_handler(
 
     // Log the query to the ledger
     {
-        let ledger = state.ledger.lock().await;
         let payload = serde_json::to_string(&json!({
             "query": &body.query,
             "args": &body.args,
         }))
         .unwrap_or_default();
 
-        if let Err(e) = ledger.append("system.query", "agentd", &payload) {
+        if let Err(e) = state.ledger.append("system.query", "agentd", &payload).await {
             tracing::error!(error = %e, "failed to log system query to ledger");
         }
     }
@@ -0,0 +1,330 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::Arc;
+
+use anyhow::Result;
+use serde_json::json;
+
+use crate::embedding::{self, Embedder};
+use crate::ledger::{Event, MemoryRecord};
+use crate::ledger_handle::LedgerHandle;
+
+/// Estimated Jaccard similarity (over character shingles) at which two memories
+/// count as near-duplicates.
+pub const DEFAULT_SIMILARITY: f64 = 0.7;
+
+/// Most recent memories considered per pass.
+const MAX_CANDIDATES: i64 = 20_000;
+
+/// Shingle width in characters. Short enough that one changed word in a
+/// one-line note still leaves most shingles shared.
+const SHINGLE_CHARS: usize = 5;
+
+/// LSH banding of the MinHash signature: two memories become a candidate pair when
+/// all rows of any band agree. 32 × 4 finds pairs at 0.7 similarity ~99.9% of the time.
+const BANDS: usize = 32;
+const ROWS: usize = 4;
+const SIGNATURE_LEN: usize = BANDS * ROWS;
+
+/// MinHash signature: the minimum of each of `SIGNATURE_LEN` hash functions over a
+/// text's shingles. The share of positions two signatures agree on estimates the
+/// Jaccard similarity of their shingle sets.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Signature([u64; SIGNATURE_LEN]);
+
+impl Signature {
+    /// None when the text has no alphanumeric content to shingle.
+    pub fn of(text: &str) -> Option<Self> {
+        let shingles = shingles(text);
+        if shingles.is_empty() {
+            return None;
+        }
+        let mut mins = [u64::MAX; SIGNATURE_LEN];
+        for shingle in shingles {
+            for (i, min) in mins.iter_mut().enumerate() {
+                *min = (*min).min(splitmix64(shingle ^ (i as u64).wrapping_mul(0x9e3779b97f4a7c15)));
+            }
+        }
+        Some(Self(mins))
+    }
+
+    pub fn similarity(&self, other: &Signature) -> f64 {
+        let agree = self.0.iter().zip(other.0.iter()).filter(|(a, b)| a == b).count();
+        agree as f64 / SIGNATURE_LEN as f64
+    }
+
+    fn band_key(&self, band: usize) -> u64 {
+        let rows = &self.0[band * ROWS..(band + 1) * ROWS];
+        rows.iter().fold(band as u64, |acc, v| splitmix64(acc ^ v))
+    }
+}
+
+/// Hashed character shingles of the text, lowercased with punctuation and
+/// whitespace runs collapsed to single spaces.
+fn shingles(text: &str) -> HashSet<u64> {
+    let normalized: Vec<char> = text
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .collect();
+    if normalized.is_empty() {
+        return HashSet::new();
+    }
+    if normalized.len() <= SHINGLE_CHARS {
+        let whole: String = normalized.iter().collect();
+        return HashSet::from([embedding::fnv1a(whole.as_bytes())]);
+    }
+    normalized
+        .windows(SHINGLE_CHARS)
+        .map(|w| embedding::fnv1a(w.iter().collect::<String>().as_bytes()))
+        .collect()
+}
+
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+/// Group signatures into clusters of near-duplicates (estimated similarity at or
+/// above `threshold`, joined transitively). Returns clusters of two or more, as
+/// indices into `signatures` in ascending order.
+pub fn clusters(signatures: &[Signature], threshold: f64) -> Vec<Vec<usize>> {
+    let mut parent: Vec<usize> = (0..signatures.len()).collect();
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+
+    let mut checked = HashSet::new();
+    for band in 0..BANDS {
+        let mut buckets: HashMap<u64, Vec<usize>> = HashMap::new();
+        for (i, sig) in signatures.iter().enumerate() {
+            buckets.entry(sig.band_key(band)).or_default().push(i);
+        }
+        for bucket in buckets.values().filter(|b| b.len() > 1) {
+            for (n, &a) in bucket.iter().enumerate() {
+                for &b in &bucket[n + 1..] {
+                    if checked.insert((a, b)) && signatures[a].similarity(&signatures[b]) >= threshold {
+                        let (ra, rb) = (root(&mut parent, a), root(&mut parent, b));
+                        if ra != rb {
+                            parent[ra.max(rb)] = ra.min(rb);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..signatures.len() {
+        let r = root(&mut parent, i);
+        groups.entry(r).or_default().push(i);
+    }
+    let mut clusters: Vec<Vec<usize>> = groups.into_values().filter(|g| g.len() > 1).collect();
+    clusters.sort();
+    clusters
+}
+
+/// Payload of the memory.ingest entry replacing `members` (oldest first): the newest
+/// member's content and category, every tag, and in `metadata` how many notes it
+/// stands for, who sent them and when. Members that are themselves consolidated
+/// contribute their own counts.
+pub fn merged_payload(namespace: &str, members: &[Event]) -> String {
+    let parsed: Vec<serde_json::Value> = members
+        .iter()
+        .map(|e| serde_json::from_str(&e.payload).unwrap_or(json!({})))
+        .collect();
+    let newest = parsed.last().cloned().unwrap_or(json!({}));
+
+    let mut tags = BTreeSet::new();
+    let mut sources = BTreeSet::new();
+    let mut duplicates = 0;
+    let mut first_seen = None::<String>;
+    let mut last_seen = None::<String>;
+    let mut category = None;
+    for (event, payload) in members.iter().zip(&parsed) {
+        let metadata = &payload["metadata"];
+        if let Some(t) = payload["tags"].as_array() {
+            tags.extend(t.iter().filter_map(|t| t.as_str().map(str::to_string)));
+        }
+        match metadata["sources"].as_array() {
+            Some(s) if payload.get("consolidates").is_some() => {
+                sources.extend(s.iter().filter_map(|s| s.as_str().map(str::to_string)));
+            }
+            _ => {
+                sources.insert(payload["source"].as_str().unwrap_or(&event.actor).to_string());
+            }
+        }
+        duplicates += metadata["duplicates"].as_u64().filter(|_| payload.get("consolidates").is_some()).unwrap_or(1);
+        let first = metadata["first_seen"].as_str().unwrap_or(&event.ts).to_string();
+        let last = metadata["last_seen"].as_str().unwrap_or(&event.ts).to_string();
+        if first_seen.as_ref().is_none_or(|f| first < *f) {
+            first_seen = Some(first);
+        }
+        if last_seen.as_ref().is_none_or(|l| last > *l) {
+            last_seen = Some(last);
+        }
+        if let Some(c) = payload["category"].as_str() {
+            category = Some(c.to_string());
+        }
+    }
+
+    json!({
+        "source": "agentd",
+        "content": newest["content"],
+        "category": category,
+        "tags": tags,
+        "metadata": {
+            "duplicates": duplicates,
+            "sources": sources,
+            "first_seen": first_seen,
+            "last_seen": last_seen,
+        },
+        "namespace": namespace,
+        "consolidates": members.iter().map(|e| e.id).collect::<Vec<_>>(),
+    })
+    .to_string()
+}
+
+/// One consolidation pass: cluster near-duplicate memory.ingest entries within each
+/// namespace and replace every cluster with a consolidated entry that links its
+/// sources. Returns how many consolidated entries were written.
+pub async fn run_pass(ledger: &LedgerHandle, embedder: &Arc<dyn Embedder>, threshold: f64) -> Result<usize> {
+    // Clustering is CPU work; do it on the reader's blocking thread
+    let merges: Vec<(String, Vec<i64>, String)> = ledger
+        .read(move |l| {
+            let candidates = l.consolidation_candidates(MAX_CANDIDATES)?;
+            let mut merges = Vec::new();
+            for namespace_group in candidates.chunk_by(|a, b| a.0.namespace == b.0.namespace) {
+                merges.extend(plan_namespace(namespace_group, threshold));
+            }
+            Ok(merges)
+        })
+        .await?;
+
+    let mut written = 0;
+    for (namespace, sources, payload) in merges {
+        let text = embedding::ingest_text(&payload);
+        let event = ledger.write(move |l| l.consolidate_memories(&namespace, &sources, &payload)).await?;
+        let Some(event) = event else {
+            continue;
+        };
+        written += 1;
+        if let Some(text) = text {
+            let model = embedder.model_id().to_string();
+            let stored = match embedding::embed_blocking(embedder, text).await {
+                Ok(vector) => ledger.write(move |l| l.store_vector(event.id, &model, &vector)).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = stored {
+                tracing::warn!(error = %e, event_id = event.id, "failed to embed consolidated memory");
+            }
+        }
+    }
+    Ok(written)
+}
+
+/// (namespace, source ids, payload) for each cluster among one namespace's memories.
+fn plan_namespace(memories: &[(MemoryRecord, Event)], threshold: f64) -> Vec<(String, Vec<i64>, String)> {
+    let (indexed, signatures): (Vec<usize>, Vec<Signature>) = memories
+        .iter()
+        .enumerate()
+        .filter_map(|(i, (_, event))| {
+            let text = embedding::ingest_text(&event.payload)?;
+            Some((i, Signature::of(&text)?))
+        })
+        .unzip();
+
+    clusters(&signatures, threshold)
+        .into_iter()
+        .map(|cluster| {
+            let members: Vec<Event> = cluster.iter().map(|&i| memories[indexed[i]].1.clone()).collect();
+            let namespace = memories[indexed[cluster[0]]].0.namespace.clone();
+            let payload = merged_payload(&namespace, &members);
+            (namespace, members.iter().map(|e| e.id).collect(), payload)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::{ForgetTarget, Ledger, MemoryOptions};
+
+    #[test]
+    fn test_signature_similarity_tracks_overlap() {
+        let a = Signature::of("nginx restarted on web1 after config reload").unwrap();
+        let b = Signature::of("nginx restarted on web1 after config reload (2)").unwrap();
+        let c = Signature::of("postgres backup completed in 4 minutes").unwrap();
+        assert!(a.similarity(&b) >= DEFAULT_SIMILARITY, "{}", a.similarity(&b));
+        assert!(a.similarity(&c) < 0.2, "{}", a.similarity(&c));
+        assert_eq!(Signature::of("NGINX restarted, on web1 after config-reload"), Signature::of("nginx restarted on web1 after config reload"));
+        assert!(Signature::of(" -- ").is_none());
+    }
+
+    #[test]
+    fn test_clusters_group_near_duplicates() {
+        let texts = [
+            "disk usage on /var above 90% on db1",
+            "backup finished",
+            "disk usage on /var above 91% on db1",
+            "certificate for example.org renews in 7 days",
+            "disk usage on /var above 92% on db1",
+            "backup finished!",
+        ];
+        let signatures: Vec<_> = texts.iter().map(|t| Signature::of(t).unwrap()).collect();
+        assert_eq!(clusters(&signatures, DEFAULT_SIMILARITY), vec![vec![0, 2, 4], vec![1, 5]]);
+    }
+
+    fn remember(ledger: &Ledger, source: &str, content: &str, tags: &[&str]) -> Event {
+        let payload = json!({"source": source, "content": content, "tags": tags}).to_string();
+        let options = MemoryOptions { namespace: "agent".into(), ttl_secs: None, pinned: false };
+        ledger.remember("memory.ingest", source, None, &payload, &options).unwrap().0
+    }
+
+    #[test]
+    fn test_consolidation_supersedes_sources() {
+        let ledger = Ledger::new(":memory:").unwrap();
+        let a = remember(&ledger, "osmoda-watch", "nginx on web1 failed health check", &["web"]);
+        let b = remember(&ledger, "osmoda-teachd", "nginx on web1 failed health check.", &["nginx"]);
+        let other = remember(&ledger, "voice", "rotate the staging ssh keys", &[]);
+
+        let candidates = ledger.consolidation_candidates(100).unwrap();
+        let plan = plan_namespace(&candidates, DEFAULT_SIMILARITY);
+        assert_eq!(plan.len(), 1);
+        let (namespace, sources, payload) = &plan[0];
+        assert_eq!(sources, &vec![a.id, b.id]);
+
+        let merged = ledger.consolidate_memories(namespace, sources, payload).unwrap().unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&merged.payload).unwrap();
+        assert_eq!(parsed["content"], "nginx on web1 failed health check.");
+        assert_eq!(parsed["tags"], json!(["nginx", "web"]));
+        assert_eq!(parsed["metadata"]["duplicates"], 2);
+        assert_eq!(parsed["metadata"]["sources"], json!(["osmoda-teachd", "osmoda-watch"]));
+
+        let records = ledger.memory_records(&[a.id, b.id, other.id, merged.id]).unwrap();
+        assert_eq!(records[&a.id].superseded_by, Some(merged.id));
+        assert_eq!(records[&b.id].superseded_by, Some(merged.id));
+        assert_eq!(records[&other.id].superseded_by, None);
+        // Sources are already superseded, so the same plan cannot apply twice
+        assert!(ledger.consolidate_memories(namespace, sources, payload).unwrap().is_none());
+
+        // A later duplicate folds into the consolidated entry
+        let c = remember(&ledger, "osmoda-mcpd", "nginx on web1 failed health check", &[]);
+        let plan = plan_namespace(&ledger.consolidation_candidates(100).unwrap(), DEFAULT_SIMILARITY);
+        assert_eq!(plan[0].1, vec![merged.id, c.id]);
+        let parsed: serde_json::Value = serde_json::from_str(&plan[0].2).unwrap();
+        assert_eq!(parsed["metadata"]["duplicates"], 3);
+
+        // Forgetting the consolidated entry brings its sources back
+        ledger.forget_memories(&ForgetTarget::Event(merged.id), "wrong merge", "agent", None).unwrap().unwrap();
+        assert_eq!(ledger.memory_records(&[a.id]).unwrap()[&a.id].superseded_by, None);
+    }
+}
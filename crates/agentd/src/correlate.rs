@@ -0,0 +1,368 @@
+//! Correlation engine: turns failure and recovery signals from the other daemons into
+//! incidents. A failure opens an incident keyed by the affected service, or attaches
+//! to the one already open for that service if it saw activity within the window, or
+//! reopens the one resolved within the window (so a crash/restart loop stays one
+//! incident); every signal becomes a step linked to its receipt, and a recovery
+//! resolves the service's open incidents. Incidents opened by hand are never touched.
+
+use anyhow::Result;
+use serde_json::{json, Value};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::ledger::{format_ts, Event, IncidentFields, IncidentTransition, Ledger, NewIncidentStep};
+use crate::ledger_handle::LedgerHandle;
+
+/// Minutes after an incident's last step during which a new failure for the same
+/// service attaches to it instead of opening another incident.
+pub const DEFAULT_WINDOW_MINS: u64 = 30;
+
+/// Events read per page when catching up after the live feed lagged.
+const CATCH_UP_PAGE: i64 = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignalKind {
+    Failure,
+    Recovery,
+}
+
+/// Maps a signal type to what it means for the service it names.
+struct Rule {
+    /// Signal type; also matches with a reporter prefix (`server.crash` matches
+    /// `mcp.server.crash` as logged by osmoda-mcpd).
+    signal: &'static str,
+    /// The daemon that reports it; the same signal from anyone else is ignored.
+    owner: &'static str,
+    kind: SignalKind,
+    /// Severity of an incident this signal opens.
+    severity: &'static str,
+}
+
+const RULES: &[Rule] = &[
+    Rule { signal: "watch.watcher.degraded", owner: "osmoda-watch", kind: SignalKind::Failure, severity: "high" },
+    Rule { signal: "watch.watcher.recovered", owner: "osmoda-watch", kind: SignalKind::Recovery, severity: "high" },
+    Rule { signal: "server.crash", owner: "osmoda-mcpd", kind: SignalKind::Failure, severity: "high" },
+    Rule { signal: "server.start_failed", owner: "osmoda-mcpd", kind: SignalKind::Failure, severity: "medium" },
+    Rule { signal: "server.restart", owner: "osmoda-mcpd", kind: SignalKind::Recovery, severity: "medium" },
+    Rule { signal: "server.start", owner: "osmoda-mcpd", kind: SignalKind::Recovery, severity: "medium" },
+    Rule { signal: "routine.failed", owner: "osmoda-routines", kind: SignalKind::Failure, severity: "medium" },
+    Rule { signal: "routine.recovered", owner: "osmoda-routines", kind: SignalKind::Recovery, severity: "medium" },
+];
+
+/// Payload fields naming the affected service, in order of preference.
+const SERVICE_FIELDS: &[&str] = &["service", "watcher", "server_name", "routine", "unit"];
+
+/// Payload fields describing what happened, in order of preference.
+const SUMMARY_FIELDS: &[&str] = &["detail", "summary", "error", "reason"];
+
+/// A failure or recovery signal extracted from a ledger event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Signal {
+    pub kind: SignalKind,
+    /// e.g. `watch.watcher.degraded`.
+    pub signal_type: String,
+    /// The correlation key.
+    pub service: String,
+    pub summary: String,
+    pub severity: &'static str,
+}
+
+/// What the engine did with a signal.
+#[derive(Debug, PartialEq)]
+pub enum Correlation {
+    Opened(String),
+    Attached(String),
+    /// A failure shortly after the service's last incident was resolved.
+    Reopened(String),
+    Resolved(Vec<String>),
+    /// A recovery for a service with no open incident.
+    Ignored,
+}
+
+fn matches(signal_type: &str, rule: &str) -> bool {
+    signal_type == rule || signal_type.strip_suffix(rule).is_some_and(|prefix| prefix.ends_with('.'))
+}
+
+/// The signal an event carries, if any. Daemons report through `/memory/ingest` with
+/// the signal type as the category and JSON details as the content; events appended
+/// under their own type carry the details as the payload. Only the daemon that owns a
+/// signal, as verified over the socket, can raise it — anyone can ingest memories.
+pub fn classify(event: &Event) -> Option<Signal> {
+    let payload: Value = serde_json::from_str(&event.payload).ok()?;
+    let (signal_type, details) = match event.event_type.as_str() {
+        "memory.ingest" => {
+            let category = payload.get("category")?.as_str()?.to_string();
+            let content = payload.get("content").and_then(Value::as_str).unwrap_or_default();
+            let details = serde_json::from_str::<Value>(content)
+                .ok()
+                .filter(Value::is_object)
+                .unwrap_or_else(|| json!({ "detail": content }));
+            (category, details)
+        }
+        other => (other.to_string(), payload),
+    };
+    let rule = RULES.iter().find(|r| matches(&signal_type, r.signal))?;
+    if event.verified_actor.as_deref() != Some(rule.owner) {
+        return None;
+    }
+
+    let text = |fields: &[&str]| {
+        fields
+            .iter()
+            .find_map(|f| details.get(*f).and_then(Value::as_str).filter(|s| !s.is_empty()))
+            .map(str::to_string)
+    };
+    let service = text(SERVICE_FIELDS).unwrap_or_else(|| event.actor.clone());
+    let summary = text(SUMMARY_FIELDS)
+        .or_else(|| {
+            // osmoda-watch reports the escalation actions it took
+            let actions = details.get("actions")?.as_array()?;
+            let actions: Vec<&str> = actions.iter().filter_map(Value::as_str).collect();
+            (!actions.is_empty()).then(|| actions.join("; "))
+        })
+        .unwrap_or_else(|| signal_type.clone());
+
+    Some(Signal { kind: rule.kind, signal_type, service, summary, severity: rule.severity })
+}
+
+/// Apply a signal from `event` to the incident workspaces. Runs on the writer.
+pub fn apply(ledger: &Ledger, event: &Event, signal: &Signal, window: chrono::Duration) -> Result<Correlation> {
+    let open = ledger.open_correlated_incidents(&signal.service)?;
+    let receipt_id = format!("receipt-{}", event.id);
+    let step = NewIncidentStep { action: &signal.signal_type, result: &signal.summary, receipt_id: Some(&receipt_id) };
+
+    match signal.kind {
+        SignalKind::Failure => {
+            let cutoff = format_ts(chrono::Utc::now() - window);
+            if let Some((id, _)) = open.iter().find(|(_, last_activity)| *last_activity >= cutoff) {
+                ledger.add_incident_step(id, &step, None)?;
+                return Ok(Correlation::Attached(id.clone()));
+            }
+            if let Some(id) = ledger.recently_resolved_correlated_incident(&signal.service, &cutoff)? {
+                let reason = format!("{} failed again ({}): {}", signal.service, signal.signal_type, signal.summary);
+                ledger.reopen_incident(&id, &reason, None)?;
+                ledger.add_incident_step(&id, &step, None)?;
+                return Ok(Correlation::Reopened(id));
+            }
+
+            let id = uuid::Uuid::new_v4().to_string();
+            let fields = IncidentFields {
+                severity: signal.severity.to_string(),
+                affected_services: vec![signal.service.clone()],
+                assignee: None,
+                correlation_key: Some(signal.service.clone()),
+            };
+            let name = format!("{}: {}", signal.service, signal.signal_type);
+            ledger.create_incident_with_step(&id, &name, &fields, &step, None)?;
+            Ok(Correlation::Opened(id))
+        }
+        SignalKind::Recovery => {
+            if open.is_empty() {
+                return Ok(Correlation::Ignored);
+            }
+            let resolution = format!("{} recovered ({}): {}", signal.service, signal.signal_type, signal.summary);
+            let mut resolved = Vec::new();
+            for (id, _) in open {
+                ledger.add_incident_step(&id, &step, None)?;
+                if let IncidentTransition::Applied(_) = ledger.resolve_incident(&id, &resolution, None)? {
+                    resolved.push(id);
+                }
+            }
+            Ok(Correlation::Resolved(resolved))
+        }
+    }
+}
+
+/// Follow the ledger from its current head and correlate every signal. Catches up
+/// from the ledger when the live feed lags, so no signal is skipped.
+pub async fn run(ledger: LedgerHandle, window: chrono::Duration) {
+    let mut live = ledger.subscribe();
+    let mut cursor = match ledger.read(|l| l.head_id()).await {
+        Ok(id) => id,
+        Err(e) => {
+            tracing::error!(error = %e, "correlation engine could not read the ledger head");
+            return;
+        }
+    };
+
+    loop {
+        match live.recv().await {
+            Ok(event) if event.id <= cursor => continue,
+            Ok(event) if event.id == cursor + 1 => {
+                cursor = event.id;
+                correlate(&ledger, event, window).await;
+            }
+            Ok(_) | Err(RecvError::Lagged(_)) => loop {
+                let page = match ledger.read(move |l| l.events_after(cursor, CATCH_UP_PAGE)).await {
+                    Ok(page) => page,
+                    Err(e) => {
+                        tracing::warn!(error = %e, cursor, "correlation catch-up failed");
+                        break;
+                    }
+                };
+                if page.is_empty() {
+                    break;
+                }
+                for event in page {
+                    cursor = event.id;
+                    correlate(&ledger, event, window).await;
+                }
+            },
+            Err(RecvError::Closed) => return,
+        }
+    }
+}
+
+async fn correlate(ledger: &LedgerHandle, event: Event, window: chrono::Duration) {
+    let Some(signal) = classify(&event) else {
+        return;
+    };
+    let (event_id, service) = (event.id, signal.service.clone());
+    match ledger.write(move |l| apply(l, &event, &signal, window)).await {
+        Ok(Correlation::Opened(id)) => tracing::info!(incident_id = %id, %service, event_id, "opened incident"),
+        Ok(Correlation::Attached(id)) => tracing::debug!(incident_id = %id, %service, event_id, "attached signal to incident"),
+        Ok(Correlation::Reopened(id)) => tracing::info!(incident_id = %id, %service, event_id, "reopened incident"),
+        Ok(Correlation::Resolved(ids)) => tracing::info!(incidents = ?ids, %service, event_id, "resolved incidents on recovery"),
+        Ok(Correlation::Ignored) => {}
+        Err(e) => tracing::warn!(error = %e, %service, event_id, "failed to correlate signal"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ingest(ledger: &Ledger, source: &str, category: &str, content: Value) -> Event {
+        let payload = json!({ "source": source, "content": content.to_string(), "category": category });
+        ledger.append_as("memory.ingest", source, Some(source), &payload.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_classify_ingested_signals() {
+        let ledger = Ledger::new(":memory:").unwrap();
+        let crash = ingest(
+            &ledger,
+            "osmoda-mcpd",
+            "mcp.server.crash",
+            json!({ "event_type": "server.crash", "server_name": "github", "detail": "process exited unexpectedly" }),
+        );
+        let signal = classify(&crash).unwrap();
+        assert_eq!(signal.kind, SignalKind::Failure);
+        assert_eq!(signal.service, "github");
+        assert_eq!(signal.summary, "process exited unexpectedly");
+
+        let degraded = ingest(
+            &ledger,
+            "osmoda-watch",
+            "watch.watcher.degraded",
+            json!({ "watcher": "nginx", "actions": ["nginx: restarted nginx.service"] }),
+        );
+        let signal = classify(&degraded).unwrap();
+        assert_eq!((signal.service.as_str(), signal.severity), ("nginx", "high"));
+        assert_eq!(signal.summary, "nginx: restarted nginx.service");
+
+        let note = ingest(&ledger, "osmoda-teachd", "teach.pattern", json!({ "detail": "x" }));
+        assert!(classify(&note).is_none());
+        assert!(!matches("mcp.server.startup", "server.start"));
+        assert!(!matches("myserver.crash", "server.crash"));
+    }
+
+    #[test]
+    fn test_forged_signals_are_ignored() {
+        let ledger = Ledger::new(":memory:").unwrap();
+        let failed = json!({ "routine": "backup", "error": "disk full" });
+        let forge = |verified: Option<&str>| {
+            let payload = json!({ "source": "osmoda-routines", "content": failed.to_string(), "category": "routine.failed" });
+            ledger.append_as("memory.ingest", "osmoda-routines", verified, &payload.to_string()).unwrap()
+        };
+
+        // Any local process can ingest a memory with a signal's category
+        assert!(classify(&forge(Some("uid:1000:curl"))).is_none());
+        assert!(classify(&forge(None)).is_none());
+        // A real daemon cannot raise another daemon's signals
+        assert!(classify(&forge(Some("osmoda-watch"))).is_none());
+        assert!(classify(&forge(Some("osmoda-routines"))).is_some());
+    }
+
+    #[test]
+    fn test_failures_open_attach_and_recovery_resolves() {
+        let ledger = Ledger::new(":memory:").unwrap();
+        let window = chrono::Duration::minutes(30);
+        let run = |event: Event| {
+            let signal = classify(&event).unwrap();
+            apply(&ledger, &event, &signal, window).unwrap()
+        };
+
+        let first = ingest(&ledger, "osmoda-routines", "routine.failed", json!({ "routine": "backup", "error": "disk full" }));
+        let Correlation::Opened(id) = run(first.clone()) else { panic!("expected a new incident") };
+        let second = ingest(&ledger, "osmoda-routines", "routine.failed", json!({ "routine": "backup", "error": "disk full" }));
+        assert_eq!(run(second), Correlation::Attached(id.clone()));
+
+        // Another service gets its own incident
+        let other = ingest(&ledger, "osmoda-routines", "routine.failed", json!({ "routine": "logscan", "error": "timeout" }));
+        let Correlation::Opened(other_id) = run(other) else { panic!("expected a new incident") };
+        assert_ne!(other_id, id);
+
+        let recovered = ingest(&ledger, "osmoda-routines", "routine.recovered", json!({ "routine": "backup" }));
+        assert_eq!(run(recovered.clone()), Correlation::Resolved(vec![id.clone()]));
+        assert_eq!(run(recovered), Correlation::Ignored);
+
+        let incident = ledger.get_incident(&id).unwrap().unwrap();
+        assert_eq!(incident.status, "resolved");
+        assert_eq!(incident.severity, "medium");
+        assert_eq!(incident.affected_services, vec!["backup"]);
+        assert_eq!(incident.correlation_key.as_deref(), Some("backup"));
+        let steps: Vec<&str> = incident.steps.iter().map(|s| s.action.as_str()).collect();
+        assert_eq!(steps, vec!["routine.failed", "routine.failed", "routine.recovered"]);
+        assert_eq!(incident.steps[0].receipt_id, Some(format!("receipt-{}", first.id)));
+        assert_eq!(incident.steps[0].result, "disk full");
+        assert_eq!(ledger.get_incident(&other_id).unwrap().unwrap().status, "open");
+
+        // A failure soon after recovery reopens the incident; once the window has
+        // passed it opens a fresh one
+        let again = ingest(&ledger, "osmoda-routines", "routine.failed", json!({ "routine": "backup", "error": "disk full" }));
+        assert_eq!(run(again.clone()), Correlation::Reopened(id.clone()));
+        assert_eq!(run(recovered_signal(&ledger, "backup")), Correlation::Resolved(vec![id.clone()]));
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let signal = classify(&again).unwrap();
+        let opened = apply(&ledger, &again, &signal, chrono::Duration::zero()).unwrap();
+        assert!(matches!(opened, Correlation::Opened(new_id) if new_id != id));
+    }
+
+    fn recovered_signal(ledger: &Ledger, routine: &str) -> Event {
+        ingest(ledger, "osmoda-routines", "routine.recovered", json!({ "routine": routine }))
+    }
+
+    #[test]
+    fn test_crash_restart_loop_stays_one_incident() {
+        let ledger = Ledger::new(":memory:").unwrap();
+        let window = chrono::Duration::minutes(30);
+        let mut outcomes = Vec::new();
+        for _ in 0..3 {
+            for (category, detail) in [("mcp.server.crash", "process exited unexpectedly"), ("mcp.server.restart", "restarted after crash")] {
+                let event = ingest(&ledger, "osmoda-mcpd", category, json!({ "server_name": "github", "detail": detail }));
+                outcomes.push(apply(&ledger, &event, &classify(&event).unwrap(), window).unwrap());
+            }
+        }
+
+        let Correlation::Opened(id) = &outcomes[0] else { panic!("expected a new incident") };
+        assert_eq!(outcomes[2], Correlation::Reopened(id.clone()));
+        assert_eq!(outcomes[4], Correlation::Reopened(id.clone()));
+        let incident = ledger.get_incident(id).unwrap().unwrap();
+        assert_eq!(incident.reopen_count, 2);
+        assert_eq!(incident.steps.len(), 6);
+        assert_eq!(incident.status, "resolved");
+    }
+
+    #[test]
+    fn test_failure_outside_window_opens_new_incident() {
+        let ledger = Ledger::new(":memory:").unwrap();
+        let event = ingest(&ledger, "osmoda-watch", "watch.watcher.degraded", json!({ "watcher": "nginx" }));
+        let signal = classify(&event).unwrap();
+        let Correlation::Opened(first) = apply(&ledger, &event, &signal, chrono::Duration::minutes(30)).unwrap() else {
+            panic!("expected a new incident");
+        };
+        // A window reaching back to before the first step cannot attach
+        let outcome = apply(&ledger, &event, &signal, chrono::Duration::minutes(-1)).unwrap();
+        assert!(matches!(outcome, Correlation::Opened(id) if id != first));
+    }
+}
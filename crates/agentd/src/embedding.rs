@@ -0,0 +1,371 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::ledger_handle::LedgerHandle;
+
+/// Width of the hashing baseline's vectors.
+pub const HASHING_DIMENSIONS: usize = 256;
+
+/// memory.ingest events embedded per backfill page.
+const BACKFILL_PAGE: usize = 200;
+
+/// Turns text into a fixed-width vector for semantic recall. Implementations must be
+/// deterministic: stored vectors are only compared with query vectors from the same
+/// `model_id`.
+pub trait Embedder: Send + Sync {
+    /// Stable name stored next to each vector; changing the model invalidates old vectors.
+    fn model_id(&self) -> &str;
+    fn dimensions(&self) -> usize;
+    /// L2-normalised embedding, so cosine similarity is a dot product.
+    fn embed(&self, text: &str) -> Result<Vec<f32>>;
+}
+
+/// Offline baseline: signed feature hashing of stemmed tokens, with common ops
+/// vocabulary folded into shared concepts so "nginx down" lands near "web server crashed".
+pub struct HashingEmbedder {
+    model_id: String,
+    dimensions: usize,
+}
+
+/// Words that mean the same thing in an ops context. Each word also keeps its own
+/// (lighter) feature so exact matches still rank above synonyms.
+const CONCEPTS: &[(&str, &[&str])] = &[
+    ("webserver", &["nginx", "apache", "httpd", "caddy", "web", "webserver", "http", "https", "site", "website"]),
+    ("failure", &["down", "crash", "fail", "failure", "dead", "die", "died", "stop", "unreachable", "outage", "broken", "error", "panic", "hang"]),
+    ("database", &["postgres", "postgresql", "mysql", "mariadb", "sqlite", "db", "database", "redis"]),
+    ("disk", &["disk", "storage", "filesystem", "volume", "partition", "inode"]),
+    ("memory", &["memory", "ram", "oom", "swap"]),
+    ("network", &["network", "dns", "connection", "connect", "timeout", "latency", "packet", "firewall"]),
+    ("restart", &["restart", "reboot", "reload", "respawn", "bounce"]),
+    ("cpu", &["cpu", "load", "processor", "throttle"]),
+    ("auth", &["ssh", "login", "auth", "authentication", "password", "unauthorized", "credential", "sudo"]),
+    ("tls", &["cert", "certificate", "tls", "ssl", "acme", "letsencrypt"]),
+    ("deploy", &["deploy", "release", "rollout", "upgrade", "update", "switch", "rebuild"]),
+];
+
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "been", "by", "for", "from", "has", "have", "in", "is", "it",
+    "its", "of", "on", "or", "that", "the", "this", "to", "was", "were", "will", "with",
+];
+
+impl HashingEmbedder {
+    pub fn new(dimensions: usize) -> Self {
+        Self {
+            model_id: format!("hashing-v1-{dimensions}"),
+            dimensions,
+        }
+    }
+
+    fn add_feature(&self, vector: &mut [f32], feature: &str, weight: f32) {
+        let h = fnv1a(feature.as_bytes());
+        let bucket = (h % self.dimensions as u64) as usize;
+        let sign = if h >> 63 == 0 { 1.0 } else { -1.0 };
+        vector[bucket] += sign * weight;
+    }
+}
+
+impl Default for HashingEmbedder {
+    fn default() -> Self {
+        Self::new(HASHING_DIMENSIONS)
+    }
+}
+
+impl Embedder for HashingEmbedder {
+    fn model_id(&self) -> &str {
+        &self.model_id
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let mut vector = vec![0.0; self.dimensions];
+        let lower = text.to_lowercase();
+        for token in lower.split(|c: char| !c.is_alphanumeric()).filter(|t| !t.is_empty()) {
+            if STOPWORDS.contains(&token) {
+                continue;
+            }
+            let stem = stem(token);
+            match concept(stem) {
+                Some(concept) => {
+                    self.add_feature(&mut vector, concept, 1.0);
+                    self.add_feature(&mut vector, stem, 0.5);
+                }
+                None => self.add_feature(&mut vector, stem, 1.0),
+            }
+        }
+        normalize(&mut vector);
+        Ok(vector)
+    }
+}
+
+/// Strip common English inflections ("crashed" → "crash", "restarting" → "restart").
+fn stem(token: &str) -> &str {
+    for suffix in ["ing", "ed", "es", "s"] {
+        if let Some(stripped) = token.strip_suffix(suffix) {
+            if stripped.len() >= 3 {
+                // "died" → "di" would be too short; "stopped" → "stopp" → "stop"
+                let bytes = stripped.as_bytes();
+                let n = bytes.len();
+                if suffix != "s" && n >= 2 && bytes[n - 1] == bytes[n - 2] {
+                    return &stripped[..n - 1];
+                }
+                return stripped;
+            }
+        }
+    }
+    token
+}
+
+fn concept(stem: &str) -> Option<&'static str> {
+    CONCEPTS
+        .iter()
+        .find(|(_, words)| words.contains(&stem) || words.iter().any(|w| stem.len() > 3 && w.starts_with(stem)))
+        .map(|(concept, _)| *concept)
+}
+
+/// FNV-1a: stable across Rust versions, unlike `DefaultHasher`, so stored vectors stay valid.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for b in bytes {
+        hash ^= *b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+fn normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    }
+}
+
+/// Cosine similarity of two L2-normalised vectors (0 when the widths differ).
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Pick the embedder: an ONNX sentence-embedding model when `model` is given (needs the
+/// `onnx` feature and a `tokenizer.json` next to the model), otherwise the hashing baseline.
+pub fn load(model: Option<&Path>) -> Result<Arc<dyn Embedder>> {
+    match model {
+        None => Ok(Arc::new(HashingEmbedder::default())),
+        #[cfg(feature = "onnx")]
+        Some(path) => Ok(Arc::new(onnx::OnnxEmbedder::load(path)?)),
+        #[cfg(not(feature = "onnx"))]
+        Some(path) => anyhow::bail!(
+            "cannot load {}: agentd was built without the `onnx` feature",
+            path.display()
+        ),
+    }
+}
+
+/// Text of a memory.ingest payload that gets embedded: its content plus any tags.
+pub fn ingest_text(payload: &str) -> Option<String> {
+    let parsed: serde_json::Value = serde_json::from_str(payload).ok()?;
+    let content = parsed.get("content")?.as_str()?;
+    let tags = parsed
+        .get("tags")
+        .and_then(|t| t.as_array())
+        .map(|tags| tags.iter().filter_map(|t| t.as_str()).collect::<Vec<_>>().join(" "))
+        .unwrap_or_default();
+    Some(format!("{content} {tags}").trim_end().to_string())
+}
+
+/// Run the embedder off the async runtime (ONNX inference is CPU-bound).
+pub async fn embed_blocking(embedder: &Arc<dyn Embedder>, text: String) -> Result<Vec<f32>> {
+    let embedder = embedder.clone();
+    tokio::task::spawn_blocking(move || embedder.embed(&text)).await?
+}
+
+/// Embed every memory.ingest event that has no vector for the current model — events
+/// from before vectors existed, or from a previous model. Runs once at startup.
+pub async fn backfill(ledger: LedgerHandle, embedder: Arc<dyn Embedder>) {
+    let model = embedder.model_id().to_string();
+    let mut cursor = 0;
+    let mut embedded = 0;
+    loop {
+        let page_model = model.clone();
+        let page = ledger
+            .read(move |l| l.memory_events_without_vectors(&page_model, cursor, BACKFILL_PAGE))
+            .await;
+        let page = match page {
+            Ok(p) if p.is_empty() => break,
+            Ok(p) => p,
+            Err(e) => {
+                tracing::warn!(error = %e, "memory vector backfill failed");
+                return;
+            }
+        };
+        for event in page {
+            cursor = event.id;
+            // Redacted events have a tombstone and nothing left to embed
+            let Some(text) = ingest_text(&event.payload) else {
+                continue;
+            };
+            let vector = match embed_blocking(&embedder, text).await {
+                Ok(v) => v,
+                Err(e) => {
+                    tracing::warn!(error = %e, event_id = event.id, "failed to embed memory");
+                    continue;
+                }
+            };
+            let model = model.clone();
+            if let Err(e) = ledger.write(move |l| l.store_vector(event.id, &model, &vector)).await {
+                tracing::warn!(error = %e, event_id = event.id, "failed to store memory vector");
+                continue;
+            }
+            embedded += 1;
+        }
+    }
+    if embedded > 0 {
+        tracing::info!(embedded, model = %model, "memory vectors backfilled");
+    }
+}
+
+#[cfg(feature = "onnx")]
+mod onnx {
+    use std::path::Path;
+    use std::sync::Mutex;
+
+    use anyhow::{Context, Result};
+    use ort::session::Session;
+    use ort::value::Tensor;
+    use tokenizers::Tokenizer;
+
+    use super::{normalize, Embedder};
+
+    /// Sentence-embedding model (e.g. all-MiniLM-L6-v2) exported to ONNX, mean-pooled
+    /// over the attention mask. Loads libonnxruntime at runtime (`ORT_DYLIB_PATH`).
+    pub struct OnnxEmbedder {
+        model_id: String,
+        dimensions: usize,
+        session: Mutex<Session>,
+        tokenizer: Tokenizer,
+    }
+
+    impl OnnxEmbedder {
+        pub fn load(path: &Path) -> Result<Self> {
+            let tokenizer_path = path.with_file_name("tokenizer.json");
+            let tokenizer = Tokenizer::from_file(&tokenizer_path)
+                .map_err(|e| anyhow::anyhow!("failed to load {}: {e}", tokenizer_path.display()))?;
+            let session = Session::builder()?
+                .commit_from_file(path)
+                .with_context(|| format!("failed to load ONNX model {}", path.display()))?;
+            let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("model");
+
+            let mut embedder = Self {
+                model_id: format!("onnx-{stem}"),
+                dimensions: 0,
+                session: Mutex::new(session),
+                tokenizer,
+            };
+            embedder.dimensions = embedder.embed("dimension probe")?.len();
+            tracing::info!(model = %embedder.model_id, dimensions = embedder.dimensions, "ONNX embedding model loaded");
+            Ok(embedder)
+        }
+    }
+
+    impl Embedder for OnnxEmbedder {
+        fn model_id(&self) -> &str {
+            &self.model_id
+        }
+
+        fn dimensions(&self) -> usize {
+            self.dimensions
+        }
+
+        fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            let encoding = self
+                .tokenizer
+                .encode(text, true)
+                .map_err(|e| anyhow::anyhow!("failed to tokenize: {e}"))?;
+            let len = encoding.get_ids().len();
+            let as_i64 = |v: &[u32]| v.iter().map(|&x| x as i64).collect::<Vec<_>>();
+            let mask = as_i64(encoding.get_attention_mask());
+
+            let mut session = self.session.lock().unwrap_or_else(|e| e.into_inner());
+            let mut inputs = Vec::new();
+            for input in &session.inputs {
+                let values = match input.name.as_str() {
+                    "input_ids" => as_i64(encoding.get_ids()),
+                    "attention_mask" => mask.clone(),
+                    "token_type_ids" => as_i64(encoding.get_type_ids()),
+                    other => anyhow::bail!("unsupported model input '{other}'"),
+                };
+                inputs.push((input.name.clone(), Tensor::from_array(([1, len], values))?));
+            }
+            let outputs = session.run(inputs)?;
+            let (shape, data) = outputs[0].try_extract_tensor::<f32>()?;
+
+            let mut pooled = match **shape {
+                // [1, tokens, hidden]: mean over unmasked tokens
+                [1, tokens, hidden] => {
+                    let (tokens, hidden) = (tokens as usize, hidden as usize);
+                    let mut sum = vec![0.0f32; hidden];
+                    let mut count = 0.0;
+                    for t in 0..tokens.min(mask.len()) {
+                        if mask[t] == 0 {
+                            continue;
+                        }
+                        count += 1.0;
+                        for (s, v) in sum.iter_mut().zip(&data[t * hidden..(t + 1) * hidden]) {
+                            *s += v;
+                        }
+                    }
+                    sum.iter_mut().for_each(|s| *s /= f32::max(count, 1.0));
+                    sum
+                }
+                // [1, hidden]: the model already pools
+                [1, _] => data.to_vec(),
+                _ => anyhow::bail!("unexpected embedding output shape {shape:?}"),
+            };
+            normalize(&mut pooled);
+            Ok(pooled)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_synonyms_are_closer_than_unrelated_text() {
+        let e = HashingEmbedder::default();
+        let query = e.embed("nginx down").unwrap();
+        let synonym = e.embed("the web server crashed after the upgrade").unwrap();
+        let unrelated = e.embed("backup of /var/lib finished in 3 minutes").unwrap();
+
+        let close = cosine(&query, &synonym);
+        let far = cosine(&query, &unrelated);
+        assert!(close > 0.4, "synonym similarity {close}");
+        assert!(close > far * 2.0, "synonym {close} vs unrelated {far}");
+    }
+
+    #[test]
+    fn test_embedding_is_deterministic_and_normalised() {
+        let e = HashingEmbedder::new(64);
+        let a = e.embed("Postgres restarted").unwrap();
+        assert_eq!(a, e.embed("postgres restarting").unwrap());
+        assert_eq!(a.len(), 64);
+        assert!((cosine(&a, &a) - 1.0).abs() < 1e-5);
+        assert!(e.embed("").unwrap().iter().all(|v| *v == 0.0));
+    }
+
+    #[test]
+    fn test_ingest_text() {
+        assert_eq!(
+            ingest_text(r#"{"content":"disk full","tags":["ops","disk"]}"#).as_deref(),
+            Some("disk full ops disk")
+        );
+        assert_eq!(ingest_text(r#"{"redacted":true,"payload_digest":"ab"}"#), None);
+    }
+}
@@ -1,37 +1,124 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
 use anyhow::{Context, Result};
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
 use serde::{Deserialize, Serialize};
-use sha2::{Digest, Sha256};
+use tokio::sync::broadcast;
 
-/// The zero hash used as prev_hash for the genesis event.
-const GENESIS_PREV_HASH: &str =
-    "0000000000000000000000000000000000000000000000000000000000000000";
+use osmoda_ledger::archive;
+use osmoda_ledger::chain::{self, GENESIS_PREV_HASH};
+use osmoda_ledger::export;
+use osmoda_ledger::merkle;
+use osmoda_ledger::query;
+use osmoda_ledger::signing::CHECKPOINT_COLUMNS;
 
-/// A single event in the hash-chained ledger.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Event {
-    pub id: i64,
-    pub ts: String,
-    #[serde(rename = "type")]
+use crate::embedding;
+use crate::schema::{self, Validation};
+use crate::signing::{self, LedgerSigner};
+
+pub use osmoda_ledger::archive::LedgerSegment;
+pub use osmoda_ledger::chain::{Event, Tombstone, VerifyReport};
+pub use osmoda_ledger::export::ExportSummary;
+pub use osmoda_ledger::merkle::MerkleCheckpoint;
+pub use osmoda_ledger::signing::{SignedCheckpoint, DEFAULT_SIGNED_CHECKPOINT_EVERY};
+pub use osmoda_ledger::query::{normalize_timestamp, EventFilter, LedgerStats, SortOrder, StatsBucket, StatsGroup};
+
+/// Seal a Merkle checkpoint once this many events are not yet covered by one.
+pub const MERKLE_CHECKPOINT_INTERVAL: i64 = 256;
+
+
+/// Default number of most recent events kept in the live table.
+pub const DEFAULT_RETAIN_EVENTS: i64 = 100_000;
+
+/// Namespace for memories that do not name one (and for memories from before namespaces).
+/// Also hard-coded in the `memories_event_insert` trigger.
+pub const DEFAULT_MEMORY_NAMESPACE: &str = "agent";
+
+/// Upper bound on events written into a single archive segment.
+pub const SEGMENT_MAX_EVENTS: i64 = 10_000;
+
+/// Appended events buffered for live subscribers before a slow one starts lagging.
+const EVENT_STREAM_CAPACITY: usize = 1024;
+
+/// How long a connection waits for another writer (e.g. the approval gate) before SQLITE_BUSY.
+const BUSY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// An event waiting to be appended as part of a batch.
+#[derive(Debug, Clone)]
+pub struct NewEvent {
     pub event_type: String,
     pub actor: String,
+    pub verified_actor: Option<String>,
     pub payload: String,
-    pub prev_hash: String,
-    pub hash: String,
 }
 
-/// Filter criteria for querying events.
-#[derive(Debug, Default, Deserialize)]
-pub struct EventFilter {
-    #[serde(rename = "type")]
-    pub event_type: Option<String>,
-    pub actor: Option<String>,
-    pub limit: Option<i64>,
+/// A step to add to an incident workspace; it is numbered when inserted.
+#[derive(Debug, Clone, Copy)]
+pub struct NewIncidentStep<'a> {
+    pub action: &'a str,
+    pub result: &'a str,
+    pub receipt_id: Option<&'a str>,
+}
+
+/// Structured filters for memory recall, applied inside the FTS and vector queries.
+#[derive(Debug, Clone)]
+pub struct RecallFilter {
+    /// Memory events are kept only while live (unexpired or pinned) in this namespace;
+    /// other event types are shared history.
+    pub namespace: String,
+    /// Only events at or after this RFC 3339 timestamp.
+    pub since: Option<String>,
+    /// Only events strictly before this RFC 3339 timestamp.
+    pub until: Option<String>,
+    /// Only events whose `ts` starts with this (e.g. `2026-03`).
+    pub ts_prefix: Option<String>,
+    /// Payload `category` equals this.
+    pub category: Option<String>,
+    /// Payload `tags` contains at least one of these.
+    pub tags_any: Vec<String>,
+    /// Payload `tags` contains every one of these.
+    pub tags_all: Vec<String>,
+    /// Event actor (the `source` of a memory.ingest).
+    pub source: Option<String>,
+}
+
+impl RecallFilter {
+    pub fn for_namespace(namespace: &str) -> Self {
+        Self {
+            namespace: namespace.to_string(),
+            since: None,
+            until: None,
+            ts_prefix: None,
+            category: None,
+            tags_any: Vec::new(),
+            tags_all: Vec::new(),
+            source: None,
+        }
+    }
 }
 
 /// Hash-chained SQLite ledger providing tamper-evident event storage.
 pub struct Ledger {
     conn: Connection,
+    /// Signs the head hash into `checkpoints`; None until `set_signer` is called.
+    signer: Option<LedgerSigner>,
+    /// Sign a new checkpoint after this many events past the last signed one.
+    signed_checkpoint_every: i64,
+    /// Where sealed segments live; None until `set_archive_dir` is called.
+    archive_dir: Option<PathBuf>,
+    /// Every committed event is published here for `/events/stream` subscribers.
+    events_tx: broadcast::Sender<Event>,
+}
+
+/// How much history stays in the live `events` table.
+/// An event is archived only once it is outside both windows.
+#[derive(Debug, Clone, Copy)]
+pub struct RetentionPolicy {
+    /// Always keep at least this many of the newest events live (0 disables archiving).
+    pub keep_events: i64,
+    /// Also keep events younger than this many days live (0 = no age floor).
+    pub keep_days: i64,
 }
 
 impl Ledger {
@@ -47,6 +134,13 @@ impl Ledger {
         conn.pragma_update(None, "synchronous", "FULL")
             .context("failed to set synchronous mode")?;
 
+        // Zero freed space so a redacted payload does not linger in the file
+        conn.pragma_update(None, "secure_delete", "ON")
+            .context("failed to enable secure_delete")?;
+
+        conn.busy_timeout(BUSY_TIMEOUT)
+            .context("failed to set busy timeout")?;
+
         conn.execute_batch(
             "CREATE TABLE IF NOT EXISTS events (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -55,7 +149,9 @@ impl Ledger {
                 actor TEXT NOT NULL,
                 payload TEXT NOT NULL,
                 prev_hash TEXT NOT NULL,
-                hash TEXT NOT NULL
+                hash TEXT NOT NULL,
+                verified_actor TEXT,
+                payload_digest TEXT
             );
 
             CREATE TABLE IF NOT EXISTS incidents (
@@ -63,7 +159,13 @@ impl Ledger {
                 name TEXT NOT NULL,
                 status TEXT NOT NULL DEFAULT 'open',
                 created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
-                resolved_at TEXT
+                resolved_at TEXT,
+                severity TEXT NOT NULL DEFAULT 'medium',
+                affected_services TEXT NOT NULL DEFAULT '[]',
+                assignee TEXT,
+                resolution TEXT,
+                reopen_count INTEGER NOT NULL DEFAULT 0,
+                correlation_key TEXT
             );
 
             CREATE TABLE IF NOT EXISTS incident_steps (
@@ -76,6 +178,49 @@ impl Ledger {
                 timestamp TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now'))
             );
 
+            CREATE TABLE IF NOT EXISTS merkle_checkpoints (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                first_event_id INTEGER NOT NULL,
+                last_event_id INTEGER NOT NULL,
+                leaf_count INTEGER NOT NULL,
+                root TEXT NOT NULL,
+                created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+                public_key TEXT,
+                signature TEXT
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_merkle_range ON merkle_checkpoints(first_event_id, last_event_id);
+
+            CREATE TABLE IF NOT EXISTS checkpoints (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                event_id INTEGER NOT NULL,
+                head_hash TEXT NOT NULL,
+                signed_at TEXT NOT NULL,
+                public_key TEXT NOT NULL,
+                signature TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS ledger_segments (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                first_event_id INTEGER NOT NULL,
+                last_event_id INTEGER NOT NULL,
+                event_count INTEGER NOT NULL,
+                first_prev_hash TEXT NOT NULL,
+                last_hash TEXT NOT NULL,
+                file TEXT NOT NULL,
+                sha256 TEXT NOT NULL,
+                created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now'))
+            );
+
+            CREATE TABLE IF NOT EXISTS verify_state (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                event_id INTEGER NOT NULL,
+                hash TEXT NOT NULL,
+                verified_at TEXT NOT NULL,
+                public_key TEXT,
+                signature TEXT
+            );
+
             CREATE TABLE IF NOT EXISTS schema_version (
                 id INTEGER PRIMARY KEY CHECK (id = 1),
                 version INTEGER NOT NULL
@@ -90,34 +235,118 @@ impl Ledger {
             CREATE TRIGGER IF NOT EXISTS events_fts_insert AFTER INSERT ON events BEGIN
                 INSERT INTO events_fts(rowid, type, actor, payload)
                 VALUES (new.id, new.type, new.actor, new.payload);
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS events_fts_delete AFTER DELETE ON events BEGIN
+                INSERT INTO events_fts(events_fts, rowid, type, actor, payload)
+                VALUES ('delete', old.id, old.type, old.actor, old.payload);
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS events_fts_update AFTER UPDATE OF payload ON events BEGIN
+                INSERT INTO events_fts(events_fts, rowid, type, actor, payload)
+                VALUES ('delete', old.id, old.type, old.actor, old.payload);
+                INSERT INTO events_fts(rowid, type, actor, payload)
+                VALUES (new.id, new.type, new.actor, new.payload);
+            END;
+
+            CREATE TABLE IF NOT EXISTS memory_vectors (
+                event_id INTEGER PRIMARY KEY,
+                model TEXT NOT NULL,
+                dimensions INTEGER NOT NULL,
+                vector BLOB NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_memory_vectors_model ON memory_vectors(model);
+
+            -- A vector is derived from its payload: drop it when the event is archived or redacted
+            CREATE TRIGGER IF NOT EXISTS memory_vectors_delete AFTER DELETE ON events BEGIN
+                DELETE FROM memory_vectors WHERE event_id = old.id;
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS memory_vectors_update AFTER UPDATE OF payload ON events BEGIN
+                DELETE FROM memory_vectors WHERE event_id = old.id;
+            END;
+
+            CREATE TABLE IF NOT EXISTS memories (
+                event_id INTEGER PRIMARY KEY,
+                namespace TEXT NOT NULL,
+                created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+                expires_at TEXT,
+                pinned INTEGER NOT NULL DEFAULT 0,
+                -- Consolidated memory that replaced this near-duplicate
+                superseded_by INTEGER
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_memories_namespace ON memories(namespace, created_at);
+            CREATE INDEX IF NOT EXISTS idx_memories_expiry ON memories(expires_at) WHERE expires_at IS NOT NULL;
+
+            -- Memory events appended outside the memory API land in the default namespace;
+            -- remember() replaces this row with the requested namespace and expiry
+            CREATE TRIGGER IF NOT EXISTS memories_event_insert AFTER INSERT ON events
+            WHEN new.type IN ('memory.ingest', 'memory.store') BEGIN
+                INSERT OR IGNORE INTO memories (event_id, namespace, created_at) VALUES (new.id, 'agent', new.ts);
+            END;
+
+            -- A forgotten memory takes its vector with it
+            CREATE TRIGGER IF NOT EXISTS memories_forget AFTER DELETE ON memories BEGIN
+                DELETE FROM memory_vectors WHERE event_id = old.event_id;
+            END;
+
+            -- Forgetting a consolidated memory restores the memories it had superseded
+            CREATE TRIGGER IF NOT EXISTS memories_unsupersede AFTER DELETE ON memories BEGIN
+                UPDATE memories SET superseded_by = NULL WHERE superseded_by = old.event_id;
+            END;
+
+            -- Archived or redacted memory events leave the store
+            CREATE TRIGGER IF NOT EXISTS memories_event_delete AFTER DELETE ON events BEGIN
+                DELETE FROM memories WHERE event_id = old.id;
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS memories_event_update AFTER UPDATE OF payload ON events BEGIN
+                DELETE FROM memories WHERE event_id = old.id;
             END;",
         )
         .context("failed to create tables")?;
 
-        let mut ledger = Self { conn };
+        let mut ledger = Self {
+            conn,
+            signer: None,
+            signed_checkpoint_every: DEFAULT_SIGNED_CHECKPOINT_EVERY,
+            archive_dir: None,
+            events_tx: broadcast::channel(EVENT_STREAM_CAPACITY).0,
+        };
         ledger.migrate()?;
         Ok(ledger)
     }
 
-    /// Compute the SHA-256 hash for an event row.
-    /// Uses pipe delimiters to prevent field-boundary collisions
-    /// (e.g., id="12" + ts="3abc" would otherwise equal id="123" + ts="abc").
-    fn compute_hash(
-        id: i64,
-        ts: &str,
-        event_type: &str,
-        actor: &str,
-        payload: &str,
-        prev_hash: &str,
-    ) -> String {
-        let input = format!("{id}|{ts}|{event_type}|{actor}|{payload}|{prev_hash}");
-        let mut hasher = Sha256::new();
-        hasher.update(input.as_bytes());
-        hex::encode(hasher.finalize())
+    /// Open another connection to the same database for reads only, sharing this ledger's
+    /// archive directory, signer (to check the watermark) and event stream. SQLite enforces
+    /// read-only, so a write through it fails instead of racing the writer.
+    pub fn open_reader(&self) -> Result<Self> {
+        let path = self
+            .conn
+            .path()
+            .filter(|p| !p.is_empty())
+            .context("in-memory ledgers cannot have reader connections")?;
+        let conn = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX | OpenFlags::SQLITE_OPEN_URI,
+        )
+        .with_context(|| format!("failed to open ledger reader at {path}"))?;
+        conn.busy_timeout(BUSY_TIMEOUT)
+            .context("failed to set busy timeout")?;
+
+        Ok(Self {
+            conn,
+            signer: self.signer.clone(),
+            signed_checkpoint_every: self.signed_checkpoint_every,
+            archive_dir: self.archive_dir.clone(),
+            events_tx: self.events_tx.clone(),
+        })
     }
 
     /// Current schema version. Increment when making breaking changes.
-    const CURRENT_SCHEMA_VERSION: i64 = 3;
+    const CURRENT_SCHEMA_VERSION: i64 = 10;
 
     /// Run any pending migrations.
     /// Schema versions are one-way: once at the current version, never downgrade.
@@ -149,6 +378,138 @@ impl Ledger {
             self.backfill_fts()?;
         }
 
+        if version < 4 {
+            // Migration to v4: verified_actor column (peer identity from SO_PEERCRED).
+            // Fresh databases already have it.
+            let has_column: bool = self.conn.query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('events') WHERE name = 'verified_actor'",
+                [],
+                |row| row.get::<_, i64>(0),
+            )? > 0;
+            if !has_column {
+                tracing::info!("migrating ledger to v4: adding verified_actor column");
+                self.conn
+                    .execute("ALTER TABLE events ADD COLUMN verified_actor TEXT", [])
+                    .context("failed to add verified_actor column")?;
+            }
+        }
+
+        if version < 5 {
+            // Migration to v5: payload_digest column. Existing events keep hashing their
+            // raw payload (and so cannot be redacted); new events commit to the digest.
+            let has_column: bool = self.conn.query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('events') WHERE name = 'payload_digest'",
+                [],
+                |row| row.get::<_, i64>(0),
+            )? > 0;
+            if !has_column {
+                tracing::info!("migrating ledger to v5: adding payload_digest column");
+                self.conn
+                    .execute("ALTER TABLE events ADD COLUMN payload_digest TEXT", [])
+                    .context("failed to add payload_digest column")?;
+            }
+        }
+
+        if version < 6 {
+            // Migration to v6: register memories ingested before the memory store existed
+            // in the default namespace, with no expiry
+            let migrated = self
+                .conn
+                .execute(
+                    "INSERT OR IGNORE INTO memories (event_id, namespace, created_at)
+                     SELECT id, ?1, ts FROM events WHERE type IN ('memory.ingest', 'memory.store')",
+                    params![DEFAULT_MEMORY_NAMESPACE],
+                )
+                .context("failed to backfill memory store")?;
+            if migrated > 0 {
+                tracing::info!(migrated, "migrating ledger to v6: registered existing memories");
+            }
+        }
+
+        if version < 7 {
+            // Migration to v7: memories superseded by a consolidated entry
+            let has_column: bool = self.conn.query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('memories') WHERE name = 'superseded_by'",
+                [],
+                |row| row.get::<_, i64>(0),
+            )? > 0;
+            if !has_column {
+                tracing::info!("migrating ledger to v7: adding memories.superseded_by");
+                self.conn
+                    .execute("ALTER TABLE memories ADD COLUMN superseded_by INTEGER", [])
+                    .context("failed to add superseded_by column")?;
+            }
+            self.conn
+                .execute(
+                    "CREATE INDEX IF NOT EXISTS idx_memories_superseded ON memories(superseded_by) WHERE superseded_by IS NOT NULL",
+                    [],
+                )
+                .context("failed to index superseded memories")?;
+        }
+
+        if version < 8 {
+            // Migration to v8: incident severity, ownership and resolution
+            for (column, definition) in [
+                ("severity", "TEXT NOT NULL DEFAULT 'medium'"),
+                ("affected_services", "TEXT NOT NULL DEFAULT '[]'"),
+                ("assignee", "TEXT"),
+                ("resolution", "TEXT"),
+                ("reopen_count", "INTEGER NOT NULL DEFAULT 0"),
+            ] {
+                let has_column: bool = self.conn.query_row(
+                    "SELECT COUNT(*) FROM pragma_table_info('incidents') WHERE name = ?1",
+                    params![column],
+                    |row| row.get::<_, i64>(0),
+                )? > 0;
+                if !has_column {
+                    tracing::info!(column, "migrating ledger to v8: adding incidents column");
+                    self.conn
+                        .execute(&format!("ALTER TABLE incidents ADD COLUMN {column} {definition}"), [])
+                        .with_context(|| format!("failed to add incidents.{column}"))?;
+                }
+            }
+        }
+
+        if version < 9 {
+            // Migration to v9: incidents opened by the correlation engine
+            let has_column: bool = self.conn.query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('incidents') WHERE name = 'correlation_key'",
+                [],
+                |row| row.get::<_, i64>(0),
+            )? > 0;
+            if !has_column {
+                tracing::info!("migrating ledger to v9: adding incidents.correlation_key");
+                self.conn
+                    .execute("ALTER TABLE incidents ADD COLUMN correlation_key TEXT", [])
+                    .context("failed to add correlation_key column")?;
+            }
+            self.conn
+                .execute(
+                    "CREATE INDEX IF NOT EXISTS idx_incidents_correlation ON incidents(correlation_key, status)
+                     WHERE correlation_key IS NOT NULL",
+                    [],
+                )
+                .context("failed to index correlated incidents")?;
+        }
+
+        if version < 10 {
+            // Migration to v10: Merkle roots signed by the ledger key. Roots sealed
+            // before it stay unsigned, so proofs against them need an explicit --root.
+            for column in ["public_key", "signature"] {
+                let has_column: bool = self.conn.query_row(
+                    "SELECT COUNT(*) FROM pragma_table_info('merkle_checkpoints') WHERE name = ?1",
+                    params![column],
+                    |row| row.get::<_, i64>(0),
+                )? > 0;
+                if !has_column {
+                    tracing::info!(column, "migrating ledger to v10: adding merkle_checkpoints column");
+                    self.conn
+                        .execute(&format!("ALTER TABLE merkle_checkpoints ADD COLUMN {column} TEXT"), [])
+                        .with_context(|| format!("failed to add merkle_checkpoints.{column}"))?;
+                }
+            }
+        }
+
         if version < Self::CURRENT_SCHEMA_VERSION {
             self.conn.execute(
                 "INSERT OR REPLACE INTO schema_version (id, version) VALUES (1, ?1)",
@@ -164,22 +525,20 @@ impl Ledger {
         let tx = self.conn.unchecked_transaction()
             .context("failed to begin rehash transaction")?;
 
-        let mut stmt = tx.prepare(
-            "SELECT id, ts, type, actor, payload FROM events ORDER BY id ASC"
-        )?;
-        let rows: Vec<(i64, String, String, String, String)> = stmt
-            .query_map([], |row| {
-                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?))
-            })?
+        // A v1 ledger is rehashed before the later columns are added
+        let mut stmt = tx.prepare(&format!("SELECT {} FROM events ORDER BY id ASC", chain::event_columns(&tx)?))?;
+        let rows: Vec<Event> = stmt
+            .query_map([], Event::from_row)?
             .collect::<std::result::Result<Vec<_>, _>>()?;
         drop(stmt);
 
         let mut prev_hash = GENESIS_PREV_HASH.to_string();
-        for (id, ts, event_type, actor, payload) in &rows {
-            let hash = Self::compute_hash(*id, ts, event_type, actor, payload, &prev_hash);
+        for event in &rows {
+            let event = Event { prev_hash: prev_hash.clone(), ..event.clone() };
+            let hash = chain::event_hash(&event);
             tx.execute(
                 "UPDATE events SET prev_hash = ?1, hash = ?2 WHERE id = ?3",
-                params![prev_hash, hash, id],
+                params![prev_hash, hash, event.id],
             )?;
             prev_hash = hash;
         }
@@ -219,35 +578,38 @@ impl Ledger {
 
     /// Full-text search over events using FTS5 with BM25 ranking.
     /// Returns events sorted by relevance. Falls back to keyword scan on FTS5 failure.
-    pub fn fts_search(&self, query: &str, limit: usize) -> Result<Vec<(Event, f64)>> {
+    pub fn fts_search(&self, query: &str, filter: Option<&RecallFilter>, limit: usize) -> Result<Vec<(Event, f64)>> {
         let fts_query = Self::sanitize_fts_query(query);
         if fts_query.is_empty() {
             return Ok(Vec::new());
         }
 
-        let sql = "SELECT e.id, e.ts, e.type, e.actor, e.payload, e.prev_hash, e.hash,
-                          bm25(events_fts) as rank
-                   FROM events_fts
-                   JOIN events e ON e.id = events_fts.rowid
-                   WHERE events_fts MATCH ?1
-                   ORDER BY rank
-                   LIMIT ?2";
+        let mut param_values: Vec<Box<dyn rusqlite::types::ToSql>> = vec![Box::new(fts_query)];
+        let conditions = match filter {
+            Some(filter) => Self::recall_conditions(filter, &mut param_values)?,
+            None => String::new(),
+        };
+        let sql = format!(
+            "SELECT e.id, e.ts, e.type, e.actor, e.payload, e.prev_hash, e.hash, e.verified_actor, e.payload_digest,
+                    bm25(events_fts) as rank
+             FROM events_fts
+             JOIN events e ON e.id = events_fts.rowid
+             WHERE events_fts MATCH ?1{conditions}
+             ORDER BY rank
+             LIMIT ?{}",
+            param_values.len() + 1
+        );
+        param_values.push(Box::new(limit as i64));
+        let params_refs: Vec<&dyn rusqlite::types::ToSql> =
+            param_values.iter().map(|p| p.as_ref()).collect();
 
-        let mut stmt = self.conn.prepare(sql)
+        let mut stmt = self.conn.prepare(&sql)
             .context("failed to prepare FTS5 query")?;
 
         let results = stmt
-            .query_map(params![fts_query, limit as i64], |row| {
-                let rank: f64 = row.get(7)?;
-                Ok((Event {
-                    id: row.get(0)?,
-                    ts: row.get(1)?,
-                    event_type: row.get(2)?,
-                    actor: row.get(3)?,
-                    payload: row.get(4)?,
-                    prev_hash: row.get(5)?,
-                    hash: row.get(6)?,
-                }, -rank)) // bm25() returns negative scores, negate for positive relevance
+            .query_map(params_refs.as_slice(), |row| {
+                let rank: f64 = row.get(9)?;
+                Ok((Event::from_row(row)?, -rank)) // bm25() returns negative scores, negate for positive relevance
             })
             .context("FTS5 query failed")?
             .collect::<std::result::Result<Vec<_>, _>>()
@@ -256,6 +618,180 @@ impl Ledger {
         Ok(results)
     }
 
+    /// Store (or replace) the embedding of a memory event.
+    pub fn store_vector(&self, event_id: i64, model: &str, vector: &[f32]) -> Result<()> {
+        let blob: Vec<u8> = vector.iter().flat_map(|v| v.to_le_bytes()).collect();
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO memory_vectors (event_id, model, dimensions, vector)
+                 SELECT ?1, ?2, ?3, ?4 WHERE EXISTS (SELECT 1 FROM events WHERE id = ?1)",
+                params![event_id, model, vector.len() as i64, blob],
+            )
+            .context("failed to store memory vector")?;
+        Ok(())
+    }
+
+    /// The `limit` vectors of memories matching `filter` most similar to `query`
+    /// (cosine, highest first). A linear scan: memory vectors number in the thousands.
+    pub fn vector_search(&self, model: &str, filter: &RecallFilter, query: &[f32], limit: usize) -> Result<Vec<(i64, f32)>> {
+        let mut param_values: Vec<Box<dyn rusqlite::types::ToSql>> =
+            vec![Box::new(model.to_string()), Box::new(query.len() as i64)];
+        let conditions = Self::recall_conditions(filter, &mut param_values)?;
+        let sql = format!(
+            "SELECT v.event_id, v.vector FROM memory_vectors v
+             JOIN events e ON e.id = v.event_id
+             WHERE v.model = ?1 AND v.dimensions = ?2{conditions}"
+        );
+        let params_refs: Vec<&dyn rusqlite::types::ToSql> =
+            param_values.iter().map(|p| p.as_ref()).collect();
+
+        let mut stmt = self.conn.prepare(&sql)
+            .context("failed to prepare vector search")?;
+        let rows = stmt
+            .query_map(params_refs.as_slice(), |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, Vec<u8>>(1)?))
+            })
+            .context("vector search failed")?;
+
+        let mut scored = Vec::new();
+        for row in rows {
+            let (event_id, blob) = row.context("failed to read memory vector")?;
+            let vector: Vec<f32> = blob
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .collect();
+            scored.push((event_id, embedding::cosine(query, &vector)));
+        }
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1).then(b.0.cmp(&a.0)));
+        scored.truncate(limit);
+        Ok(scored)
+    }
+
+    /// The newest `limit` events matching `filter`, for keyword scoring when FTS5 is unavailable.
+    pub fn recall_scan(&self, filter: &RecallFilter, limit: usize) -> Result<Vec<Event>> {
+        let mut param_values: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
+        let conditions = Self::recall_conditions(filter, &mut param_values)?;
+        let sql = format!(
+            "SELECT e.id, e.ts, e.type, e.actor, e.payload, e.prev_hash, e.hash, e.verified_actor, e.payload_digest
+             FROM events e WHERE 1=1{conditions} ORDER BY e.id DESC LIMIT ?{}",
+            param_values.len() + 1
+        );
+        param_values.push(Box::new(limit as i64));
+        let params_refs: Vec<&dyn rusqlite::types::ToSql> =
+            param_values.iter().map(|p| p.as_ref()).collect();
+
+        let mut stmt = self.conn.prepare(&sql).context("failed to prepare recall scan")?;
+        let events = stmt
+            .query_map(params_refs.as_slice(), Event::from_row)
+            .context("recall scan failed")?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .context("failed to collect recall scan")?;
+        Ok(events)
+    }
+
+    /// SQL `AND ...` conditions on events aliased `e` for a recall filter, numbered after
+    /// the parameters already in `param_values` (which receives the new ones).
+    fn recall_conditions(
+        filter: &RecallFilter,
+        param_values: &mut Vec<Box<dyn rusqlite::types::ToSql>>,
+    ) -> Result<String> {
+        let mut sql = String::new();
+
+        sql.push_str(&format!(
+            " AND (e.type NOT IN ('memory.ingest', 'memory.store') OR EXISTS (
+                SELECT 1 FROM memories m WHERE m.event_id = e.id AND m.namespace = ?{}
+                  AND (m.pinned = 1 OR m.expires_at IS NULL OR m.expires_at > strftime('%Y-%m-%dT%H:%M:%fZ','now'))))",
+            param_values.len() + 1
+        ));
+        param_values.push(Box::new(filter.namespace.clone()));
+
+        if let Some(ref since) = filter.since {
+            sql.push_str(&format!(" AND e.ts >= ?{}", param_values.len() + 1));
+            param_values.push(Box::new(normalize_timestamp(since)?));
+        }
+
+        if let Some(ref until) = filter.until {
+            sql.push_str(&format!(" AND e.ts < ?{}", param_values.len() + 1));
+            param_values.push(Box::new(normalize_timestamp(until)?));
+        }
+
+        if let Some(ref prefix) = filter.ts_prefix {
+            let n = param_values.len();
+            sql.push_str(&format!(" AND substr(e.ts, 1, ?{}) = ?{}", n + 1, n + 2));
+            param_values.push(Box::new(prefix.chars().count() as i64));
+            param_values.push(Box::new(prefix.clone()));
+        }
+
+        if let Some(ref source) = filter.source {
+            sql.push_str(&format!(" AND e.actor = ?{}", param_values.len() + 1));
+            param_values.push(Box::new(source.clone()));
+        }
+
+        // Payloads need not be JSON; json_extract/json_each raise errors on those, so guard with CASE
+        if let Some(ref category) = filter.category {
+            sql.push_str(&format!(
+                " AND CASE WHEN json_valid(e.payload) THEN json_extract(e.payload, '$.category') = ?{} END",
+                param_values.len() + 1
+            ));
+            param_values.push(Box::new(category.clone()));
+        }
+
+        let mut tag_clause = |tags: &[String], all: bool| {
+            let mut tags = tags.to_vec();
+            tags.sort();
+            tags.dedup();
+            if tags.is_empty() {
+                return;
+            }
+            let placeholders: Vec<String> =
+                (0..tags.len()).map(|i| format!("?{}", param_values.len() + 1 + i)).collect();
+            let matching = format!(
+                "FROM json_each(e.payload, '$.tags') WHERE json_each.value IN ({})",
+                placeholders.join(", ")
+            );
+            let test = if all {
+                format!("(SELECT COUNT(DISTINCT json_each.value) {matching}) = {}", tags.len())
+            } else {
+                format!("EXISTS (SELECT 1 {matching})")
+            };
+            sql.push_str(&format!(" AND CASE WHEN json_valid(e.payload) THEN {test} END"));
+            param_values.extend(tags.into_iter().map(|t| Box::new(t) as Box<dyn rusqlite::types::ToSql>));
+        };
+        tag_clause(&filter.tags_any, false);
+        tag_clause(&filter.tags_all, true);
+
+        Ok(sql)
+    }
+
+    /// Live memory.ingest events after `after_id` with no vector from `model`, oldest first.
+    pub fn memory_events_without_vectors(&self, model: &str, after_id: i64, limit: usize) -> Result<Vec<Event>> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT e.id, e.ts, e.type, e.actor, e.payload, e.prev_hash, e.hash, e.verified_actor, e.payload_digest
+                 FROM events e
+                 JOIN memories m ON m.event_id = e.id
+                 LEFT JOIN memory_vectors v ON v.event_id = e.id AND v.model = ?1
+                 WHERE e.type = 'memory.ingest' AND e.id > ?2 AND v.event_id IS NULL
+                 ORDER BY e.id ASC
+                 LIMIT ?3",
+            )
+            .context("failed to prepare unembedded memory query")?;
+        let events = stmt
+            .query_map(params![model, after_id, limit as i64], Event::from_row)
+            .context("failed to query unembedded memories")?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .context("failed to read unembedded memories")?;
+        Ok(events)
+    }
+
+    /// Number of stored vectors from `model`.
+    pub fn vector_count(&self, model: &str) -> Result<i64> {
+        self.conn
+            .query_row("SELECT COUNT(*) FROM memory_vectors WHERE model = ?1", params![model], |row| row.get(0))
+            .context("failed to count memory vectors")
+    }
+
     /// Flush WAL to main database file. Call on graceful shutdown.
     pub fn flush(&self) -> Result<()> {
         self.conn.pragma_update(None, "wal_checkpoint", "TRUNCATE")
@@ -270,6 +806,7 @@ impl Ledger {
     }
 
     /// Retrieve the hash of the most recent event using an explicit connection/transaction.
+    /// Falls back to the newest archive segment when every event has been archived.
     fn last_hash_conn(conn: &Connection) -> Result<String> {
         let maybe: Option<String> = conn
             .query_row(
@@ -277,7 +814,15 @@ impl Ledger {
                 [],
                 |row| row.get(0),
             )
-            .ok();
+            .ok()
+            .or_else(|| {
+                conn.query_row(
+                    "SELECT last_hash FROM ledger_segments ORDER BY last_event_id DESC LIMIT 1",
+                    [],
+                    |row| row.get(0),
+                )
+                .ok()
+            });
 
         Ok(maybe.unwrap_or_else(|| GENESIS_PREV_HASH.to_string()))
     }
@@ -289,14 +834,80 @@ impl Ledger {
     ///
     /// Returns the created event including its assigned id, timestamp, and hash.
     pub fn append(&self, event_type: &str, actor: &str, payload: &str) -> Result<Event> {
+        self.append_as(event_type, actor, None, payload)
+    }
+
+    /// Append an event submitted by a connected peer, recording the identity
+    /// agentd verified for it alongside the claimed `actor`. Both are hashed.
+    pub fn append_as(
+        &self,
+        event_type: &str,
+        actor: &str,
+        verified_actor: Option<&str>,
+        payload: &str,
+    ) -> Result<Event> {
+        Self::validate_payload(event_type, payload)?;
+
         let tx = self.conn.unchecked_transaction()
             .context("failed to begin transaction")?;
+        let event = Self::insert_event(&tx, event_type, actor, verified_actor, payload)?;
+        tx.commit().context("failed to commit event")?;
+
+        self.after_commit(std::slice::from_ref(&event));
+        Ok(event)
+    }
+
+    /// Append several events in one transaction, in order. Each runs in its own savepoint,
+    /// so one that fails (e.g. schema validation) is rolled back and reported without
+    /// affecting the rest. Returns Err only if the transaction itself fails.
+    pub fn append_batch(&self, batch: &[NewEvent]) -> Result<Vec<Result<Event>>> {
+        let tx = self.conn.unchecked_transaction()
+            .context("failed to begin batch transaction")?;
+
+        let mut results = Vec::with_capacity(batch.len());
+        for new in batch {
+            if let Err(e) = Self::validate_payload(&new.event_type, &new.payload) {
+                results.push(Err(e));
+                continue;
+            }
+
+            tx.execute_batch("SAVEPOINT append_one")?;
+            let result = Self::insert_event(
+                &tx,
+                &new.event_type,
+                &new.actor,
+                new.verified_actor.as_deref(),
+                &new.payload,
+            );
+            if result.is_err() {
+                tx.execute_batch("ROLLBACK TO append_one")?;
+            }
+            tx.execute_batch("RELEASE append_one")?;
+            results.push(result);
+        }
+
+        tx.commit().context("failed to commit event batch")?;
+
+        let committed: Vec<Event> = results.iter().filter_map(|r| r.as_ref().ok().cloned()).collect();
+        self.after_commit(&committed);
+        Ok(results)
+    }
 
-        let prev_hash = Self::last_hash_conn(&tx)?;
+    /// Insert an event at the head of the chain inside an open transaction.
+    fn insert_event(
+        tx: &Connection,
+        event_type: &str,
+        actor: &str,
+        verified_actor: Option<&str>,
+        payload: &str,
+    ) -> Result<Event> {
+        let prev_hash = Self::last_hash_conn(tx)?;
+        let payload_digest = chain::payload_digest(payload);
 
         tx.execute(
-            "INSERT INTO events (type, actor, payload, prev_hash, hash) VALUES (?1, ?2, ?3, ?4, '')",
-            params![event_type, actor, payload, prev_hash],
+            "INSERT INTO events (type, actor, payload, prev_hash, hash, verified_actor, payload_digest)
+             VALUES (?1, ?2, ?3, ?4, '', ?5, ?6)",
+            params![event_type, actor, payload, prev_hash, verified_actor, payload_digest],
         )
         .context("failed to insert event")?;
 
@@ -308,43 +919,259 @@ impl Ledger {
             })
             .context("failed to read back event timestamp")?;
 
-        let hash = Self::compute_hash(id, &ts, event_type, actor, payload, &prev_hash);
+        let mut event = Event {
+            id,
+            ts,
+            event_type: event_type.to_string(),
+            actor: actor.to_string(),
+            payload: payload.to_string(),
+            prev_hash,
+            hash: String::new(),
+            verified_actor: verified_actor.map(str::to_string),
+            payload_digest: Some(payload_digest),
+        };
+        event.hash = chain::event_hash(&event);
 
         tx.execute(
             "UPDATE events SET hash = ?1 WHERE id = ?2",
-            params![hash, id],
+            params![event.hash, id],
         )
         .context("failed to update event hash")?;
 
-        tx.commit().context("failed to commit event")?;
+        Ok(event)
+    }
 
-        Ok(Event {
-            id,
-            ts,
-            event_type: event_type.to_string(),
-            actor: actor.to_string(),
-            payload: payload.to_string(),
-            prev_hash,
-            hash,
+    /// Reject payloads that break their registered schema; unregistered types pass.
+    fn validate_payload(event_type: &str, payload: &str) -> Result<()> {
+        match schema::validate(event_type, payload) {
+            Validation::Valid => {}
+            Validation::Unregistered => {
+                tracing::debug!(event_type, "appending event type with no registered schema");
+            }
+            Validation::Invalid(errors) => {
+                anyhow::bail!("payload for '{event_type}' does not match its schema: {}", errors.join("; "));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checkpointing and fan-out for newly committed events, oldest first.
+    fn after_commit(&self, events: &[Event]) {
+        let Some(last) = events.last() else {
+            return;
+        };
+        if let Err(e) = self.checkpoint_if_due() {
+            tracing::warn!(error = %e, "failed to seal merkle checkpoint");
+        }
+        if let Err(e) = self.sign_checkpoint_if_due(last.id) {
+            tracing::warn!(error = %e, "failed to sign ledger checkpoint");
+        }
+
+        for event in events {
+            // No subscribers is not an error
+            let _ = self.events_tx.send(event.clone());
+        }
+    }
+
+    /// Replace an event's payload with a tombstone that keeps its original digest,
+    /// and record the redaction as a new `ledger.redact` event in the same transaction.
+    /// The chain still verifies because the event's hash commits to the digest, not the
+    /// payload. An archived event's segment is rewritten with the tombstone. Events
+    /// written before schema v5 cannot be redacted; authorisation is the caller's job.
+    pub fn redact(
+        &self,
+        event_id: i64,
+        reason: &str,
+        approval_id: Option<&str>,
+        actor: &str,
+        verified_actor: Option<&str>,
+    ) -> Result<Redaction> {
+        let target = match self.conn.query_row(
+            "SELECT id, ts, type, actor, payload, prev_hash, hash, verified_actor, payload_digest FROM events WHERE id = ?1",
+            params![event_id],
+            Event::from_row,
+        ) {
+            Ok(event) => event,
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                return self.redact_archived(event_id, reason, approval_id, actor, verified_actor);
+            }
+            Err(e) => return Err(e).context("failed to fetch event to redact"),
+        };
+        let record = match Self::redaction_record(&target, reason, approval_id) {
+            Ok(record) => record,
+            Err(refused) => return Ok(refused),
+        };
+
+        let tx = self.conn.unchecked_transaction()
+            .context("failed to begin redaction transaction")?;
+        let redaction = Self::insert_event(&tx, "ledger.redact", actor, verified_actor, &record)?;
+        let tombstone = Self::tombstone(&target, &redaction)?;
+        tx.execute(
+            "UPDATE events SET payload = ?1 WHERE id = ?2",
+            params![tombstone, event_id],
+        )
+        .context("failed to write tombstone")?;
+        tx.commit().context("failed to commit redaction")?;
+
+        // FTS5 keeps deleted tokens in its segments until they are merged
+        if let Err(e) = self.conn.execute("INSERT INTO events_fts(events_fts) VALUES ('optimize')", []) {
+            tracing::warn!(error = %e, "failed to purge redacted payload from search index");
+        }
+        // Push the old payload out of the WAL; secure_delete has zeroed it in the main file
+        if let Err(e) = self.flush() {
+            tracing::warn!(error = %e, "failed to checkpoint WAL after redaction");
+        }
+        tracing::info!(event_id, redaction_event_id = redaction.id, "ledger payload redacted");
+
+        self.after_commit(std::slice::from_ref(&redaction));
+        Ok(Redaction::Redacted {
+            target: Box::new(Event { payload: tombstone, ..target }),
+            redaction: Box::new(redaction),
         })
     }
 
-    /// Query events with optional filters.
-    pub fn query(&self, filter: &EventFilter) -> Result<Vec<Event>> {
-        let mut sql = String::from("SELECT id, ts, type, actor, payload, prev_hash, hash FROM events WHERE 1=1");
-        let mut param_values: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
+    /// Redact an event sealed in an archive segment: the segment is rewritten with the
+    /// tombstone and its index row takes the new file's digest, in the same transaction
+    /// as the `ledger.redact` event. The new file replaces the old one after the commit.
+    fn redact_archived(
+        &self,
+        event_id: i64,
+        reason: &str,
+        approval_id: Option<&str>,
+        actor: &str,
+        verified_actor: Option<&str>,
+    ) -> Result<Redaction> {
+        let Some(segment) = self
+            .segments()?
+            .into_iter()
+            .find(|s| s.first_event_id <= event_id && s.last_event_id >= event_id)
+        else {
+            return Ok(Redaction::NotFound);
+        };
+        let dir = self.archive_dir.as_ref().context("no archive directory configured")?;
+        let mut events = self.read_segment(&segment)?;
+        let Some(index) = events.iter().position(|e| e.id == event_id) else {
+            return Ok(Redaction::NotFound);
+        };
+        let record = match Self::redaction_record(&events[index], reason, approval_id) {
+            Ok(record) => record,
+            Err(refused) => return Ok(refused),
+        };
+
+        let tx = self.conn.unchecked_transaction()
+            .context("failed to begin redaction transaction")?;
+        let redaction = Self::insert_event(&tx, "ledger.redact", actor, verified_actor, &record)?;
+        events[index].payload = Self::tombstone(&events[index], &redaction)?;
+        let staged = archive::stage_segment(dir, &events)?;
+        let committed = (|| {
+            anyhow::ensure!(staged.file == segment.file, "rewritten segment {} has a different name", staged.file);
+            tx.execute(
+                "UPDATE ledger_segments SET sha256 = ?1 WHERE id = ?2",
+                params![staged.sha256, segment.id],
+            )
+            .context("failed to update ledger segment digest")?;
+            tx.commit().context("failed to commit redaction")
+        })();
+        if let Err(e) = committed {
+            staged.discard();
+            return Err(e);
+        }
+        staged
+            .publish()
+            .context("redacted segment committed but not moved into place; it will be on the next start")?;
+        tracing::info!(event_id, redaction_event_id = redaction.id, file = %segment.file, "archived ledger payload redacted");
+
+        self.after_commit(std::slice::from_ref(&redaction));
+        Ok(Redaction::Redacted {
+            target: Box::new(events.swap_remove(index)),
+            redaction: Box::new(redaction),
+        })
+    }
 
-        if let Some(ref t) = filter.event_type {
-            sql.push_str(&format!(" AND type = ?{}", param_values.len() + 1));
-            param_values.push(Box::new(t.clone()));
+    /// The `ledger.redact` payload for redacting `target`, or why it cannot be redacted.
+    fn redaction_record(target: &Event, reason: &str, approval_id: Option<&str>) -> std::result::Result<String, Redaction> {
+        let Some(digest) = target.payload_digest.as_deref() else {
+            return Err(Redaction::Legacy);
+        };
+        if Tombstone::parse(&target.payload).is_some() {
+            return Err(Redaction::AlreadyRedacted);
         }
+        Ok(serde_json::json!({
+            "event_id": target.id,
+            "payload_digest": digest,
+            "reason": reason,
+            "approval_id": approval_id,
+        })
+        .to_string())
+    }
+
+    fn tombstone(target: &Event, redaction: &Event) -> Result<String> {
+        let tombstone = Tombstone {
+            redacted: true,
+            payload_digest: target.payload_digest.clone().context("redacted event has no payload digest")?,
+            redaction_event_id: redaction.id,
+        };
+        Ok(serde_json::to_string(&tombstone)?)
+    }
+
+    /// Fan-out of every committed event; `subscribe` on it to receive events from now on.
+    /// A receiver that falls more than `EVENT_STREAM_CAPACITY` events behind gets
+    /// `RecvError::Lagged` and should catch up with `events_after`.
+    pub fn event_sender(&self) -> broadcast::Sender<Event> {
+        self.events_tx.clone()
+    }
+
+    /// Id of the newest event (0 for an empty ledger).
+    pub fn head_id(&self) -> Result<i64> {
+        let live: i64 = self
+            .conn
+            .query_row("SELECT COALESCE(MAX(id), 0) FROM events", [], |row| row.get(0))
+            .context("failed to read ledger head")?;
+        let archived: i64 = self
+            .conn
+            .query_row("SELECT COALESCE(MAX(last_event_id), 0) FROM ledger_segments", [], |row| row.get(0))
+            .context("failed to read archived head")?;
+        Ok(live.max(archived))
+    }
 
-        if let Some(ref a) = filter.actor {
-            sql.push_str(&format!(" AND actor = ?{}", param_values.len() + 1));
-            param_values.push(Box::new(a.clone()));
+    /// Up to `limit` events with id greater than `after_id`, oldest first.
+    /// Reads archive segments when the cursor points into archived history.
+    pub fn events_after(&self, after_id: i64, limit: i64) -> Result<Vec<Event>> {
+        let mut events = Vec::new();
+        for segment in self.segments()? {
+            if segment.last_event_id <= after_id {
+                continue;
+            }
+            events.extend(self.read_segment(&segment)?.into_iter().filter(|e| e.id > after_id));
+            if events.len() as i64 >= limit {
+                events.truncate(limit.max(0) as usize);
+                return Ok(events);
+            }
         }
 
-        sql.push_str(" ORDER BY id DESC");
+        let cursor = events.last().map(|e| e.id).unwrap_or(after_id);
+        let mut stmt = self.conn.prepare(
+            "SELECT id, ts, type, actor, payload, prev_hash, hash, verified_actor, payload_digest FROM events WHERE id > ?1 ORDER BY id ASC LIMIT ?2",
+        )?;
+        let live = stmt
+            .query_map(params![cursor, limit - events.len() as i64], Event::from_row)?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .context("failed to read events after cursor")?;
+        events.extend(live);
+        Ok(events)
+    }
+
+    /// Query events with optional filters.
+    pub fn query(&self, filter: &EventFilter) -> Result<Vec<Event>> {
+        let (conditions, mut param_values) = query::filter_conditions(filter)?;
+        let mut sql = format!(
+            "SELECT id, ts, type, actor, payload, prev_hash, hash, verified_actor, payload_digest FROM events WHERE 1=1{conditions}"
+        );
+
+        match filter.order.unwrap_or_default() {
+            SortOrder::Asc => sql.push_str(" ORDER BY id ASC"),
+            SortOrder::Desc => sql.push_str(" ORDER BY id DESC"),
+        }
 
         let limit = filter.limit.unwrap_or(50);
         sql.push_str(&format!(" LIMIT ?{}", param_values.len() + 1));
@@ -356,17 +1183,7 @@ impl Ledger {
         let mut stmt = self.conn.prepare(&sql).context("failed to prepare query")?;
 
         let events = stmt
-            .query_map(params_refs.as_slice(), |row| {
-                Ok(Event {
-                    id: row.get(0)?,
-                    ts: row.get(1)?,
-                    event_type: row.get(2)?,
-                    actor: row.get(3)?,
-                    payload: row.get(4)?,
-                    prev_hash: row.get(5)?,
-                    hash: row.get(6)?,
-                })
-            })
+            .query_map(params_refs.as_slice(), Event::from_row)
             .context("failed to execute query")?
             .collect::<std::result::Result<Vec<_>, _>>()
             .context("failed to collect query results")?;
@@ -374,68 +1191,157 @@ impl Ledger {
         Ok(events)
     }
 
-    /// Walk the entire chain and verify every hash is correct.
+    /// Counts over the live table matching `filter`; see `query::stats`.
+    pub fn stats(&self, filter: &EventFilter, group_by: StatsGroup, bucket: Option<StatsBucket>) -> Result<LedgerStats> {
+        query::stats(&self.conn, filter, group_by, bucket)
+    }
+
+    /// Walk the entire chain — archived segments first, then the live table —
+    /// and verify every hash is correct. Read-only: the watermark does not move.
     /// Returns Ok(true) if the chain is valid, Ok(false) with tracing warning if not.
+    #[allow(dead_code)] // Public API — callers wanting details use verify_report
     pub fn verify(&self) -> Result<bool> {
-        let mut stmt = self
-            .conn
-            .prepare("SELECT id, ts, type, actor, payload, prev_hash, hash FROM events ORDER BY id ASC")
-            .context("failed to prepare verify query")?;
+        Ok(self.verify_chain(true)?.ok)
+    }
 
-        let events: Vec<Event> = stmt
-            .query_map([], |row| {
-                Ok(Event {
-                    id: row.get(0)?,
-                    ts: row.get(1)?,
-                    event_type: row.get(2)?,
-                    actor: row.get(3)?,
-                    payload: row.get(4)?,
-                    prev_hash: row.get(5)?,
-                    hash: row.get(6)?,
-                })
-            })
-            .context("failed to execute verify query")?
-            .collect::<std::result::Result<Vec<_>, _>>()
-            .context("failed to collect verify results")?;
-
-        let mut expected_prev_hash = GENESIS_PREV_HASH.to_string();
-
-        for event in &events {
-            // Check the prev_hash chain link
-            if event.prev_hash != expected_prev_hash {
-                tracing::warn!(
-                    event_id = event.id,
-                    expected = %expected_prev_hash,
-                    actual = %event.prev_hash,
-                    "chain break: prev_hash mismatch"
-                );
-                return Ok(false);
-            }
+    /// Verify the chain and describe every problem found, not just the first.
+    /// `full` rehashes from genesis; otherwise only events after the last trusted watermark
+    /// are checked, falling back to a full walk when there is no watermark or it is not
+    /// validly signed by this ledger's key (always, without a signer). Events before the watermark are not rehashed —
+    /// schedule a full verify for that.
+    /// The walk resyncs on each stored hash, so one edited row is reported once instead
+    /// of breaking every later link. The watermark only advances when the report is clean.
+    pub fn verify_report(&self, full: bool) -> Result<VerifyReport> {
+        let report = self.verify_chain(full)?;
+        self.advance_watermark(&report)?;
+        Ok(report)
+    }
 
-            // Recompute and verify the event hash
-            let computed = Self::compute_hash(
-                event.id,
-                &event.ts,
-                &event.event_type,
-                &event.actor,
-                &event.payload,
-                &event.prev_hash,
-            );
+    /// Record a clean report's head as the new watermark; reports with issues, that
+    /// checked nothing new, or that a concurrent verify has already overtaken leave it
+    /// where it was.
+    pub fn advance_watermark(&self, report: &VerifyReport) -> Result<()> {
+        if !report.ok || report.head_event_id <= report.from_event_id {
+            return Ok(());
+        }
+        if let Some(current) = self.watermark()? {
+            if current.event_id >= report.head_event_id && self.watermark_trusted(&current) {
+                return Ok(());
+            }
+        }
+        self.record_watermark(report.head_event_id, &report.head_hash)
+    }
 
-            if computed != event.hash {
-                tracing::warn!(
-                    event_id = event.id,
-                    expected = %computed,
-                    actual = %event.hash,
-                    "chain break: hash mismatch"
-                );
-                return Ok(false);
+    /// The read-only half of `verify_report`: walk the chain without moving the watermark,
+    /// so it can run on a reader connection. The walk reads one snapshot, so an archive
+    /// pass committing meanwhile cannot move events between the segments and the live table.
+    pub fn verify_chain(&self, full: bool) -> Result<VerifyReport> {
+        let _snapshot = self.conn.unchecked_transaction()
+            .context("failed to begin verify transaction")?;
+        let watermark = if full {
+            None
+        } else {
+            match self.watermark()? {
+                Some(w) if self.watermark_trusted(&w) => Some(w),
+                Some(w) => {
+                    tracing::warn!(event_id = w.event_id, "verification watermark not signed by this ledger's key — running full verify");
+                    None
+                }
+                None => None,
             }
+        };
 
-            expected_prev_hash = event.hash.clone();
+        let anchor = watermark.as_ref().map(|w| (w.event_id, w.hash.as_str()));
+        let trusted_key = self.signer.as_ref().map(|s| s.public_key_hex());
+        let mut walker = chain::walk(&self.conn, self.archive_dir.as_deref(), anchor, trusted_key.as_deref())?;
+        if trusted_key.is_some() {
+            walker.require_checkpoint_every(self.signed_checkpoint_every);
         }
+        let report = walker.finish(watermark.is_none(), watermark.as_ref().map(|w| w.event_id).unwrap_or(0));
+        if let (Some(first), Some(range)) = (&report.first_broken, &report.untrusted) {
+            tracing::warn!(
+                event_id = first.event_id,
+                check = ?first.check,
+                untrusted_from = range.first_event_id,
+                untrusted_to = range.last_event_id,
+                issues = report.issue_count,
+                "ledger verification failed"
+            );
+        }
+        Ok(report)
+    }
 
-        Ok(true)
+    /// The last "verified up to" point, if any.
+    pub fn watermark(&self) -> Result<Option<VerifyWatermark>> {
+        let result = self.conn.query_row(
+            "SELECT event_id, hash, verified_at, public_key, signature FROM verify_state WHERE id = 1",
+            [],
+            |row| {
+                Ok(VerifyWatermark {
+                    event_id: row.get(0)?,
+                    hash: row.get(1)?,
+                    verified_at: row.get(2)?,
+                    public_key: row.get(3)?,
+                    signature: row.get(4)?,
+                })
+            },
+        );
+        match result {
+            Ok(w) => Ok(Some(w)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e).context("failed to read verification watermark"),
+        }
+    }
+
+    /// Whether `watermark` was signed by this ledger's key. Without a signer nothing
+    /// vouches for it, so it is not trusted.
+    fn watermark_trusted(&self, watermark: &VerifyWatermark) -> bool {
+        let Some(ref signer) = self.signer else {
+            return false;
+        };
+        let (Some(public_key), Some(signature)) = (&watermark.public_key, &watermark.signature) else {
+            return false;
+        };
+        *public_key == signer.public_key_hex()
+            && signing::verify_signature(
+                public_key,
+                &signing::watermark_message(watermark.event_id, &watermark.hash, &watermark.verified_at),
+                signature,
+            )
+    }
+
+    fn record_watermark(&self, event_id: i64, hash: &str) -> Result<()> {
+        let verified_at = chrono::Utc::now().to_rfc3339();
+        let (public_key, signature) = match self.signer {
+            Some(ref signer) => (
+                Some(signer.public_key_hex()),
+                Some(signer.sign(&signing::watermark_message(event_id, hash, &verified_at))),
+            ),
+            None => (None, None),
+        };
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO verify_state (id, event_id, hash, verified_at, public_key, signature)
+                 VALUES (1, ?1, ?2, ?3, ?4, ?5)",
+                params![event_id, hash, verified_at, public_key, signature],
+            )
+            .context("failed to record verification watermark")?;
+        Ok(())
+    }
+
+    /// Event types present in the live table that have no registered schema, with counts.
+    pub fn unregistered_types(&self) -> Result<Vec<(String, i64)>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT type, COUNT(*) FROM events GROUP BY type ORDER BY type")?;
+        let types = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .context("failed to list event types")?;
+        Ok(types
+            .into_iter()
+            .filter(|(t, _)| schema::lookup(t).is_none())
+            .collect())
     }
 
     /// Return the total number of events in the ledger.
@@ -447,35 +1353,542 @@ impl Ledger {
         Ok(count)
     }
 
-    // ── Incidents ──
+    /// Fetch a single event by id, reading the archive if it has been moved there.
+    pub fn get_event(&self, id: i64) -> Result<Option<Event>> {
+        chain::get_event(&self.conn, self.archive_dir.as_deref(), id)
+    }
+
+    // ── Merkle checkpoints ──
+
+    /// Last event id covered by a Merkle checkpoint (0 if none).
+    fn last_checkpointed_id(&self) -> Result<i64> {
+        self.conn
+            .query_row(
+                "SELECT COALESCE(MAX(last_event_id), 0) FROM merkle_checkpoints",
+                [],
+                |row| row.get(0),
+            )
+            .context("failed to read last merkle checkpoint")
+    }
+
+    /// Seal a checkpoint if at least `MERKLE_CHECKPOINT_INTERVAL` events are uncovered.
+    fn checkpoint_if_due(&self) -> Result<Option<MerkleCheckpoint>> {
+        let covered = self.last_checkpointed_id()?;
+        let pending: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM events WHERE id > ?1",
+            params![covered],
+            |row| row.get(0),
+        )?;
+        if pending < MERKLE_CHECKPOINT_INTERVAL {
+            return Ok(None);
+        }
+        self.checkpoint_pending()
+    }
+
+    /// Seal a Merkle checkpoint over every event not yet covered by one.
+    /// Returns None if there is nothing to seal.
+    pub fn checkpoint_pending(&self) -> Result<Option<MerkleCheckpoint>> {
+        let tx = self.conn.unchecked_transaction()
+            .context("failed to begin checkpoint transaction")?;
+
+        let covered: i64 = tx.query_row(
+            "SELECT COALESCE(MAX(last_event_id), 0) FROM merkle_checkpoints",
+            [],
+            |row| row.get(0),
+        )?;
+
+        let mut stmt = tx.prepare("SELECT id, hash FROM events WHERE id > ?1 ORDER BY id ASC")?;
+        let rows: Vec<(i64, String)> = stmt
+            .query_map(params![covered], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .context("failed to collect checkpoint leaves")?;
+        drop(stmt);
+
+        let (first_event_id, last_event_id) = match (rows.first(), rows.last()) {
+            (Some(first), Some(last)) => (first.0, last.0),
+            _ => return Ok(None),
+        };
+
+        let hashes: Vec<String> = rows.into_iter().map(|(_, h)| h).collect();
+        let leaf_count = hashes.len() as i64;
+        let root = merkle::root(&hashes);
+        let (public_key, signature) = match self.signer {
+            Some(ref signer) => (
+                Some(signer.public_key_hex()),
+                Some(signer.sign(&signing::merkle_root_message(first_event_id, last_event_id, leaf_count, &root))),
+            ),
+            None => (None, None),
+        };
+
+        tx.execute(
+            "INSERT INTO merkle_checkpoints (first_event_id, last_event_id, leaf_count, root, public_key, signature)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![first_event_id, last_event_id, leaf_count, root, public_key, signature],
+        )
+        .context("failed to insert merkle checkpoint")?;
+        let id = tx.last_insert_rowid();
+        let checkpoint = Self::merkle_checkpoint_by_id(&tx, id)?
+            .context("merkle checkpoint vanished after insert")?;
+
+        tx.commit().context("failed to commit merkle checkpoint")?;
+        tracing::info!(first_event_id, last_event_id, root = %checkpoint.root, "merkle checkpoint sealed");
+        Ok(Some(checkpoint))
+    }
+
+    fn merkle_checkpoint_by_id(conn: &Connection, id: i64) -> Result<Option<MerkleCheckpoint>> {
+        let result = conn.query_row(
+            &format!("SELECT {MERKLE_CHECKPOINT_COLUMNS} FROM merkle_checkpoints WHERE id = ?1"),
+            params![id],
+            Self::row_to_checkpoint,
+        );
+        match result {
+            Ok(c) => Ok(Some(c)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e).context("failed to read merkle checkpoint"),
+        }
+    }
+
+    fn row_to_checkpoint(row: &rusqlite::Row<'_>) -> rusqlite::Result<MerkleCheckpoint> {
+        Ok(MerkleCheckpoint {
+            id: row.get(0)?,
+            first_event_id: row.get(1)?,
+            last_event_id: row.get(2)?,
+            leaf_count: row.get(3)?,
+            root: row.get(4)?,
+            created_at: row.get(5)?,
+            public_key: row.get(6)?,
+            signature: row.get(7)?,
+        })
+    }
+
+    /// List Merkle checkpoints, newest first.
+    pub fn merkle_checkpoints(&self, limit: i64) -> Result<Vec<MerkleCheckpoint>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {MERKLE_CHECKPOINT_COLUMNS} FROM merkle_checkpoints ORDER BY id DESC LIMIT ?1"
+        ))?;
+        let checkpoints = stmt
+            .query_map(params![limit], Self::row_to_checkpoint)?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .context("failed to list merkle checkpoints")?;
+        Ok(checkpoints)
+    }
+
+    /// Build an inclusion proof for an event against the checkpoint covering it.
+    /// Returns None if the event does not exist or is not yet covered by a checkpoint.
+    pub fn inclusion_proof(&self, event_id: i64) -> Result<Option<InclusionProof>> {
+        let Some(event) = self.get_event(event_id)? else {
+            return Ok(None);
+        };
+
+        let checkpoint = match self.conn.query_row(
+            &format!(
+                "SELECT {MERKLE_CHECKPOINT_COLUMNS} FROM merkle_checkpoints
+                 WHERE first_event_id <= ?1 AND last_event_id >= ?1"
+            ),
+            params![event_id],
+            Self::row_to_checkpoint,
+        ) {
+            Ok(c) => c,
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+            Err(e) => return Err(e).context("failed to find covering checkpoint"),
+        };
+
+        let rows = self.event_hashes_between(checkpoint.first_event_id, checkpoint.last_event_id)?;
+
+        if rows.len() as i64 != checkpoint.leaf_count {
+            anyhow::bail!(
+                "checkpoint {} expects {} leaves but {} events remain in range",
+                checkpoint.id, checkpoint.leaf_count, rows.len()
+            );
+        }
+
+        let leaf_index = rows
+            .iter()
+            .position(|(id, _)| *id == event_id)
+            .context("event missing from its checkpoint range")?;
+        let hashes: Vec<String> = rows.into_iter().map(|(_, h)| h).collect();
+        let path = merkle::proof(&hashes, leaf_index).context("leaf index out of range")?;
+
+        Ok(Some(InclusionProof {
+            event,
+            leaf_index,
+            checkpoint,
+            path,
+        }))
+    }
+
+    // ── Signed checkpoints ──
+
+    /// Attach agentd's signing identity. Checkpoints are signed every `every_events` events.
+    pub fn set_signer(&mut self, signer: LedgerSigner, every_events: i64) {
+        self.signer = Some(signer);
+        self.signed_checkpoint_every = every_events.max(1);
+    }
+
+    fn last_signed_event_id(&self) -> Result<i64> {
+        self.conn
+            .query_row(
+                "SELECT COALESCE(MAX(event_id), 0) FROM checkpoints",
+                [],
+                |row| row.get(0),
+            )
+            .context("failed to read last signed checkpoint")
+    }
+
+    fn sign_checkpoint_if_due(&self, head_id: i64) -> Result<Option<SignedCheckpoint>> {
+        if self.signer.is_none() {
+            return Ok(None);
+        }
+        if head_id - self.last_signed_event_id()? < self.signed_checkpoint_every {
+            return Ok(None);
+        }
+        self.sign_checkpoint()
+    }
+
+    /// Sign the current head hash. Returns None if no signer is configured,
+    /// the ledger is empty, or the head is already covered by a signed checkpoint.
+    pub fn sign_checkpoint(&self) -> Result<Option<SignedCheckpoint>> {
+        let Some(ref signer) = self.signer else {
+            return Ok(None);
+        };
+
+        let head: Option<(i64, String)> = match self.conn.query_row(
+            "SELECT id, hash FROM events ORDER BY id DESC LIMIT 1",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ) {
+            Ok(h) => Some(h),
+            Err(rusqlite::Error::QueryReturnedNoRows) => None,
+            Err(e) => return Err(e).context("failed to read ledger head"),
+        };
+        let Some((event_id, head_hash)) = head else {
+            return Ok(None);
+        };
+        if event_id <= self.last_signed_event_id()? {
+            return Ok(None);
+        }
+
+        let signed_at = chrono::Utc::now().to_rfc3339();
+        let public_key = signer.public_key_hex();
+        let signature = signer.sign(&signing::checkpoint_message(event_id, &head_hash, &signed_at));
 
-    /// Create a new incident workspace.
-    pub fn create_incident(&self, id: &str, name: &str) -> Result<()> {
         self.conn
             .execute(
-                "INSERT INTO incidents (id, name) VALUES (?1, ?2)",
-                params![id, name],
+                "INSERT INTO checkpoints (event_id, head_hash, signed_at, public_key, signature) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![event_id, head_hash, signed_at, public_key, signature],
             )
-            .context("failed to create incident")?;
+            .context("failed to insert signed checkpoint")?;
+
+        Ok(Some(SignedCheckpoint {
+            id: self.conn.last_insert_rowid(),
+            event_id,
+            head_hash,
+            signed_at,
+            public_key,
+            signature,
+        }))
+    }
+
+    /// List signed checkpoints, newest first.
+    pub fn signed_checkpoints(&self, limit: i64) -> Result<Vec<SignedCheckpoint>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {CHECKPOINT_COLUMNS} FROM checkpoints ORDER BY id DESC LIMIT ?1"
+        ))?;
+        let checkpoints = stmt
+            .query_map(params![limit], SignedCheckpoint::from_row)?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .context("failed to list signed checkpoints")?;
+        Ok(checkpoints)
+    }
+
+    // ── Export ──
+
+    /// Write the full audit history as JSONL; see `export::write_jsonl`.
+    pub fn export_jsonl<W: std::io::Write>(&self, out: &mut W) -> Result<ExportSummary> {
+        export::write_jsonl(&self.conn, self.archive_dir.as_deref(), out)
+    }
+
+    // ── Retention ──
+
+    /// Directory where sealed archive segments are written and read.
+    pub fn set_archive_dir(&mut self, dir: PathBuf) {
+        self.archive_dir = Some(dir);
+    }
+
+    /// Settle archive segments a crash left staged: move committed ones into place and
+    /// delete the rest. Call once at startup, before anything reads the archive.
+    pub fn reconcile_archive(&self) -> Result<()> {
+        let Some(dir) = self.archive_dir.as_ref() else {
+            return Ok(());
+        };
+        let committed = self.segments()?.into_iter().map(|s| (s.file, s.sha256)).collect();
+        for file in archive::reconcile_staged(dir, &committed)? {
+            tracing::warn!(file = %file, "moved a committed ledger segment into place after an interrupted archive pass");
+        }
         Ok(())
     }
 
-    /// Add a step to an incident.
+    /// List archive segments, oldest first.
+    pub fn segments(&self) -> Result<Vec<LedgerSegment>> {
+        archive::segments(&self.conn)
+    }
+
+    fn read_segment(&self, segment: &LedgerSegment) -> Result<Vec<Event>> {
+        archive::read_indexed_segment(self.archive_dir.as_deref(), segment)
+    }
+
+    /// (id, hash) for every event in `first..=last`, reading archive segments as needed.
+    fn event_hashes_between(&self, first: i64, last: i64) -> Result<Vec<(i64, String)>> {
+        let mut rows = Vec::new();
+        for segment in self.segments()? {
+            if segment.last_event_id < first || segment.first_event_id > last {
+                continue;
+            }
+            rows.extend(
+                self.read_segment(&segment)?
+                    .into_iter()
+                    .filter(|e| e.id >= first && e.id <= last)
+                    .map(|e| (e.id, e.hash)),
+            );
+        }
+
+        let mut stmt = self.conn.prepare(
+            "SELECT id, hash FROM events WHERE id >= ?1 AND id <= ?2 ORDER BY id ASC",
+        )?;
+        let live = stmt
+            .query_map(params![first, last], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<std::result::Result<Vec<(i64, String)>, _>>()
+            .context("failed to collect event hashes")?;
+        rows.extend(live);
+        Ok(rows)
+    }
+
+    /// Move the oldest events outside the retention window into one sealed segment
+    /// (at most `SEGMENT_MAX_EVENTS`). Only events already covered by a Merkle
+    /// checkpoint are archived, so inclusion proofs keep working afterwards.
+    /// Returns None when nothing is due; call repeatedly to drain a backlog.
+    pub fn archive_expired(&self, policy: &RetentionPolicy) -> Result<Option<LedgerSegment>> {
+        if policy.keep_events <= 0 {
+            return Ok(None);
+        }
+        let dir = self
+            .archive_dir
+            .as_ref()
+            .context("no archive directory configured")?;
+
+        let head: i64 = self
+            .conn
+            .query_row("SELECT COALESCE(MAX(id), 0) FROM events", [], |row| row.get(0))?;
+        let mut cutoff = (head - policy.keep_events).min(self.last_checkpointed_id()?);
+        if policy.keep_days > 0 {
+            let horizon = (chrono::Utc::now() - chrono::Duration::days(policy.keep_days))
+                .format("%Y-%m-%dT%H:%M:%S%.3fZ")
+                .to_string();
+            let aged: i64 = self.conn.query_row(
+                "SELECT COALESCE(MAX(id), 0) FROM events WHERE ts < ?1",
+                params![horizon],
+                |row| row.get(0),
+            )?;
+            cutoff = cutoff.min(aged);
+        }
+
+        let mut stmt = self.conn.prepare(
+            "SELECT id, ts, type, actor, payload, prev_hash, hash, verified_actor, payload_digest FROM events
+             WHERE id <= ?1 ORDER BY id ASC LIMIT ?2",
+        )?;
+        let events: Vec<Event> = stmt
+            .query_map(params![cutoff, SEGMENT_MAX_EVENTS], Event::from_row)?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .context("failed to collect events to archive")?;
+        drop(stmt);
+
+        let (Some(first), Some(last)) = (events.first(), events.last()) else {
+            return Ok(None);
+        };
+        let (first_event_id, last_event_id) = (first.id, last.id);
+        let first_prev_hash = first.prev_hash.clone();
+        let last_hash = last.hash.clone();
+
+        // The segment must continue exactly where earlier history ends
+        let expected_prev = self
+            .conn
+            .query_row(
+                "SELECT last_hash FROM ledger_segments ORDER BY last_event_id DESC LIMIT 1",
+                [],
+                |row| row.get::<_, String>(0),
+            )
+            .unwrap_or_else(|_| GENESIS_PREV_HASH.to_string());
+        if first_prev_hash != expected_prev {
+            anyhow::bail!("refusing to archive event #{first_event_id}: it does not link to the previous segment");
+        }
+
+        // Staged until the index commits; a crash in between is settled by `reconcile_archive`
+        let staged = archive::stage_segment(dir, &events)?;
+        let committed = (|| {
+            let tx = self.conn.unchecked_transaction()
+                .context("failed to begin archive transaction")?;
+            tx.execute(
+                "INSERT INTO ledger_segments (first_event_id, last_event_id, event_count, first_prev_hash, last_hash, file, sha256)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![first_event_id, last_event_id, events.len() as i64, first_prev_hash, last_hash, staged.file, staged.sha256],
+            )
+            .context("failed to record ledger segment")?;
+            let segment_id = tx.last_insert_rowid();
+            tx.execute(
+                "DELETE FROM events WHERE id >= ?1 AND id <= ?2",
+                params![first_event_id, last_event_id],
+            )
+            .context("failed to remove archived events")?;
+            tx.commit().context("failed to commit archive segment")?;
+            Ok::<_, anyhow::Error>(segment_id)
+        })();
+        let segment_id = match committed {
+            Ok(id) => id,
+            Err(e) => {
+                staged.discard();
+                return Err(e);
+            }
+        };
+        staged
+            .publish()
+            .context("ledger segment committed but not moved into place; it will be on the next start")?;
+
+        let segment = self
+            .segments()?
+            .into_iter()
+            .find(|s| s.id == segment_id)
+            .context("ledger segment vanished after insert")?;
+        tracing::info!(first_event_id, last_event_id, file = %segment.file, "ledger segment archived");
+        Ok(Some(segment))
+    }
+
+    // ── Incidents ──
+
+    /// Create a new incident workspace and record `incident.create`, in one transaction.
+    pub fn create_incident(
+        &self,
+        id: &str,
+        name: &str,
+        fields: &IncidentFields,
+        verified_actor: Option<&str>,
+    ) -> Result<Event> {
+        let tx = self.conn.unchecked_transaction()
+            .context("failed to begin incident transaction")?;
+        let event = Self::insert_incident(&tx, id, name, fields, verified_actor)?;
+        tx.commit().context("failed to commit incident")?;
+
+        self.after_commit(std::slice::from_ref(&event));
+        Ok(event)
+    }
+
+    /// Create an incident together with its first step, recording `incident.create` and
+    /// `incident.step`, in one transaction.
+    pub fn create_incident_with_step(
+        &self,
+        id: &str,
+        name: &str,
+        fields: &IncidentFields,
+        step: &NewIncidentStep<'_>,
+        verified_actor: Option<&str>,
+    ) -> Result<Vec<Event>> {
+        let tx = self.conn.unchecked_transaction()
+            .context("failed to begin incident transaction")?;
+        let events = vec![
+            Self::insert_incident(&tx, id, name, fields, verified_actor)?,
+            Self::insert_incident_step(&tx, id, step, verified_actor)?,
+        ];
+        tx.commit().context("failed to commit incident")?;
+
+        self.after_commit(&events);
+        Ok(events)
+    }
+
+    fn insert_incident(
+        tx: &Connection,
+        id: &str,
+        name: &str,
+        fields: &IncidentFields,
+        verified_actor: Option<&str>,
+    ) -> Result<Event> {
+        let payload = serde_json::json!({
+            "incident_id": id,
+            "name": name,
+            "severity": fields.severity,
+            "affected_services": fields.affected_services,
+            "assignee": fields.assignee,
+            "correlation_key": fields.correlation_key,
+        })
+        .to_string();
+        Self::validate_payload("incident.create", &payload)?;
+
+        tx.execute(
+            "INSERT INTO incidents (id, name, severity, affected_services, assignee, correlation_key)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                id,
+                name,
+                fields.severity,
+                serde_json::to_string(&fields.affected_services)?,
+                fields.assignee,
+                fields.correlation_key,
+            ],
+        )
+        .context("failed to create incident")?;
+        Self::insert_event(tx, "incident.create", "agentd", verified_actor, &payload)
+    }
+
+    /// Add the next step to an incident and record `incident.step`, in one transaction.
+    /// None if the incident does not exist.
     pub fn add_incident_step(
         &self,
         incident_id: &str,
-        step_number: u32,
-        action: &str,
-        result: &str,
-        receipt_id: Option<&str>,
-    ) -> Result<()> {
-        self.conn
-            .execute(
-                "INSERT INTO incident_steps (incident_id, step_number, action, result, receipt_id) VALUES (?1, ?2, ?3, ?4, ?5)",
-                params![incident_id, step_number, action, result, receipt_id],
+        step: &NewIncidentStep<'_>,
+        verified_actor: Option<&str>,
+    ) -> Result<Option<Event>> {
+        let tx = self.conn.unchecked_transaction()
+            .context("failed to begin incident step transaction")?;
+        let exists: bool = tx
+            .query_row("SELECT EXISTS(SELECT 1 FROM incidents WHERE id = ?1)", params![incident_id], |row| row.get(0))
+            .context("failed to look up incident")?;
+        if !exists {
+            return Ok(None);
+        }
+        let event = Self::insert_incident_step(&tx, incident_id, step, verified_actor)?;
+        tx.commit().context("failed to commit incident step")?;
+
+        self.after_commit(std::slice::from_ref(&event));
+        Ok(Some(event))
+    }
+
+    /// Number and insert a step, then its `incident.step` event, inside an open transaction.
+    fn insert_incident_step(
+        tx: &Connection,
+        incident_id: &str,
+        step: &NewIncidentStep<'_>,
+        verified_actor: Option<&str>,
+    ) -> Result<Event> {
+        let step_number: u32 = tx
+            .query_row(
+                "SELECT COALESCE(MAX(step_number), 0) + 1 FROM incident_steps WHERE incident_id = ?1",
+                params![incident_id],
+                |row| row.get(0),
             )
-            .context("failed to add incident step")?;
-        Ok(())
+            .context("failed to number incident step")?;
+        let payload = serde_json::json!({
+            "incident_id": incident_id,
+            "step_number": step_number,
+            "action": step.action,
+            "result": step.result,
+        })
+        .to_string();
+        Self::validate_payload("incident.step", &payload)?;
+
+        tx.execute(
+            "INSERT INTO incident_steps (incident_id, step_number, action, result, receipt_id) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![incident_id, step_number, step.action, step.result, step.receipt_id],
+        )
+        .context("failed to add incident step")?;
+        Self::insert_event(tx, "incident.step", "agentd", verified_actor, &payload)
     }
 
     /// Get an incident by ID with all its steps.
@@ -483,20 +1896,12 @@ impl Ledger {
         let incident = self
             .conn
             .query_row(
-                "SELECT id, name, status, created_at, resolved_at FROM incidents WHERE id = ?1",
+                &format!("SELECT {INCIDENT_COLUMNS} FROM incidents WHERE id = ?1"),
                 params![id],
-                |row| {
-                    Ok(IncidentRow {
-                        id: row.get(0)?,
-                        name: row.get(1)?,
-                        status: row.get(2)?,
-                        created_at: row.get(3)?,
-                        resolved_at: row.get(4)?,
-                        steps: Vec::new(),
-                    })
-                },
+                Self::row_to_incident,
             )
-            .ok();
+            .optional()
+            .context("failed to query incident")?;
 
         match incident {
             Some(mut inc) => {
@@ -527,33 +1932,27 @@ impl Ledger {
         Ok(steps)
     }
 
-    /// List incidents, optionally filtered by status.
-    pub fn list_incidents(&self, status: Option<&str>) -> Result<Vec<IncidentRow>> {
-        let (sql, param_values): (String, Vec<Box<dyn rusqlite::types::ToSql>>) = match status {
-            Some(s) => (
-                "SELECT id, name, status, created_at, resolved_at FROM incidents WHERE status = ?1 ORDER BY created_at DESC".to_string(),
-                vec![Box::new(s.to_string())],
-            ),
-            None => (
-                "SELECT id, name, status, created_at, resolved_at FROM incidents ORDER BY created_at DESC".to_string(),
-                vec![],
-            ),
-        };
+    /// List incidents, newest first, optionally filtered by status, severity and assignee.
+    pub fn list_incidents(&self, filter: &IncidentFilter) -> Result<Vec<IncidentRow>> {
+        let mut sql = format!("SELECT {INCIDENT_COLUMNS} FROM incidents WHERE 1=1");
+        let mut param_values: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
+        for (column, value) in [
+            ("status", &filter.status),
+            ("severity", &filter.severity),
+            ("assignee", &filter.assignee),
+        ] {
+            if let Some(value) = value {
+                sql.push_str(&format!(" AND {column} = ?{}", param_values.len() + 1));
+                param_values.push(Box::new(value.clone()));
+            }
+        }
+        sql.push_str(" ORDER BY created_at DESC");
 
         let params_refs: Vec<&dyn rusqlite::types::ToSql> =
             param_values.iter().map(|p| p.as_ref()).collect();
         let mut stmt = self.conn.prepare(&sql)?;
         let incidents = stmt
-            .query_map(params_refs.as_slice(), |row| {
-                Ok(IncidentRow {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    status: row.get(2)?,
-                    created_at: row.get(3)?,
-                    resolved_at: row.get(4)?,
-                    steps: Vec::new(),
-                })
-            })?
+            .query_map(params_refs.as_slice(), Self::row_to_incident)?
             .collect::<std::result::Result<Vec<_>, _>>()
             .context("failed to list incidents")?;
 
@@ -566,17 +1965,619 @@ impl Ledger {
         Ok(result)
     }
 
-    /// Update incident status (e.g., "resolved").
-    #[allow(dead_code)] // Public API — will be called from incident resolution endpoint
-    pub fn resolve_incident(&self, id: &str) -> Result<()> {
-        self.conn.execute(
-            "UPDATE incidents SET status = 'resolved', resolved_at = strftime('%Y-%m-%dT%H:%M:%fZ','now') WHERE id = ?1",
-            params![id],
-        ).context("failed to resolve incident")?;
-        Ok(())
+    /// Change severity, affected services and/or assignee (an empty assignee clears it),
+    /// recording `incident.update` with the fields that changed.
+    pub fn update_incident(
+        &self,
+        id: &str,
+        update: &IncidentUpdate,
+        verified_actor: Option<&str>,
+    ) -> Result<IncidentTransition> {
+        let Some(current) = self.get_incident(id)? else {
+            return Ok(IncidentTransition::NotFound);
+        };
+
+        let mut changes = serde_json::Map::new();
+        if let Some(ref severity) = update.severity {
+            if *severity != current.severity {
+                changes.insert("severity".into(), severity.clone().into());
+            }
+        }
+        if let Some(ref services) = update.affected_services {
+            if *services != current.affected_services {
+                changes.insert("affected_services".into(), serde_json::json!(services));
+            }
+        }
+        if let Some(ref assignee) = update.assignee {
+            let assignee = Some(assignee.as_str()).filter(|a| !a.is_empty());
+            if assignee != current.assignee.as_deref() {
+                changes.insert("assignee".into(), serde_json::json!(assignee));
+            }
+        }
+        if changes.is_empty() {
+            return Ok(IncidentTransition::Unchanged);
+        }
+        let mut payload = changes.clone();
+        payload.insert("incident_id".into(), id.into());
+        let payload = serde_json::Value::Object(payload).to_string();
+        Self::validate_payload("incident.update", &payload)?;
+
+        let tx = self.conn.unchecked_transaction()
+            .context("failed to begin incident transaction")?;
+        for (field, value) in &changes {
+            let value = match value {
+                serde_json::Value::Array(_) => Some(value.to_string()),
+                other => other.as_str().map(str::to_string),
+            };
+            // Column names come from the fixed set above
+            tx.execute(&format!("UPDATE incidents SET {field} = ?2 WHERE id = ?1"), params![id, value])
+                .with_context(|| format!("failed to update incident {field}"))?;
+        }
+        let event = Self::insert_event(&tx, "incident.update", "agentd", verified_actor, &payload)?;
+        tx.commit().context("failed to commit incident update")?;
+
+        self.after_commit(std::slice::from_ref(&event));
+        Ok(IncidentTransition::Applied(event))
+    }
+
+    /// Resolve an open incident with a summary of what fixed it, recording
+    /// `incident.resolve` with the time to resolve.
+    pub fn resolve_incident(&self, id: &str, resolution: &str, verified_actor: Option<&str>) -> Result<IncidentTransition> {
+        self.transition_incident(id, "resolved", verified_actor, |tx, incident| {
+            tx.execute(
+                "UPDATE incidents SET status = 'resolved', resolution = ?2,
+                        resolved_at = strftime('%Y-%m-%dT%H:%M:%fZ','now') WHERE id = ?1",
+                params![incident.id, resolution],
+            )
+            .context("failed to resolve incident")?;
+            let resolved = tx.query_row(
+                &format!("SELECT {INCIDENT_COLUMNS} FROM incidents WHERE id = ?1"),
+                params![incident.id],
+                Self::row_to_incident,
+            )?;
+            Ok(("incident.resolve", serde_json::json!({
+                "incident_id": incident.id,
+                "resolution": resolution,
+                "time_to_resolve_secs": resolved.time_to_resolve_secs,
+            })))
+        })
+    }
+
+    /// Reopen a resolved incident. The previous resolution stays in its
+    /// `incident.resolve` event; time to resolve will count from the original creation.
+    pub fn reopen_incident(&self, id: &str, reason: &str, verified_actor: Option<&str>) -> Result<IncidentTransition> {
+        self.transition_incident(id, "open", verified_actor, |tx, incident| {
+            tx.execute(
+                "UPDATE incidents SET status = 'open', resolution = NULL, resolved_at = NULL,
+                        reopen_count = reopen_count + 1 WHERE id = ?1",
+                params![incident.id],
+            )
+            .context("failed to reopen incident")?;
+            Ok(("incident.reopen", serde_json::json!({
+                "incident_id": incident.id,
+                "reason": reason,
+                "previous_resolution": incident.resolution,
+            })))
+        })
+    }
+
+    /// Move an incident into status `to` (Unchanged if it is already there) and record
+    /// the event `apply` describes, in one transaction.
+    fn transition_incident(
+        &self,
+        id: &str,
+        to: &str,
+        verified_actor: Option<&str>,
+        apply: impl FnOnce(&Connection, &IncidentRow) -> Result<(&'static str, serde_json::Value)>,
+    ) -> Result<IncidentTransition> {
+        let Some(incident) = self.get_incident(id)? else {
+            return Ok(IncidentTransition::NotFound);
+        };
+        if incident.status == to {
+            return Ok(IncidentTransition::Unchanged);
+        }
+
+        let tx = self.conn.unchecked_transaction()
+            .context("failed to begin incident transaction")?;
+        let (event_type, payload) = apply(&tx, &incident)?;
+        let payload = payload.to_string();
+        Self::validate_payload(event_type, &payload)?;
+        let event = Self::insert_event(&tx, event_type, "agentd", verified_actor, &payload)?;
+        tx.commit().context("failed to commit incident transition")?;
+
+        self.after_commit(std::slice::from_ref(&event));
+        Ok(IncidentTransition::Applied(event))
+    }
+
+    /// Open incidents with `correlation_key`, most recently active first, with the
+    /// time of their last step (or creation).
+    pub fn open_correlated_incidents(&self, correlation_key: &str) -> Result<Vec<(String, String)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT i.id,
+                    COALESCE((SELECT MAX(s.timestamp) FROM incident_steps s WHERE s.incident_id = i.id), i.created_at)
+                        AS last_activity
+             FROM incidents i
+             WHERE i.correlation_key = ?1 AND i.status = 'open'
+             ORDER BY last_activity DESC, i.id ASC",
+        )?;
+        let incidents = stmt
+            .query_map(params![correlation_key], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .context("failed to query correlated incidents")?;
+        Ok(incidents)
+    }
+
+    /// The incident with `correlation_key` most recently resolved at or after `since`.
+    pub fn recently_resolved_correlated_incident(&self, correlation_key: &str, since: &str) -> Result<Option<String>> {
+        self.conn
+            .query_row(
+                "SELECT id FROM incidents
+                 WHERE correlation_key = ?1 AND status = 'resolved' AND resolved_at >= ?2
+                 ORDER BY resolved_at DESC, id ASC LIMIT 1",
+                params![correlation_key, since],
+                |row| row.get(0),
+            )
+            .optional()
+            .context("failed to query resolved correlated incidents")
+    }
+
+    /// Every `incident.*` event recorded for an incident, archived or live, oldest first.
+    pub fn incident_events(&self, incident_id: &str) -> Result<Vec<Event>> {
+        let created_at: Option<String> = self
+            .conn
+            .query_row("SELECT created_at FROM incidents WHERE id = ?1", params![incident_id], |row| row.get(0))
+            .optional()
+            .context("failed to look up incident")?;
+        let mut events = self.archived_events_since(created_at.as_deref().unwrap_or(""), |e| {
+            e.event_type.starts_with("incident.")
+                && serde_json::from_str::<serde_json::Value>(&e.payload)
+                    .is_ok_and(|p| p.get("incident_id").and_then(|v| v.as_str()) == Some(incident_id))
+        })?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT id, ts, type, actor, payload, prev_hash, hash, verified_actor, payload_digest
+             FROM events
+             WHERE type LIKE 'incident.%'
+               AND CASE WHEN json_valid(payload) THEN json_extract(payload, '$.incident_id') END = ?1
+             ORDER BY id ASC",
+        )?;
+        let live = stmt
+            .query_map(params![incident_id], Event::from_row)?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .context("failed to query incident events")?;
+        events.extend(live);
+        Ok(events)
+    }
+
+    /// Events, archived or live, from `since` up to and including `until` whose actor
+    /// or verified actor is one of `actors`, oldest first.
+    pub fn events_by_actors(
+        &self,
+        actors: &[String],
+        since: &str,
+        until: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<Event>> {
+        let mut events = self.archived_events_since(since, |e| {
+            until.is_none_or(|until| e.ts.as_str() <= until)
+                && (actors.contains(&e.actor) || e.verified_actor.as_ref().is_some_and(|v| actors.contains(v)))
+        })?;
+        events.truncate(limit.max(0) as usize);
+        let remaining = limit - events.len() as i64;
+        if remaining <= 0 {
+            return Ok(events);
+        }
+
+        let actors = serde_json::to_string(actors)?;
+        let mut stmt = self.conn.prepare(
+            "SELECT id, ts, type, actor, payload, prev_hash, hash, verified_actor, payload_digest
+             FROM events
+             WHERE ts >= ?1 AND (?2 IS NULL OR ts <= ?2)
+               AND (actor IN (SELECT value FROM json_each(?3))
+                    OR verified_actor IN (SELECT value FROM json_each(?3)))
+             ORDER BY id ASC LIMIT ?4",
+        )?;
+        let live = stmt
+            .query_map(params![since, until, actors, remaining], Event::from_row)?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .context("failed to query events by actor")?;
+        events.extend(live);
+        Ok(events)
+    }
+
+    /// Archived events at or after `since` that `keep` accepts, oldest first. Timestamps
+    /// only grow along the chain, so segments are read newest first and the walk stops at
+    /// the first one that starts before `since`.
+    fn archived_events_since(&self, since: &str, keep: impl Fn(&Event) -> bool) -> Result<Vec<Event>> {
+        let mut events = Vec::new();
+        for segment in self.segments()?.iter().rev() {
+            let archived = self.read_segment(segment)?;
+            let reached_since = archived.first().is_none_or(|e| e.ts.as_str() < since);
+            events.extend(archived.into_iter().rev().filter(|e| e.ts.as_str() >= since && keep(e)));
+            if reached_since {
+                break;
+            }
+        }
+        events.reverse();
+        Ok(events)
+    }
+
+    fn row_to_incident(row: &rusqlite::Row<'_>) -> rusqlite::Result<IncidentRow> {
+        let services: String = row.get(6)?;
+        Ok(IncidentRow {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            status: row.get(2)?,
+            created_at: row.get(3)?,
+            resolved_at: row.get(4)?,
+            severity: row.get(5)?,
+            affected_services: serde_json::from_str(&services).unwrap_or_default(),
+            assignee: row.get(7)?,
+            resolution: row.get(8)?,
+            reopen_count: row.get(9)?,
+            time_to_resolve_secs: row.get(10)?,
+            correlation_key: row.get(11)?,
+            steps: Vec::new(),
+        })
+    }
+
+    // ── Memory store ──
+
+    /// Append a memory event and register it in the memory store in one transaction.
+    /// The ledger keeps the event for audit; the store decides whether recall sees it.
+    pub fn remember(
+        &self,
+        event_type: &str,
+        actor: &str,
+        verified_actor: Option<&str>,
+        payload: &str,
+        options: &MemoryOptions,
+    ) -> Result<(Event, MemoryRecord)> {
+        Self::validate_payload(event_type, payload)?;
+
+        let tx = self.conn.unchecked_transaction()
+            .context("failed to begin transaction")?;
+        let event = Self::insert_event(&tx, event_type, actor, verified_actor, payload)?;
+        let expires_at = options
+            .ttl_secs
+            .map(|ttl| format_ts(chrono::Utc::now() + chrono::Duration::seconds(ttl)));
+        tx.execute(
+            "INSERT OR REPLACE INTO memories (event_id, namespace, created_at, expires_at, pinned) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![event.id, options.namespace, event.ts, expires_at, options.pinned],
+        )
+        .context("failed to register memory")?;
+        tx.commit().context("failed to commit memory")?;
+
+        self.after_commit(std::slice::from_ref(&event));
+        let record = MemoryRecord {
+            event_id: event.id,
+            namespace: options.namespace.clone(),
+            created_at: event.ts.clone(),
+            expires_at,
+            pinned: options.pinned,
+            superseded_by: None,
+        };
+        Ok((event, record))
+    }
+
+    /// Store entries for the given events; events that are not (or no longer) memories are absent.
+    pub fn memory_records(&self, event_ids: &[i64]) -> Result<HashMap<i64, MemoryRecord>> {
+        if event_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let placeholders = vec!["?"; event_ids.len()].join(", ");
+        let sql = format!(
+            "SELECT event_id, namespace, created_at, expires_at, pinned, superseded_by FROM memories WHERE event_id IN ({placeholders})"
+        );
+        let mut stmt = self.conn.prepare(&sql).context("failed to prepare memory lookup")?;
+        let records = stmt
+            .query_map(rusqlite::params_from_iter(event_ids), Self::row_to_memory)
+            .context("failed to look up memories")?
+            .map(|r| r.map(|m| (m.event_id, m)))
+            .collect::<std::result::Result<HashMap<_, _>, _>>()
+            .context("failed to read memories")?;
+        Ok(records)
+    }
+
+    /// Live memories in `namespace`, newest first, with their events.
+    pub fn list_memories(&self, namespace: &str, limit: i64) -> Result<Vec<(MemoryRecord, Event)>> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT m.event_id, m.namespace, m.created_at, m.expires_at, m.pinned, m.superseded_by,
+                        e.id, e.ts, e.type, e.actor, e.payload, e.prev_hash, e.hash, e.verified_actor, e.payload_digest
+                 FROM memories m JOIN events e ON e.id = m.event_id
+                 WHERE m.namespace = ?1
+                   AND (m.pinned = 1 OR m.expires_at IS NULL OR m.expires_at > strftime('%Y-%m-%dT%H:%M:%fZ','now'))
+                 ORDER BY m.event_id DESC
+                 LIMIT ?2",
+            )
+            .context("failed to prepare memory listing")?;
+        let memories = stmt
+            .query_map(params![namespace, limit], Self::row_to_memory_event)
+            .context("failed to list memories")?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .context("failed to read memories")?;
+        Ok(memories)
+    }
+
+    /// Pin (exempt from expiry) or unpin a memory, recording a `memory.pin` event when
+    /// that changes anything. None if the memory is not in the store.
+    pub fn set_memory_pinned(
+        &self,
+        event_id: i64,
+        pinned: bool,
+        actor: &str,
+        verified_actor: Option<&str>,
+    ) -> Result<Option<MemoryRecord>> {
+        let payload = serde_json::json!({"event_id": event_id, "pinned": pinned}).to_string();
+        Self::validate_payload("memory.pin", &payload)?;
+        let tx = self.conn.unchecked_transaction()
+            .context("failed to begin pin transaction")?;
+        let changed = tx
+            .execute(
+                "UPDATE memories SET pinned = ?2 WHERE event_id = ?1 AND pinned != ?2",
+                params![event_id, pinned],
+            )
+            .context("failed to update memory pin")?;
+        let audit = if changed > 0 {
+            Some(Self::insert_event(&tx, "memory.pin", actor, verified_actor, &payload)?)
+        } else {
+            None
+        };
+        tx.commit().context("failed to commit memory pin")?;
+
+        if let Some(audit) = audit {
+            self.after_commit(std::slice::from_ref(&audit));
+        }
+        Ok(self.memory_records(&[event_id])?.remove(&event_id))
+    }
+
+    /// Drop memories from the store and record a `memory.forget` event listing them, in one
+    /// transaction. A namespace-wide forget keeps pinned memories. The events themselves stay
+    /// in the ledger (use redaction to remove their content). None if nothing matched.
+    pub fn forget_memories(
+        &self,
+        target: &ForgetTarget,
+        reason: &str,
+        actor: &str,
+        verified_actor: Option<&str>,
+    ) -> Result<Option<(Vec<i64>, Event)>> {
+        let tx = self.conn.unchecked_transaction()
+            .context("failed to begin forget transaction")?;
+
+        let (event_ids, namespace): (Vec<i64>, Option<String>) = match target {
+            ForgetTarget::Event(id) => {
+                let namespace: Option<String> = tx
+                    .query_row("SELECT namespace FROM memories WHERE event_id = ?1", params![id], |row| row.get(0))
+                    .optional()
+                    .context("failed to look up memory")?;
+                match namespace {
+                    Some(ns) => (vec![*id], Some(ns)),
+                    None => return Ok(None),
+                }
+            }
+            ForgetTarget::Namespace(ns) => {
+                let mut stmt = tx
+                    .prepare("SELECT event_id FROM memories WHERE namespace = ?1 AND pinned = 0 ORDER BY event_id")
+                    .context("failed to prepare namespace forget")?;
+                let ids = stmt
+                    .query_map(params![ns], |row| row.get(0))?
+                    .collect::<std::result::Result<Vec<i64>, _>>()
+                    .context("failed to list namespace memories")?;
+                (ids, Some(ns.clone()))
+            }
+        };
+        if event_ids.is_empty() {
+            return Ok(None);
+        }
+
+        for id in &event_ids {
+            tx.execute("DELETE FROM memories WHERE event_id = ?1", params![id])
+                .context("failed to forget memory")?;
+        }
+        let payload = serde_json::json!({
+            "event_ids": event_ids,
+            "namespace": namespace,
+            "reason": reason,
+        })
+        .to_string();
+        Self::validate_payload("memory.forget", &payload)?;
+        let audit = Self::insert_event(&tx, "memory.forget", actor, verified_actor, &payload)?;
+        tx.commit().context("failed to commit forget")?;
+
+        self.after_commit(std::slice::from_ref(&audit));
+        Ok(Some((event_ids, audit)))
+    }
+
+    /// Drop unpinned memories past their expiry, recording one `memory.expire` event per
+    /// namespace. Returns those events.
+    pub fn expire_memories(&self) -> Result<Vec<Event>> {
+        let tx = self.conn.unchecked_transaction()
+            .context("failed to begin expiry transaction")?;
+
+        let expired: Vec<(i64, String)> = {
+            let mut stmt = tx
+                .prepare(
+                    "SELECT event_id, namespace FROM memories
+                     WHERE pinned = 0 AND expires_at IS NOT NULL
+                       AND expires_at <= strftime('%Y-%m-%dT%H:%M:%fZ','now')
+                     ORDER BY namespace, event_id",
+                )
+                .context("failed to prepare expiry query")?;
+            let rows = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .context("failed to list expired memories")?;
+            rows
+        };
+        if expired.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut events = Vec::new();
+        for group in expired.chunk_by(|a, b| a.1 == b.1) {
+            let namespace = &group[0].1;
+            let ids: Vec<i64> = group.iter().map(|(id, _)| *id).collect();
+            for id in &ids {
+                tx.execute("DELETE FROM memories WHERE event_id = ?1", params![id])
+                    .context("failed to expire memory")?;
+            }
+            let payload = serde_json::json!({"namespace": namespace, "event_ids": ids}).to_string();
+            Self::validate_payload("memory.expire", &payload)?;
+            events.push(Self::insert_event(&tx, "memory.expire", "agentd", None, &payload)?);
+        }
+        tx.commit().context("failed to commit memory expiry")?;
+
+        self.after_commit(&events);
+        Ok(events)
+    }
+
+    /// Live memory.ingest entries not yet superseded, grouped by namespace, newest
+    /// `limit` overall: the input to a consolidation pass.
+    pub fn consolidation_candidates(&self, limit: i64) -> Result<Vec<(MemoryRecord, Event)>> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT * FROM (
+                     SELECT m.event_id, m.namespace, m.created_at, m.expires_at, m.pinned, m.superseded_by,
+                            e.id, e.ts, e.type, e.actor, e.payload, e.prev_hash, e.hash, e.verified_actor, e.payload_digest
+                     FROM memories m JOIN events e ON e.id = m.event_id
+                     WHERE e.type = 'memory.ingest' AND m.superseded_by IS NULL
+                       AND (m.pinned = 1 OR m.expires_at IS NULL OR m.expires_at > strftime('%Y-%m-%dT%H:%M:%fZ','now'))
+                     ORDER BY m.event_id DESC
+                     LIMIT ?1)
+                 ORDER BY namespace, event_id",
+            )
+            .context("failed to prepare consolidation query")?;
+        let candidates = stmt
+            .query_map(params![limit], Self::row_to_memory_event)
+            .context("failed to list consolidation candidates")?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .context("failed to read consolidation candidates")?;
+        Ok(candidates)
+    }
+
+    /// Record a consolidated memory.ingest entry in `namespace` and mark `sources` as
+    /// superseded by it, in one transaction. The entry expires when the last source
+    /// would have (never, if any source never expires) and is pinned if any source is.
+    /// None if a source was forgotten, expired or consolidated in the meantime.
+    pub fn consolidate_memories(&self, namespace: &str, sources: &[i64], payload: &str) -> Result<Option<Event>> {
+        Self::validate_payload("memory.ingest", payload)?;
+
+        let tx = self.conn.unchecked_transaction()
+            .context("failed to begin consolidation transaction")?;
+        let placeholders: Vec<String> = (0..sources.len()).map(|i| format!("?{}", i + 2)).collect();
+        let placeholders = placeholders.join(", ");
+        let mut source_params: Vec<&dyn rusqlite::types::ToSql> = vec![&namespace];
+        source_params.extend(sources.iter().map(|id| id as &dyn rusqlite::types::ToSql));
+
+        let (live, expires_at, pinned): (i64, Option<String>, bool) = tx
+            .query_row(
+                &format!(
+                    "SELECT COUNT(*),
+                            CASE WHEN COUNT(expires_at) = COUNT(*) THEN MAX(expires_at) END,
+                            COALESCE(MAX(pinned), 0)
+                     FROM memories
+                     WHERE namespace = ?1 AND superseded_by IS NULL AND event_id IN ({placeholders})
+                       AND (pinned = 1 OR expires_at IS NULL OR expires_at > strftime('%Y-%m-%dT%H:%M:%fZ','now'))"
+                ),
+                source_params.as_slice(),
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .context("failed to check consolidation sources")?;
+        if live != sources.len() as i64 {
+            return Ok(None);
+        }
+
+        let event = Self::insert_event(&tx, "memory.ingest", "agentd", None, payload)?;
+        tx.execute(
+            "INSERT OR REPLACE INTO memories (event_id, namespace, created_at, expires_at, pinned) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![event.id, namespace, event.ts, expires_at, pinned],
+        )
+        .context("failed to register consolidated memory")?;
+        let mut update_params: Vec<&dyn rusqlite::types::ToSql> = vec![&event.id];
+        update_params.extend(sources.iter().map(|id| id as &dyn rusqlite::types::ToSql));
+        tx.execute(
+            &format!("UPDATE memories SET superseded_by = ?1 WHERE event_id IN ({placeholders})"),
+            update_params.as_slice(),
+        )
+        .context("failed to supersede memories")?;
+        tx.commit().context("failed to commit consolidation")?;
+
+        self.after_commit(std::slice::from_ref(&event));
+        Ok(Some(event))
+    }
+
+    fn row_to_memory(row: &rusqlite::Row<'_>) -> rusqlite::Result<MemoryRecord> {
+        Ok(MemoryRecord {
+            event_id: row.get(0)?,
+            namespace: row.get(1)?,
+            created_at: row.get(2)?,
+            expires_at: row.get(3)?,
+            pinned: row.get(4)?,
+            superseded_by: row.get(5)?,
+        })
+    }
+
+    /// A memory row (columns 0-5) joined with its event (columns 6-14).
+    fn row_to_memory_event(row: &rusqlite::Row<'_>) -> rusqlite::Result<(MemoryRecord, Event)> {
+        let event = Event {
+            id: row.get(6)?,
+            ts: row.get(7)?,
+            event_type: row.get(8)?,
+            actor: row.get(9)?,
+            payload: row.get(10)?,
+            prev_hash: row.get(11)?,
+            hash: row.get(12)?,
+            verified_actor: row.get(13)?,
+            payload_digest: row.get(14)?,
+        };
+        Ok((Self::row_to_memory(row)?, event))
     }
 }
 
+/// Proof that an event is included in a Merkle checkpoint.
+/// Self-contained: an auditor can recompute the event hash from `event`
+/// and fold `path` to reach `checkpoint.root` without the rest of the ledger.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InclusionProof {
+    pub event: Event,
+    pub leaf_index: usize,
+    pub checkpoint: MerkleCheckpoint,
+    pub path: Vec<merkle::ProofStep>,
+}
+
+/// "Verified up to" point for incremental verification, signed by the ledger key when available.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyWatermark {
+    pub event_id: i64,
+    pub hash: String,
+    pub verified_at: String,
+    pub public_key: Option<String>,
+    pub signature: Option<String>,
+}
+
+/// Outcome of `Ledger::redact`.
+#[derive(Debug)]
+pub enum Redaction {
+    /// The payload was replaced; `redaction` is the new `ledger.redact` event.
+    Redacted { target: Box<Event>, redaction: Box<Event> },
+    NotFound,
+    /// Written before payload digests (schema v5); its hash covers the raw payload.
+    Legacy,
+    AlreadyRedacted,
+}
+
+/// Columns read by `row_to_checkpoint`, in order.
+const MERKLE_CHECKPOINT_COLUMNS: &str = "id, first_event_id, last_event_id, leaf_count, root, created_at, public_key, signature";
+
+/// Columns read by `row_to_incident`, in order; the last is whole seconds from
+/// creation to resolution (NULL while open).
+const INCIDENT_COLUMNS: &str = "id, name, status, created_at, resolved_at, severity, affected_services, \
+     assignee, resolution, reopen_count, \
+     CAST(ROUND((julianday(resolved_at) - julianday(created_at)) * 86400) AS INTEGER), correlation_key";
+
 /// Row type for incidents from the database.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IncidentRow {
@@ -585,9 +2586,62 @@ pub struct IncidentRow {
     pub status: String,
     pub created_at: String,
     pub resolved_at: Option<String>,
+    pub severity: String,
+    pub affected_services: Vec<String>,
+    pub assignee: Option<String>,
+    /// Summary given when the incident was (last) resolved.
+    pub resolution: Option<String>,
+    pub reopen_count: i64,
+    pub time_to_resolve_secs: Option<i64>,
+    /// Key of the correlation engine's incidents (the affected service).
+    pub correlation_key: Option<String>,
     pub steps: Vec<IncidentStepRow>,
 }
 
+/// Triage fields set when an incident is created.
+#[derive(Debug, Clone)]
+pub struct IncidentFields {
+    pub severity: String,
+    pub affected_services: Vec<String>,
+    pub assignee: Option<String>,
+    /// Set when the correlation engine opened the incident; later signals with the
+    /// same key attach to it.
+    pub correlation_key: Option<String>,
+}
+
+impl Default for IncidentFields {
+    fn default() -> Self {
+        Self { severity: "medium".to_string(), affected_services: Vec::new(), assignee: None, correlation_key: None }
+    }
+}
+
+/// Triage fields to change; None leaves a field as it is.
+#[derive(Debug, Clone, Default)]
+pub struct IncidentUpdate {
+    pub severity: Option<String>,
+    pub affected_services: Option<Vec<String>>,
+    /// Empty string clears the assignee.
+    pub assignee: Option<String>,
+}
+
+/// Which incidents `list_incidents` returns.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct IncidentFilter {
+    pub status: Option<String>,
+    pub severity: Option<String>,
+    pub assignee: Option<String>,
+}
+
+/// Outcome of an incident update or status change.
+#[derive(Debug)]
+pub enum IncidentTransition {
+    /// Applied and recorded by this event.
+    Applied(Event),
+    NotFound,
+    /// Nothing to do: already in that status, or no field changed.
+    Unchanged,
+}
+
 /// Row type for incident steps.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IncidentStepRow {
@@ -598,9 +2652,49 @@ pub struct IncidentStepRow {
     pub timestamp: String,
 }
 
+/// How a new memory is filed in the memory store.
+#[derive(Debug, Clone)]
+pub struct MemoryOptions {
+    pub namespace: String,
+    /// Expire this many seconds after creation (None = keep until forgotten).
+    pub ttl_secs: Option<i64>,
+    /// Pinned memories never expire and survive a namespace-wide forget.
+    pub pinned: bool,
+}
+
+/// A memory store entry, linked to the ledger event that holds its content.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryRecord {
+    pub event_id: i64,
+    pub namespace: String,
+    pub created_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<String>,
+    pub pinned: bool,
+    /// The consolidated memory that replaced this one; recall down-weights it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub superseded_by: Option<i64>,
+}
+
+/// What `forget_memories` removes.
+#[derive(Debug, Clone)]
+pub enum ForgetTarget {
+    Event(i64),
+    /// Every unpinned memory in the namespace.
+    Namespace(String),
+}
+
+/// Timestamp in the ledger's own `ts` format, so text comparison orders correctly.
+pub fn format_ts(ts: chrono::DateTime<chrono::Utc>) -> String {
+    ts.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use osmoda_ledger::chain::{EventRange, VerifyCheck};
+    use osmoda_ledger::query::GroupCount;
+    use sha2::{Digest, Sha256};
 
     #[test]
     fn test_incident_create_and_get() {
@@ -608,7 +2702,7 @@ mod tests {
 
         // Create an incident
         ledger
-            .create_incident("inc-001", "Database Connection Failed")
+            .create_incident("inc-001", "Database Connection Failed", &IncidentFields::default(), None)
             .expect("failed to create incident");
 
         // Get the incident back
@@ -631,18 +2725,27 @@ mod tests {
 
         // Create an incident
         ledger
-            .create_incident("inc-002", "Server CPU High")
+            .create_incident("inc-002", "Server CPU High", &IncidentFields::default(), None)
             .expect("failed to create incident");
 
         // Add step 1
+        let step = NewIncidentStep { action: "Investigate CPU usage", result: "CPU at 95%", receipt_id: None };
         ledger
-            .add_incident_step("inc-002", 1, "Investigate CPU usage", "CPU at 95%", None)
+            .add_incident_step("inc-002", &step, None)
             .expect("failed to add step 1");
 
         // Add step 2
-        ledger
-            .add_incident_step("inc-002", 2, "Restart service", "Service restarted successfully", Some("receipt-123"))
-            .expect("failed to add step 2");
+        let step = NewIncidentStep {
+            action: "Restart service",
+            result: "Service restarted successfully",
+            receipt_id: Some("receipt-123"),
+        };
+        let event = ledger
+            .add_incident_step("inc-002", &step, None)
+            .expect("failed to add step 2")
+            .expect("incident exists");
+        assert_eq!(event.event_type, "incident.step");
+        assert!(ledger.add_incident_step("inc-404", &step, None).unwrap().is_none());
 
         // Get the incident and verify steps
         let incident = ledger
@@ -670,22 +2773,22 @@ mod tests {
 
         // Create incident 1
         ledger
-            .create_incident("inc-003", "Memory Leak Detected")
+            .create_incident("inc-003", "Memory Leak Detected", &IncidentFields::default(), None)
             .expect("failed to create incident 1");
 
         // Create incident 2
         ledger
-            .create_incident("inc-004", "Disk Space Low")
+            .create_incident("inc-004", "Disk Space Low", &IncidentFields::default(), None)
             .expect("failed to create incident 2");
 
         // Resolve incident 2
         ledger
-            .resolve_incident("inc-004")
+            .resolve_incident("inc-004", "cleared old journals", None)
             .expect("failed to resolve incident");
 
         // List open incidents
         let open_incidents = ledger
-            .list_incidents(Some("open"))
+            .list_incidents(&IncidentFilter { status: Some("open".into()), ..Default::default() })
             .expect("failed to list open incidents");
 
         assert_eq!(open_incidents.len(), 1);
@@ -694,14 +2797,14 @@ mod tests {
 
         // List all incidents
         let all_incidents = ledger
-            .list_incidents(None)
+            .list_incidents(&IncidentFilter::default())
             .expect("failed to list all incidents");
 
         assert_eq!(all_incidents.len(), 2);
 
         // List resolved incidents
         let resolved_incidents = ledger
-            .list_incidents(Some("resolved"))
+            .list_incidents(&IncidentFilter { status: Some("resolved".into()), ..Default::default() })
             .expect("failed to list resolved incidents");
 
         assert_eq!(resolved_incidents.len(), 1);
@@ -710,14 +2813,64 @@ mod tests {
         assert!(resolved_incidents[0].resolved_at.is_some());
     }
 
+    #[test]
+    fn test_incident_lifecycle_is_chained() {
+        let ledger = Ledger::new(":memory:").unwrap();
+        let fields = IncidentFields {
+            severity: "high".into(),
+            affected_services: vec!["nginx".into(), "php-fpm".into()],
+            assignee: Some("oncall".into()),
+            ..Default::default()
+        };
+        ledger.create_incident("inc-9", "Site down", &fields, Some("uid:0")).unwrap();
+
+        let update = IncidentUpdate { severity: Some("critical".into()), assignee: Some("oncall".into()), ..Default::default() };
+        let IncidentTransition::Applied(event) = ledger.update_incident("inc-9", &update, None).unwrap() else {
+            panic!("expected an update");
+        };
+        // Only the field that changed is recorded
+        assert_eq!(event.payload, r#"{"incident_id":"inc-9","severity":"critical"}"#);
+        assert!(matches!(ledger.update_incident("inc-9", &update, None).unwrap(), IncidentTransition::Unchanged));
+        let unassign = IncidentUpdate { assignee: Some(String::new()), ..Default::default() };
+        ledger.update_incident("inc-9", &unassign, None).unwrap();
+
+        let IncidentTransition::Applied(resolved) = ledger.resolve_incident("inc-9", "restarted php-fpm", None).unwrap() else {
+            panic!("expected resolution");
+        };
+        assert_eq!(resolved.event_type, "incident.resolve");
+        assert!(matches!(ledger.resolve_incident("inc-9", "again", None).unwrap(), IncidentTransition::Unchanged));
+        let incident = ledger.get_incident("inc-9").unwrap().unwrap();
+        assert_eq!((incident.severity.as_str(), incident.assignee.as_deref()), ("critical", None));
+        assert_eq!(incident.resolution.as_deref(), Some("restarted php-fpm"));
+        assert!(incident.time_to_resolve_secs.is_some_and(|t| t >= 0));
+
+        ledger.reopen_incident("inc-9", "502s are back", None).unwrap();
+        let incident = ledger.get_incident("inc-9").unwrap().unwrap();
+        assert_eq!((incident.status.as_str(), incident.reopen_count), ("open", 1));
+        assert!(incident.resolved_at.is_none() && incident.time_to_resolve_secs.is_none());
+        assert!(matches!(ledger.reopen_incident("nope", "x", None).unwrap(), IncidentTransition::NotFound));
+
+        let types: Vec<String> = ledger
+            .query(&EventFilter { event_type: Some("incident.*".into()), order: Some(SortOrder::Asc), ..Default::default() })
+            .unwrap()
+            .into_iter()
+            .map(|e| e.event_type)
+            .collect();
+        assert_eq!(types, ["incident.create", "incident.update", "incident.update", "incident.resolve", "incident.reopen"]);
+        assert!(ledger.verify_report(true).unwrap().ok);
+
+        let high = IncidentFilter { severity: Some("critical".into()), ..Default::default() };
+        assert_eq!(ledger.list_incidents(&high).unwrap().len(), 1);
+    }
+
     #[test]
     fn test_hash_delimiter_prevents_collision() {
         // Without delimiters, these two events could produce the same hash:
         // Event A: id=1, ts="23", type="abc" → "123abc..."
         // Event B: id=12, ts="3", type="abc" → "123abc..."
         // With pipe delimiters: "1|23|abc|..." vs "12|3|abc|..." → different hashes
-        let hash_a = Ledger::compute_hash(1, "23", "abc", "actor", "payload", "prev");
-        let hash_b = Ledger::compute_hash(12, "3", "abc", "actor", "payload", "prev");
+        let hash_a = chain::compute_hash(1, "23", "abc", "actor", "payload", "prev", None);
+        let hash_b = chain::compute_hash(12, "3", "abc", "actor", "payload", "prev", None);
         assert_ne!(hash_a, hash_b, "pipe delimiters should prevent field-boundary collisions");
     }
 
@@ -769,7 +2922,7 @@ mod tests {
         ledger.append("memory.store", "agentd", r#"{"summary":"postgres backup completed","detail":"daily backup finished"}"#).unwrap();
         ledger.append("memory.store", "agentd", r#"{"summary":"user login from SSH","detail":"admin connected via SSH"}"#).unwrap();
 
-        let results = ledger.fts_search("nginx memory", 10).unwrap();
+        let results = ledger.fts_search("nginx memory", None, 10).unwrap();
         assert!(!results.is_empty(), "should find nginx-related events");
         assert!(results[0].0.payload.contains("nginx"), "most relevant result should mention nginx");
     }
@@ -779,7 +2932,7 @@ mod tests {
         let ledger = Ledger::new(":memory:").expect("failed to create in-memory ledger");
         ledger.append("test", "actor", "payload").unwrap();
 
-        let results = ledger.fts_search("", 10).unwrap();
+        let results = ledger.fts_search("", None, 10).unwrap();
         assert!(results.is_empty());
     }
 
@@ -788,7 +2941,7 @@ mod tests {
         let ledger = Ledger::new(":memory:").expect("failed to create in-memory ledger");
         ledger.append("test", "actor", r#"{"summary":"hello world"}"#).unwrap();
 
-        let results = ledger.fts_search("zzzznonexistent", 10).unwrap();
+        let results = ledger.fts_search("zzzznonexistent", None, 10).unwrap();
         assert!(results.is_empty());
     }
 
@@ -806,8 +2959,842 @@ mod tests {
         ledger.append("test", "actor", r#"{"summary":"the service is running normally"}"#).unwrap();
 
         // "run" should match "running" via Porter stemming
-        let results = ledger.fts_search("run", 10).unwrap();
+        let results = ledger.fts_search("run", None, 10).unwrap();
         assert!(!results.is_empty(), "Porter stemming should match 'run' to 'running'");
     }
-}
 
+    #[test]
+    fn test_merkle_checkpoint_and_inclusion_proof() {
+        let ledger = Ledger::new(":memory:").expect("failed to create in-memory ledger");
+        for i in 0..5 {
+            ledger.append("test.event", "tester", &format!("payload{i}")).unwrap();
+        }
+
+        // Not yet covered by any checkpoint
+        assert!(ledger.inclusion_proof(3).unwrap().is_none());
+
+        let checkpoint = ledger.checkpoint_pending().unwrap().expect("should seal pending events");
+        assert_eq!(checkpoint.first_event_id, 1);
+        assert_eq!(checkpoint.last_event_id, 5);
+        assert_eq!(checkpoint.leaf_count, 5);
+
+        // Nothing left to seal
+        assert!(ledger.checkpoint_pending().unwrap().is_none());
+
+        let proof = ledger.inclusion_proof(3).unwrap().expect("event 3 should be provable");
+        assert_eq!(proof.leaf_index, 2);
+        assert!(merkle::verify_proof(&proof.event.hash, &proof.path, &checkpoint.root));
+        // No signer, so nothing vouches for the root offline
+        assert!(checkpoint.signature_problem(&"00".repeat(32)).is_some());
+    }
+
+    #[test]
+    fn test_merkle_roots_signed_by_ledger_key() {
+        let mut ledger = Ledger::new(":memory:").expect("failed to create in-memory ledger");
+        let signer = LedgerSigner::new(ed25519_dalek::SigningKey::from_bytes(&[5u8; 32]));
+        let public_key = signer.public_key_hex();
+        ledger.set_signer(signer, 1000);
+        for i in 0..3 {
+            ledger.append("test.event", "tester", &format!("payload{i}")).unwrap();
+        }
+        ledger.checkpoint_pending().unwrap().unwrap();
+
+        let proof = ledger.inclusion_proof(2).unwrap().unwrap();
+        assert_eq!(proof.checkpoint.signature_problem(&public_key), None);
+        let other = LedgerSigner::new(ed25519_dalek::SigningKey::from_bytes(&[6u8; 32]));
+        assert!(proof.checkpoint.signature_problem(&other.public_key_hex()).is_some());
+
+        let mut forged = proof.checkpoint.clone();
+        forged.root = merkle::root(&["ab".repeat(32)]);
+        assert!(forged.signature_problem(&public_key).unwrap().contains("bad signature"));
+    }
+
+    #[test]
+    fn test_merkle_checkpoint_sealed_automatically() {
+        let ledger = Ledger::new(":memory:").expect("failed to create in-memory ledger");
+        for _ in 0..MERKLE_CHECKPOINT_INTERVAL + 3 {
+            ledger.append("test.event", "tester", "payload").unwrap();
+        }
+
+        let checkpoints = ledger.merkle_checkpoints(10).unwrap();
+        assert_eq!(checkpoints.len(), 1);
+        assert_eq!(checkpoints[0].last_event_id, MERKLE_CHECKPOINT_INTERVAL);
+        assert!(ledger.inclusion_proof(MERKLE_CHECKPOINT_INTERVAL + 1).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_signed_checkpoints_detect_rewrite() {
+        let mut ledger = Ledger::new(":memory:").expect("failed to create in-memory ledger");
+        ledger.set_signer(LedgerSigner::new(ed25519_dalek::SigningKey::from_bytes(&[9u8; 32])), 2);
+
+        for i in 0..5 {
+            ledger.append("test.event", "tester", &format!("payload{i}")).unwrap();
+        }
+
+        // Signed at event 2 and 4
+        let checkpoints = ledger.signed_checkpoints(10).unwrap();
+        assert_eq!(checkpoints.iter().map(|c| c.event_id).collect::<Vec<_>>(), vec![4, 2]);
+        let report = ledger.verify_chain(true).unwrap();
+        assert!(report.ok);
+        assert_eq!(report.signed_checkpoints_checked, Some(2));
+
+        // Full rewrite: chain stays internally consistent but signed head no longer matches
+        ledger
+            .conn
+            .execute(
+                "UPDATE events SET payload = 'forged', payload_digest = ?1 WHERE id = 1",
+                params![chain::payload_digest("forged")],
+            )
+            .unwrap();
+        ledger.rehash_chain().unwrap();
+        let report = ledger.verify_chain(true).unwrap();
+        assert!(!report.ok);
+        // The rewritten chain is self-consistent; only the signed heads give it away,
+        // and with both rejected nothing vouches for the head any more
+        let flagged: Vec<_> = report.issues.iter().map(|i| (i.event_id, i.check)).collect();
+        assert_eq!(
+            flagged,
+            vec![
+                (1, VerifyCheck::CheckpointMissing),
+                (2, VerifyCheck::CheckpointMismatch),
+                (4, VerifyCheck::CheckpointMismatch),
+            ]
+        );
+        assert!(report.issues[1].detail.contains("rewrite detected"));
+    }
+
+    #[test]
+    fn test_deleted_signed_checkpoints_detected() {
+        let mut ledger = Ledger::new(":memory:").expect("failed to create in-memory ledger");
+        ledger.set_signer(LedgerSigner::new(ed25519_dalek::SigningKey::from_bytes(&[9u8; 32])), 2);
+        for i in 0..5 {
+            ledger.append("test.event", "tester", &format!("payload{i}")).unwrap();
+        }
+
+        // Dropping only the newest checkpoint leaves the head too far past the one before it
+        ledger.conn.execute("DELETE FROM checkpoints WHERE event_id = 4", []).unwrap();
+        let report = ledger.verify_chain(true).unwrap();
+        assert!(!report.ok);
+        assert_eq!(report.issues[0].check, VerifyCheck::CheckpointMissing);
+        assert_eq!(report.untrusted, Some(EventRange { first_event_id: 3, last_event_id: 5 }));
+
+        ledger.conn.execute("DELETE FROM checkpoints", []).unwrap();
+        let report = ledger.verify_chain(true).unwrap();
+        assert_eq!(report.signed_checkpoints_checked, Some(0));
+        assert_eq!(report.issues[0].check, VerifyCheck::CheckpointMissing);
+        assert_eq!(report.issues[0].event_id, 1);
+    }
+
+    #[test]
+    fn test_signed_checkpoints_reject_untrusted_key() {
+        let mut ledger = Ledger::new(":memory:").expect("failed to create in-memory ledger");
+        ledger.set_signer(LedgerSigner::new(ed25519_dalek::SigningKey::from_bytes(&[1u8; 32])), 1);
+        ledger.append("test.event", "tester", "payload").unwrap();
+
+        ledger.set_signer(LedgerSigner::new(ed25519_dalek::SigningKey::from_bytes(&[2u8; 32])), 1000);
+        let report = ledger.verify_chain(true).unwrap();
+        assert!(!report.ok);
+        assert_eq!(report.issues[0].check, VerifyCheck::CheckpointMismatch);
+        assert!(report.issues[0].detail.contains("untrusted key"));
+    }
+
+    #[test]
+    fn test_sign_checkpoint_without_signer_is_noop() {
+        let ledger = Ledger::new(":memory:").expect("failed to create in-memory ledger");
+        ledger.append("test.event", "tester", "payload").unwrap();
+        assert!(ledger.sign_checkpoint().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_export_jsonl() {
+        let ledger = Ledger::new(":memory:").expect("failed to create in-memory ledger");
+        ledger.create_incident("inc-1", "Disk Full", &IncidentFields::default(), None).unwrap();
+        ledger.add_incident_step("inc-1", &NewIncidentStep { action: "check", result: "full", receipt_id: None }, None).unwrap();
+        ledger.append("test.event", "tester", "payload1").unwrap();
+        let last = ledger.append("test.event", "tester", "payload2").unwrap();
+
+        let mut out = Vec::new();
+        let summary = ledger.export_jsonl(&mut out).unwrap();
+        assert_eq!(summary.event_count, 4);
+        assert_eq!(summary.head_hash, last.hash);
+
+        let lines: Vec<serde_json::Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        let kinds: Vec<&str> = lines.iter().map(|l| l["kind"].as_str().unwrap()).collect();
+        assert_eq!(kinds, vec!["header", "event", "event", "event", "event", "incident", "incident_step", "footer"]);
+        assert_eq!(lines[2]["prev_hash"], lines[1]["hash"]);
+        assert_eq!(lines[7]["head_hash"], serde_json::json!(last.hash));
+    }
+
+    #[test]
+    fn test_archive_keeps_chain_provable() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut ledger = Ledger::new(":memory:").expect("failed to create in-memory ledger");
+        ledger.set_archive_dir(dir.path().to_path_buf());
+        for i in 0..10 {
+            ledger.append("test.event", "tester", &format!("archivable {i}")).unwrap();
+        }
+        let policy = RetentionPolicy { keep_events: 3, keep_days: 0 };
+
+        // Nothing is archived before a Merkle checkpoint covers it
+        assert!(ledger.archive_expired(&policy).unwrap().is_none());
+        ledger.checkpoint_pending().unwrap().unwrap();
+
+        let segment = ledger.archive_expired(&policy).unwrap().expect("should archive");
+        assert_eq!((segment.first_event_id, segment.last_event_id), (1, 7));
+        assert_eq!(segment.first_prev_hash, GENESIS_PREV_HASH);
+        assert!(ledger.archive_expired(&policy).unwrap().is_none());
+        assert_eq!(ledger.event_count().unwrap(), 3);
+
+        // Chain still verifies through the archive and new events link onto it
+        assert!(ledger.verify().unwrap());
+        let next = ledger.append("test.event", "tester", "appended later").unwrap();
+        assert_eq!(ledger.get_event(10).unwrap().unwrap().hash, next.prev_hash);
+        assert!(ledger.verify().unwrap());
+
+        // Archived events remain retrievable and provable, but leave the FTS index
+        assert_eq!(ledger.get_event(2).unwrap().unwrap().payload, "archivable 1");
+        let proof = ledger.inclusion_proof(2).unwrap().expect("archived event should be provable");
+        assert!(merkle::verify_proof(&proof.event.hash, &proof.path, &proof.checkpoint.root));
+        let hits = ledger.fts_search("archivable", None, 20).unwrap();
+        assert_eq!(hits.len(), 3);
+        assert!(hits.iter().all(|(e, _)| e.id > 7));
+
+        // Exports still start at genesis
+        let mut out = Vec::new();
+        assert_eq!(ledger.export_jsonl(&mut out).unwrap().event_count, 11);
+    }
+
+    #[test]
+    fn test_archive_tampering_detected() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut ledger = Ledger::new(":memory:").expect("failed to create in-memory ledger");
+        ledger.set_archive_dir(dir.path().to_path_buf());
+        for _ in 0..6 {
+            ledger.append("test.event", "tester", "payload").unwrap();
+        }
+        ledger.checkpoint_pending().unwrap();
+        let segment = ledger
+            .archive_expired(&RetentionPolicy { keep_events: 2, keep_days: 0 })
+            .unwrap()
+            .unwrap();
+
+        let path = dir.path().join(&segment.file);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600)).unwrap();
+        }
+        std::fs::write(&path, b"not a segment").unwrap();
+        assert!(!ledger.verify().unwrap());
+    }
+
+    #[test]
+    fn test_incident_queries_read_archive() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut ledger = Ledger::new(":memory:").expect("failed to create in-memory ledger");
+        ledger.set_archive_dir(dir.path().to_path_buf());
+        let fields = IncidentFields { severity: "high".into(), ..Default::default() };
+        ledger.create_incident("inc-1", "disk full", &fields, None).unwrap();
+        ledger.append("watch.alert", "osmoda-watch", "disk at 99%").unwrap();
+        for _ in 0..4 {
+            ledger.append("test.event", "tester", "payload").unwrap();
+        }
+        ledger.checkpoint_pending().unwrap();
+        ledger.archive_expired(&RetentionPolicy { keep_events: 2, keep_days: 0 }).unwrap().unwrap();
+        ledger.append("watch.alert", "osmoda-watch", "still full").unwrap();
+
+        let own = ledger.incident_events("inc-1").unwrap();
+        assert_eq!(own.iter().map(|e| e.id).collect::<Vec<_>>(), vec![1]);
+
+        let actors = vec!["osmoda-watch".to_string()];
+        let created_at = ledger.get_incident("inc-1").unwrap().unwrap().created_at;
+        let window = ledger.events_by_actors(&actors, &created_at, None, 10).unwrap();
+        assert_eq!(window.iter().map(|e| e.id).collect::<Vec<_>>(), vec![2, 7]);
+        let window = ledger.events_by_actors(&actors, &created_at, None, 1).unwrap();
+        assert_eq!(window.iter().map(|e| e.id).collect::<Vec<_>>(), vec![2]);
+    }
+
+    #[test]
+    fn test_reconcile_archive_after_interrupted_pass() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut ledger = Ledger::new(":memory:").expect("failed to create in-memory ledger");
+        ledger.set_archive_dir(dir.path().to_path_buf());
+        for _ in 0..6 {
+            ledger.append("test.event", "tester", "payload").unwrap();
+        }
+        ledger.checkpoint_pending().unwrap();
+        let segment = ledger
+            .archive_expired(&RetentionPolicy { keep_events: 2, keep_days: 0 })
+            .unwrap()
+            .unwrap();
+
+        // As if agentd died after the commit but before the rename, with a stale staged
+        // file from a pass whose commit failed
+        let path = dir.path().join(&segment.file);
+        std::fs::rename(&path, dir.path().join(format!("{}.tmp", segment.file))).unwrap();
+        std::fs::write(dir.path().join("events-000000000005-000000000006.jsonl.gz.tmp"), b"uncommitted").unwrap();
+        assert!(!ledger.verify_report(true).unwrap().ok);
+
+        ledger.reconcile_archive().unwrap();
+        assert!(ledger.verify_report(true).unwrap().ok);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_archive_respects_age_floor() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut ledger = Ledger::new(":memory:").expect("failed to create in-memory ledger");
+        ledger.set_archive_dir(dir.path().to_path_buf());
+        for _ in 0..6 {
+            ledger.append("test.event", "tester", "payload").unwrap();
+        }
+        ledger.checkpoint_pending().unwrap();
+
+        // Every event is younger than a day, so none may leave the live table
+        let policy = RetentionPolicy { keep_events: 1, keep_days: 1 };
+        assert!(ledger.archive_expired(&policy).unwrap().is_none());
+        assert_eq!(ledger.event_count().unwrap(), 6);
+    }
+
+    #[test]
+    fn test_subscribe_and_events_after() {
+        let ledger = Ledger::new(":memory:").expect("failed to create in-memory ledger");
+        ledger.append("test.event", "tester", "before").unwrap();
+
+        let mut rx = ledger.event_sender().subscribe();
+        let appended = ledger.append("test.event", "tester", "after").unwrap();
+        let received = rx.try_recv().expect("subscriber should see the append");
+        assert_eq!(received.id, appended.id);
+        assert_eq!(received.hash, appended.hash);
+
+        for i in 0..3 {
+            ledger.append("test.event", "tester", &format!("more{i}")).unwrap();
+        }
+        assert_eq!(ledger.head_id().unwrap(), 5);
+        let page = ledger.events_after(1, 2).unwrap();
+        assert_eq!(page.iter().map(|e| e.id).collect::<Vec<_>>(), vec![2, 3]);
+        assert!(ledger.events_after(5, 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_events_after_reads_archive() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut ledger = Ledger::new(":memory:").expect("failed to create in-memory ledger");
+        ledger.set_archive_dir(dir.path().to_path_buf());
+        for _ in 0..6 {
+            ledger.append("test.event", "tester", "payload").unwrap();
+        }
+        ledger.checkpoint_pending().unwrap();
+        ledger
+            .archive_expired(&RetentionPolicy { keep_events: 2, keep_days: 0 })
+            .unwrap()
+            .unwrap();
+
+        let ids: Vec<i64> = ledger.events_after(2, 10).unwrap().iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![3, 4, 5, 6]);
+        assert_eq!(ledger.head_id().unwrap(), 6);
+    }
+
+    #[test]
+    fn test_query_prefix_and_cursors() {
+        let ledger = Ledger::new(":memory:").expect("failed to create in-memory ledger");
+        ledger.append("switch.begin", "agentd", "{}").unwrap();
+        ledger.append("memory.ingest", "agentd", r#"{"content":"x"}"#).unwrap();
+        ledger.append("switch.commit", "agentd", "{}").unwrap();
+        ledger.append("switchboard", "agentd", "{}").unwrap();
+        ledger.append("switch.rollback", "agentd", "{}").unwrap();
+
+        let ids = |f: EventFilter| -> Vec<i64> { ledger.query(&f).unwrap().iter().map(|e| e.id).collect() };
+
+        assert_eq!(ids(EventFilter { event_type: Some("switch.*".into()), ..Default::default() }), vec![5, 3, 1]);
+        assert_eq!(ids(EventFilter { event_type: Some("switch.commit".into()), ..Default::default() }), vec![3]);
+
+        // Ascending pages with after_id
+        let page1 = ids(EventFilter { limit: Some(2), order: Some(SortOrder::Asc), ..Default::default() });
+        assert_eq!(page1, vec![1, 2]);
+        let page2 = ids(EventFilter { limit: Some(2), order: Some(SortOrder::Asc), after_id: Some(2), ..Default::default() });
+        assert_eq!(page2, vec![3, 4]);
+
+        // Descending pages with before_id
+        let page = ids(EventFilter { limit: Some(2), before_id: Some(4), ..Default::default() });
+        assert_eq!(page, vec![3, 2]);
+    }
+
+    #[test]
+    fn test_query_time_range() {
+        let ledger = Ledger::new(":memory:").expect("failed to create in-memory ledger");
+        ledger.append("test.event", "tester", "now").unwrap();
+
+        let future = EventFilter { since: Some("2999-01-01".into()), ..Default::default() };
+        assert!(ledger.query(&future).unwrap().is_empty());
+
+        let window = EventFilter {
+            since: Some("2000-01-01T00:00:00Z".into()),
+            until: Some("2999-01-01T00:00:00+02:00".into()),
+            ..Default::default()
+        };
+        assert_eq!(ledger.query(&window).unwrap().len(), 1);
+
+        assert!(ledger.query(&EventFilter { since: Some("yesterday".into()), ..Default::default() }).is_err());
+    }
+
+    #[test]
+    fn test_normalize_timestamp() {
+        assert_eq!(normalize_timestamp("2026-03-01T02:00:00Z").unwrap(), "2026-03-01T02:00:00.000Z");
+        assert_eq!(normalize_timestamp("2026-03-01T04:00:00+02:00").unwrap(), "2026-03-01T02:00:00.000Z");
+        assert_eq!(normalize_timestamp("2026-03-01T02:00:00").unwrap(), "2026-03-01T02:00:00.000Z");
+        assert_eq!(normalize_timestamp("2026-03-01").unwrap(), "2026-03-01T00:00:00.000Z");
+    }
+
+    #[test]
+    fn test_append_validates_registered_schemas() {
+        let ledger = Ledger::new(":memory:").expect("failed to create in-memory ledger");
+
+        let err = ledger.append("backup.create", "agentd", r#"{"backup_id":"b1"}"#).unwrap_err();
+        assert!(err.to_string().contains("does not match its schema"));
+        assert_eq!(ledger.event_count().unwrap(), 0);
+
+        ledger
+            .append("backup.create", "agentd", r#"{"backup_id":"b1","path":"/b1.tar.gz","size_bytes":1}"#)
+            .unwrap();
+        ledger.append("custom.thing", "plugin", "free-form").unwrap();
+
+        let unregistered = ledger.unregistered_types().unwrap();
+        assert_eq!(unregistered, vec![("custom.thing".to_string(), 1)]);
+    }
+
+    #[test]
+    fn test_verify_incremental_uses_watermark() {
+        let mut ledger = Ledger::new(":memory:").expect("failed to create in-memory ledger");
+        ledger.set_signer(LedgerSigner::new(ed25519_dalek::SigningKey::from_bytes(&[3u8; 32])), 1000);
+        for i in 0..5 {
+            ledger.append("test.event", "tester", &format!("payload{i}")).unwrap();
+        }
+
+        // No watermark yet: falls back to a full verify and records one
+        assert!(ledger.verify_report(false).unwrap().ok);
+        assert_eq!(ledger.watermark().unwrap().unwrap().event_id, 5);
+
+        // Tampering behind the watermark is only caught by a full verify
+        ledger.conn.execute("UPDATE events SET payload = 'forged' WHERE id = 2", []).unwrap();
+        ledger.append("test.event", "tester", "payload5").unwrap();
+        assert!(ledger.verify_report(false).unwrap().ok);
+        assert_eq!(ledger.watermark().unwrap().unwrap().event_id, 6);
+        assert!(!ledger.verify().unwrap());
+
+        // Tampering after the watermark is caught incrementally
+        ledger.conn.execute("UPDATE events SET payload = 'fixed' WHERE id = 2", []).unwrap();
+        ledger.append("test.event", "tester", "payload6").unwrap();
+        ledger.conn.execute("UPDATE events SET payload = 'forged' WHERE id = 7", []).unwrap();
+        assert!(!ledger.verify_report(false).unwrap().ok);
+    }
+
+    #[test]
+    fn test_forged_watermark_forces_full_verify() {
+        let mut ledger = Ledger::new(":memory:").expect("failed to create in-memory ledger");
+        ledger.set_signer(LedgerSigner::new(ed25519_dalek::SigningKey::from_bytes(&[3u8; 32])), 1000);
+        for i in 0..3 {
+            ledger.append("test.event", "tester", &format!("payload{i}")).unwrap();
+        }
+        ledger.conn.execute("UPDATE events SET payload = 'forged' WHERE id = 1", []).unwrap();
+
+        // An attacker moves the watermark past the tampered event without the key
+        let head = ledger.get_event(3).unwrap().unwrap();
+        ledger
+            .conn
+            .execute(
+                "INSERT OR REPLACE INTO verify_state (id, event_id, hash, verified_at) VALUES (1, 3, ?1, 'now')",
+                params![head.hash],
+            )
+            .unwrap();
+        assert!(!ledger.verify_report(false).unwrap().ok);
+    }
+
+    #[test]
+    fn test_watermark_untrusted_without_signer() {
+        let ledger = ledger_with(3);
+        assert!(ledger.verify().unwrap());
+        assert!(ledger.watermark().unwrap().is_none(), "verify() must not write the watermark");
+
+        // Nothing can vouch for a watermark without a key, so incremental verifies walk everything
+        assert!(ledger.verify_report(false).unwrap().ok);
+        ledger.conn.execute("UPDATE events SET payload = 'forged' WHERE id = 1", []).unwrap();
+        let report = ledger.verify_report(false).unwrap();
+        assert!(report.full && !report.ok);
+    }
+
+    fn ledger_with(n: usize) -> Ledger {
+        let ledger = Ledger::new(":memory:").expect("failed to create in-memory ledger");
+        for i in 0..n {
+            ledger.append("test.event", "tester", &format!("payload{i}")).unwrap();
+        }
+        ledger
+    }
+
+    #[test]
+    fn test_verify_report_localises_edits() {
+        // Payload edited in place: only that event is flagged
+        let ledger = ledger_with(5);
+        ledger.conn.execute("UPDATE events SET payload = 'forged' WHERE id = 3", []).unwrap();
+        let report = ledger.verify_report(true).unwrap();
+        assert!(!report.ok);
+        assert_eq!(report.issue_count, 1);
+        let first = report.first_broken.unwrap();
+        assert_eq!((first.event_id, first.check), (3, VerifyCheck::PayloadDigestMismatch));
+        assert_eq!(report.untrusted, Some(EventRange { first_event_id: 3, last_event_id: 5 }));
+        assert!(ledger.watermark().unwrap().is_none(), "a failed walk must not move the watermark");
+
+        // Event rewritten with a valid hash: the break shows on the next link, both distrusted
+        let ledger = ledger_with(5);
+        let e = ledger.get_event(3).unwrap().unwrap();
+        let digest = chain::payload_digest("forged");
+        let forged = chain::event_hash(&Event { payload_digest: Some(digest.clone()), ..e });
+        ledger
+            .conn
+            .execute(
+                "UPDATE events SET payload = 'forged', payload_digest = ?1, hash = ?2 WHERE id = 3",
+                params![digest, forged],
+            )
+            .unwrap();
+        let report = ledger.verify_report(true).unwrap();
+        let first = report.first_broken.unwrap();
+        assert_eq!((first.event_id, first.check), (4, VerifyCheck::PrevHashMismatch));
+        assert_eq!(report.untrusted.unwrap().first_event_id, 3);
+    }
+
+    #[test]
+    fn test_verify_report_gaps_and_timestamps() {
+        let ledger = ledger_with(5);
+        ledger.conn.execute("DELETE FROM events WHERE id = 3", []).unwrap();
+        let report = ledger.verify_report(true).unwrap();
+        let checks: Vec<_> = report.issues.iter().map(|i| (i.event_id, i.check)).collect();
+        assert_eq!(checks, vec![(4, VerifyCheck::IdGap), (4, VerifyCheck::PrevHashMismatch)]);
+        assert_eq!(report.events_checked, 4);
+
+        let ledger = ledger_with(3);
+        ledger
+            .conn
+            .execute("UPDATE events SET ts = '2999-01-01T00:00:00.000Z' WHERE id = 1", [])
+            .unwrap();
+        let report = ledger.verify_report(true).unwrap();
+        assert!(report.issues.iter().any(|i| i.event_id == 2 && i.check == VerifyCheck::NonMonotonicTimestamp));
+        assert!(report.issues.iter().any(|i| i.event_id == 1 && i.check == VerifyCheck::HashMismatch));
+    }
+
+    #[test]
+    fn test_verified_actor_is_hashed_and_filterable() {
+        let ledger = Ledger::new(":memory:").expect("failed to create in-memory ledger");
+        ledger.append("test.event", "agentd", "internal").unwrap();
+        let receipt = ledger
+            .append_as("wallet.send", "osmoda-keyd", Some("osmoda-keyd"), "{}")
+            .unwrap();
+        ledger.append_as("wallet.send", "osmoda-keyd", Some("uid:0:python3"), "{}").unwrap();
+
+        // Events without a verified actor keep the original field layout
+        let internal = ledger.get_event(1).unwrap().unwrap();
+        assert_eq!(internal.verified_actor, None);
+        assert_eq!(
+            internal.hash,
+            hex::encode(Sha256::digest(format!(
+                "1|{}|test.event|agentd|sha256:{}|{}",
+                internal.ts,
+                chain::payload_digest("internal"),
+                GENESIS_PREV_HASH
+            )))
+        );
+        assert_eq!(ledger.get_event(2).unwrap().unwrap().verified_actor.as_deref(), Some("osmoda-keyd"));
+        assert!(ledger.verify().unwrap());
+
+        let filter = EventFilter { verified_actor: Some("osmoda-keyd".to_string()), ..Default::default() };
+        let genuine = ledger.query(&filter).unwrap();
+        assert_eq!(genuine.len(), 1);
+        assert_eq!(genuine[0].id, receipt.id);
+
+        // Rewriting who submitted an event breaks its hash
+        ledger
+            .conn
+            .execute("UPDATE events SET verified_actor = 'osmoda-keyd' WHERE id = 3", [])
+            .unwrap();
+        let report = ledger.verify_report(true).unwrap();
+        assert_eq!(report.first_broken.unwrap().event_id, 3);
+    }
+
+    #[test]
+    fn test_redaction_keeps_chain_valid() {
+        let ledger = ledger_with(2);
+        let leaked = ledger.append("test.event", "tester", "curl -H 'Authorization: hunter2' ...").unwrap();
+        ledger.append("test.event", "tester", "after").unwrap();
+        let merkle = ledger.checkpoint_pending().unwrap().unwrap();
+
+        let Redaction::Redacted { target, redaction } =
+            ledger.redact(leaked.id, "token in command line", Some("a1"), "agent", None).unwrap()
+        else {
+            panic!("expected redaction");
+        };
+        assert_eq!(target.hash, leaked.hash);
+        assert!(!target.payload.contains("hunter2"));
+        assert_eq!(redaction.event_type, "ledger.redact");
+        assert_eq!(ledger.get_event(leaked.id).unwrap().unwrap().payload, target.payload);
+        assert!(ledger.fts_search("hunter2", None, 10).unwrap().is_empty());
+
+        // Hash chain, Merkle root and proofs are untouched
+        assert!(ledger.verify_report(true).unwrap().ok);
+        assert_eq!(ledger.merkle_checkpoints(1).unwrap()[0].root, merkle.root);
+        assert!(ledger.inclusion_proof(leaked.id).unwrap().is_some());
+
+        assert!(matches!(ledger.redact(leaked.id, "again", None, "agent", None).unwrap(), Redaction::AlreadyRedacted));
+        assert!(matches!(ledger.redact(999, "missing", None, "agent", None).unwrap(), Redaction::NotFound));
+
+        // Events written before payload digests cannot be redacted
+        ledger.conn.execute("UPDATE events SET payload_digest = NULL WHERE id = 1", []).unwrap();
+        assert!(matches!(ledger.redact(1, "legacy", None, "agent", None).unwrap(), Redaction::Legacy));
+    }
+
+    #[test]
+    fn test_redact_archived_event() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut ledger = Ledger::new(":memory:").expect("failed to create in-memory ledger");
+        ledger.set_archive_dir(dir.path().to_path_buf());
+        ledger.append("test.event", "tester", "before").unwrap();
+        let leaked = ledger.append("test.event", "tester", "password=hunter2").unwrap();
+        for _ in 0..4 {
+            ledger.append("test.event", "tester", "after").unwrap();
+        }
+        ledger.checkpoint_pending().unwrap();
+        let segment = ledger.archive_expired(&RetentionPolicy { keep_events: 2, keep_days: 0 }).unwrap().unwrap();
+
+        let Redaction::Redacted { target, redaction } =
+            ledger.redact(leaked.id, "password in payload", Some("a1"), "agent", None).unwrap()
+        else {
+            panic!("expected redaction");
+        };
+        assert_eq!(target.hash, leaked.hash);
+        assert_eq!(Tombstone::parse(&target.payload).unwrap().redaction_event_id, redaction.id);
+
+        // The segment on disk no longer holds the secret, and everything still verifies
+        let rewritten = ledger.segments().unwrap().remove(0);
+        assert_ne!(rewritten.sha256, segment.sha256);
+        let events = archive::read_segment(dir.path(), &rewritten.file, &rewritten.sha256).unwrap();
+        assert!(events.iter().all(|e| !e.payload.contains("hunter2")));
+        assert_eq!(ledger.get_event(leaked.id).unwrap().unwrap().payload, target.payload);
+        assert!(ledger.verify_report(true).unwrap().ok);
+        assert!(ledger.inclusion_proof(leaked.id).unwrap().is_some());
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        assert!(matches!(ledger.redact(leaked.id, "again", None, "agent", None).unwrap(), Redaction::AlreadyRedacted));
+    }
+
+    #[test]
+    fn test_forged_tombstone_detected() {
+        let ledger = ledger_with(3);
+        let e = ledger.get_event(2).unwrap().unwrap();
+        let tombstone = serde_json::to_string(&Tombstone {
+            redacted: true,
+            payload_digest: e.payload_digest.clone().unwrap(),
+            redaction_event_id: 3,
+        })
+        .unwrap();
+        ledger.conn.execute("UPDATE events SET payload = ?1 WHERE id = 2", params![tombstone]).unwrap();
+
+        // Event #3 is not a ledger.redact event for #2, so the tombstone is unaccounted for
+        let report = ledger.verify_report(true).unwrap();
+        let first = report.first_broken.unwrap();
+        assert_eq!((first.event_id, first.check), (2, VerifyCheck::PayloadDigestMismatch));
+    }
+
+    #[test]
+    fn test_stats_groups_and_buckets() {
+        let ledger = Ledger::new(":memory:").expect("failed to create in-memory ledger");
+        let rows = [
+            ("approval.denied", "agentd", "2026-03-02T10:15:00.000Z"), // Monday
+            ("approval.denied", "agentd", "2026-03-08T23:59:00.000Z"), // Sunday, same week
+            ("approval.approved", "agentd", "2026-03-09T00:00:00.000Z"), // next Monday
+            ("approval.denied", "agentd", "2026-03-10T08:00:00.000Z"),
+            ("other.event", "agentd", "2026-03-10T08:30:00.000Z"),
+        ];
+        for (i, (event_type, actor, ts)) in rows.iter().enumerate() {
+            let payload = serde_json::json!({"approval_id": format!("a{i}"), "command": "rm -rf /tmp/x"});
+            ledger.append(event_type, actor, &payload.to_string()).unwrap();
+            ledger.conn.execute("UPDATE events SET ts = ?1 WHERE id = ?2", params![ts, i as i64 + 1]).unwrap();
+        }
+
+        let all = ledger.stats(&EventFilter::default(), StatsGroup::Type, None).unwrap();
+        assert_eq!(all.total, 5);
+        assert_eq!(all.groups[0], GroupCount { key: "approval.denied".to_string(), count: 3 });
+        assert!(all.histogram.is_empty());
+
+        let filter = EventFilter { event_type: Some("approval.*".to_string()), ..Default::default() };
+        let weekly = ledger.stats(&filter, StatsGroup::Type, Some(StatsBucket::Week)).unwrap();
+        assert_eq!(weekly.total, 4);
+        let starts: Vec<_> = weekly.histogram.iter().map(|b| b.start.as_str()).collect();
+        assert_eq!(starts, vec!["2026-03-02T00:00:00Z", "2026-03-09T00:00:00Z"]);
+        assert_eq!(weekly.histogram[0].counts["approval.denied"], 2);
+        assert_eq!(weekly.histogram[1].counts["approval.denied"], 1);
+        assert_eq!(weekly.histogram[1].total, 2);
+
+        let hourly = ledger.stats(&EventFilter::default(), StatsGroup::Actor, Some(StatsBucket::Hour)).unwrap();
+        let last = hourly.histogram.last().unwrap();
+        assert_eq!((last.start.as_str(), last.total), ("2026-03-10T08:00:00Z", 2));
+        assert_eq!(hourly.groups, vec![GroupCount { key: "agentd".to_string(), count: 5 }]);
+    }
+
+    fn memory(ledger: &Ledger, content: &str, namespace: &str, ttl_secs: Option<i64>, pinned: bool) -> Event {
+        let payload = serde_json::json!({"content": content}).to_string();
+        let options = MemoryOptions { namespace: namespace.to_string(), ttl_secs, pinned };
+        ledger.remember("memory.ingest", "voice", None, &payload, &options).unwrap().0
+    }
+
+    #[test]
+    fn test_memory_expiry_spares_pinned() {
+        let ledger = Ledger::new(":memory:").unwrap();
+        let short = memory(&ledger, "temporary", "session:s1", Some(60), false);
+        let pinned = memory(&ledger, "keep me", "session:s1", Some(60), true);
+        let durable = memory(&ledger, "no ttl", "session:s1", None, false);
+        ledger.conn.execute("UPDATE memories SET expires_at = '2000-01-01T00:00:00.000Z' WHERE expires_at IS NOT NULL", []).unwrap();
+
+        let expired = ledger.expire_memories().unwrap();
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].event_type, "memory.expire");
+        let payload: serde_json::Value = serde_json::from_str(&expired[0].payload).unwrap();
+        assert_eq!(payload, serde_json::json!({"namespace": "session:s1", "event_ids": [short.id]}));
+
+        let live: Vec<i64> = ledger.list_memories("session:s1", 10).unwrap().iter().map(|(m, _)| m.event_id).collect();
+        assert_eq!(live, vec![durable.id, pinned.id]);
+        assert!(ledger.expire_memories().unwrap().is_empty());
+        // The expired memory's event is still in the ledger
+        assert!(ledger.get_event(short.id).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_memory_pin_records_verified_actor() {
+        let ledger = Ledger::new(":memory:").unwrap();
+        let m = memory(&ledger, "keep", "agent", Some(60), false);
+
+        let record = ledger.set_memory_pinned(m.id, true, "agent", Some("uid:1000")).unwrap().unwrap();
+        assert!(record.pinned);
+        let pin = ledger.get_event(m.id + 1).unwrap().unwrap();
+        assert_eq!(pin.event_type, "memory.pin");
+        assert_eq!(pin.actor, "agent");
+        assert_eq!(pin.verified_actor.as_deref(), Some("uid:1000"));
+
+        // Pinning again changes nothing and records nothing
+        ledger.set_memory_pinned(m.id, true, "agent", Some("uid:1000")).unwrap().unwrap();
+        assert!(ledger.get_event(m.id + 2).unwrap().is_none());
+        assert!(ledger.set_memory_pinned(m.id + 100, true, "agent", None).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_forget_leaves_audit_event() {
+        let ledger = Ledger::new(":memory:").unwrap();
+        let a = memory(&ledger, "alpha", "project:p", None, false);
+        let b = memory(&ledger, "beta", "project:p", None, true);
+        let other = memory(&ledger, "gamma", "agent", None, false);
+        ledger.store_vector(a.id, "m", &[1.0, 0.0]).unwrap();
+
+        let (ids, audit) = ledger
+            .forget_memories(&ForgetTarget::Namespace("project:p".into()), "project closed", "agent", Some("uid:0"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(ids, vec![a.id], "pinned memories survive a namespace forget");
+        assert_eq!(audit.event_type, "memory.forget");
+        assert_eq!(audit.verified_actor.as_deref(), Some("uid:0"));
+        assert_eq!(ledger.vector_count("m").unwrap(), 0);
+
+        let (ids, _) = ledger.forget_memories(&ForgetTarget::Event(b.id), "no longer true", "agent", None).unwrap().unwrap();
+        assert_eq!(ids, vec![b.id]);
+        assert!(ledger.forget_memories(&ForgetTarget::Event(b.id), "twice", "agent", None).unwrap().is_none());
+        assert_eq!(ledger.memory_records(&[a.id, b.id, other.id]).unwrap().len(), 1);
+        assert!(ledger.verify_report(true).unwrap().ok);
+    }
+
+    #[test]
+    fn test_migrates_v1_ledger_to_current() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ledger.db");
+        {
+            // Tables as schema v1 created them; hashes in the old undelimited format
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE events (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    ts TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+                    type TEXT NOT NULL,
+                    actor TEXT NOT NULL,
+                    payload TEXT NOT NULL,
+                    prev_hash TEXT NOT NULL,
+                    hash TEXT NOT NULL
+                );
+                CREATE TABLE incidents (
+                    id TEXT PRIMARY KEY,
+                    name TEXT NOT NULL,
+                    status TEXT NOT NULL DEFAULT 'open',
+                    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+                    resolved_at TEXT
+                );
+                CREATE TABLE merkle_checkpoints (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    first_event_id INTEGER NOT NULL,
+                    last_event_id INTEGER NOT NULL,
+                    leaf_count INTEGER NOT NULL,
+                    root TEXT NOT NULL,
+                    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now'))
+                );
+                CREATE TABLE schema_version (id INTEGER PRIMARY KEY CHECK (id = 1), version INTEGER NOT NULL);
+                INSERT INTO schema_version (id, version) VALUES (1, 1);
+                INSERT INTO events (type, actor, payload, prev_hash, hash) VALUES
+                    ('daemon.start', 'agentd', '{}', 'x', 'y'),
+                    ('memory.ingest', 'tester', '{\"content\":\"disk almost full\"}', 'y', 'z');
+                INSERT INTO incidents (id, name) VALUES ('inc-1', 'Disk Full');",
+            )
+            .unwrap();
+        }
+
+        let mut ledger = Ledger::new(path.to_str().unwrap()).unwrap();
+        let version: i64 = ledger
+            .conn
+            .query_row("SELECT version FROM schema_version WHERE id = 1", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, Ledger::CURRENT_SCHEMA_VERSION);
+
+        // v2 rehash, then the columns added since; new events chain on
+        ledger.append("test.event", "tester", "after migration").unwrap();
+        let report = ledger.verify_chain(true).unwrap();
+        assert!(report.ok, "{:?}", report.issues);
+        assert_eq!(report.events_checked, 3);
+        // v3 FTS backfill and v6 memory registration
+        assert_eq!(ledger.fts_search("disk", None, 10).unwrap().len(), 1);
+        assert_eq!(ledger.memory_records(&[2]).unwrap()[&2].namespace, DEFAULT_MEMORY_NAMESPACE);
+        // v8/v9 incident columns
+        let incident = ledger.get_incident("inc-1").unwrap().unwrap();
+        assert_eq!(incident.severity, "medium");
+        assert!(incident.correlation_key.is_none());
+        // v10 signed Merkle roots
+        ledger.set_signer(LedgerSigner::new(ed25519_dalek::SigningKey::from_bytes(&[4u8; 32])), 1000);
+        assert!(ledger.checkpoint_pending().unwrap().unwrap().signature.is_some());
+    }
+
+    #[test]
+    fn test_memory_events_registered_in_default_namespace() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ledger.db");
+        let path = path.to_str().unwrap();
+        {
+            let ledger = Ledger::new(path).unwrap();
+            ledger.append("memory.store", "agentd", r#"{"summary":"appended directly"}"#).unwrap();
+            ledger.append("test.event", "tester", "not a memory").unwrap();
+            ledger.conn.execute("DELETE FROM memories", []).unwrap();
+            ledger.conn.execute("UPDATE schema_version SET version = 5 WHERE id = 1", []).unwrap();
+        }
+
+        // Reopening migrates memories written before the memory store existed
+        let ledger = Ledger::new(path).unwrap();
+        let records = ledger.memory_records(&[1, 2]).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[&1].namespace, DEFAULT_MEMORY_NAMESPACE);
+        assert!(records[&1].expires_at.is_none());
+    }
+}
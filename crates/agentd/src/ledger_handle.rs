@@ -0,0 +1,413 @@
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use tokio::sync::{broadcast, mpsc, oneshot, Semaphore};
+
+use crate::ledger::{Event, Ledger, NewEvent, VerifyReport};
+
+/// Appends waiting in the queue are committed together, up to this many per transaction.
+pub const MAX_APPEND_BATCH: usize = 256;
+
+/// Writes queued before `append` callers start waiting for the writer to catch up.
+const WRITE_QUEUE_CAPACITY: usize = 4096;
+
+/// Default number of read-only connections serving queries alongside the writer.
+pub const DEFAULT_READERS: usize = 4;
+
+type WriteFn = Box<dyn FnOnce(&mut Ledger) + Send>;
+
+enum WriteCommand {
+    Append {
+        event: NewEvent,
+        reply: oneshot::Sender<Result<Event>>,
+    },
+    /// Any other mutation (redaction, archiving, checkpoints). Runs after the appends
+    /// queued ahead of it have been committed.
+    Run(WriteFn),
+}
+
+/// Shared access to the ledger: one writer thread owns the read-write connection and
+/// commits queued appends in batches, while reads run concurrently on a pool of
+/// read-only WAL connections. Cheap to clone.
+#[derive(Clone)]
+pub struct LedgerHandle {
+    writes: mpsc::Sender<WriteCommand>,
+    readers: Arc<ReadPool>,
+    events_tx: broadcast::Sender<Event>,
+}
+
+impl LedgerHandle {
+    /// Move `ledger` onto a dedicated writer thread and open `readers` read-only
+    /// connections next to it. The writer exits once every handle is dropped.
+    pub fn spawn(ledger: Ledger, readers: usize) -> Result<Self> {
+        let pool = (0..readers.max(1))
+            .map(|_| ledger.open_reader())
+            .collect::<Result<Vec<_>>>()?;
+        let events_tx = ledger.event_sender();
+
+        let (writes, rx) = mpsc::channel(WRITE_QUEUE_CAPACITY);
+        std::thread::Builder::new()
+            .name("ledger-writer".to_string())
+            .spawn(move || writer_loop(ledger, rx))
+            .context("failed to spawn ledger writer thread")?;
+
+        Ok(Self {
+            writes,
+            readers: Arc::new(ReadPool::new(pool)),
+            events_tx,
+        })
+    }
+
+    /// Append an event written by agentd itself or on behalf of an unverified caller.
+    pub async fn append(&self, event_type: &str, actor: &str, payload: &str) -> Result<Event> {
+        self.append_as(event_type, actor, None, payload).await
+    }
+
+    /// Queue an event for the writer and wait until its batch is committed.
+    pub async fn append_as(
+        &self,
+        event_type: &str,
+        actor: &str,
+        verified_actor: Option<&str>,
+        payload: &str,
+    ) -> Result<Event> {
+        let (reply, rx) = oneshot::channel();
+        let event = NewEvent {
+            event_type: event_type.to_string(),
+            actor: actor.to_string(),
+            verified_actor: verified_actor.map(str::to_string),
+            payload: payload.to_string(),
+        };
+        self.writes
+            .send(WriteCommand::Append { event, reply })
+            .await
+            .map_err(|_| anyhow::anyhow!("ledger writer has stopped"))?;
+        rx.await.context("ledger writer dropped the append")?
+    }
+
+    /// Run a mutation on the writer connection, in order with queued appends.
+    pub async fn write<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Ledger) -> Result<T> + Send + 'static,
+    {
+        let (reply, rx) = oneshot::channel();
+        let run: WriteFn = Box::new(move |ledger| {
+            let _ = reply.send(f(ledger));
+        });
+        self.writes
+            .send(WriteCommand::Run(run))
+            .await
+            .map_err(|_| anyhow::anyhow!("ledger writer has stopped"))?;
+        rx.await.context("ledger writer dropped the request")?
+    }
+
+    /// Run a query on a pooled read-only connection. Sees every append that has
+    /// returned before the call, never a partially committed batch.
+    pub async fn read<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Ledger) -> Result<T> + Send + 'static,
+    {
+        let permit = self
+            .readers
+            .available
+            .clone()
+            .acquire_owned()
+            .await
+            .context("ledger reader pool closed")?;
+        let pool = self.readers.clone();
+        tokio::task::spawn_blocking(move || {
+            // The guard is dropped before the permit, so a panicking `f` still returns the reader
+            let _permit = permit;
+            let reader = pool.take();
+            f(&reader)
+        })
+        .await
+        .context("ledger read task panicked")?
+    }
+
+    /// Verify the chain on a reader, then advance the watermark through the writer
+    /// if the report is clean. Same semantics as `Ledger::verify_report`.
+    pub async fn verify_report(&self, full: bool) -> Result<VerifyReport> {
+        let report = self.read(move |ledger| ledger.verify_chain(full)).await?;
+        if report.ok && report.head_event_id > report.from_event_id {
+            let clean = report.clone();
+            self.write(move |ledger| ledger.advance_watermark(&clean)).await?;
+        }
+        Ok(report)
+    }
+
+    /// Receive every event committed from now on (see `Ledger::event_sender`).
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.events_tx.subscribe()
+    }
+}
+
+/// Read-only connections handed out one per query; the semaphore guarantees one is free.
+struct ReadPool {
+    idle: Mutex<Vec<Ledger>>,
+    available: Arc<Semaphore>,
+}
+
+impl ReadPool {
+    fn new(readers: Vec<Ledger>) -> Self {
+        let available = Arc::new(Semaphore::new(readers.len()));
+        Self { idle: Mutex::new(readers), available }
+    }
+
+    fn take(&self) -> PooledReader<'_> {
+        let reader = self
+            .idle
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .pop()
+            .expect("reader permit held but no idle reader");
+        PooledReader { pool: self, reader: Some(reader) }
+    }
+}
+
+/// A reader taken from the pool; goes back on drop, including while unwinding.
+struct PooledReader<'a> {
+    pool: &'a ReadPool,
+    reader: Option<Ledger>,
+}
+
+impl std::ops::Deref for PooledReader<'_> {
+    type Target = Ledger;
+
+    fn deref(&self) -> &Ledger {
+        self.reader.as_ref().expect("pooled reader already returned")
+    }
+}
+
+impl Drop for PooledReader<'_> {
+    fn drop(&mut self) {
+        if let Some(reader) = self.reader.take() {
+            self.pool.idle.lock().unwrap_or_else(|e| e.into_inner()).push(reader);
+        }
+    }
+}
+
+/// Drain the write queue until every handle is gone. Consecutive appends are taken
+/// without waiting and committed in one transaction; any other write first flushes
+/// the appends queued ahead of it so ordering is preserved.
+fn writer_loop(mut ledger: Ledger, mut rx: mpsc::Receiver<WriteCommand>) {
+    let mut next = rx.blocking_recv();
+    while let Some(command) = next.take() {
+        match command {
+            WriteCommand::Run(run) => {
+                // A panicking write drops its reply; the writer keeps serving everyone else
+                if std::panic::catch_unwind(AssertUnwindSafe(|| run(&mut ledger))).is_err() {
+                    tracing::error!("ledger write panicked");
+                }
+            }
+            WriteCommand::Append { event, reply } => {
+                let mut events = vec![event];
+                let mut replies = vec![reply];
+                while events.len() < MAX_APPEND_BATCH {
+                    match rx.try_recv() {
+                        Ok(WriteCommand::Append { event, reply }) => {
+                            events.push(event);
+                            replies.push(reply);
+                        }
+                        Ok(other) => {
+                            next = Some(other);
+                            break;
+                        }
+                        Err(_) => break,
+                    }
+                }
+                commit_batch(&ledger, &events, replies);
+            }
+        }
+        if next.is_none() {
+            next = rx.blocking_recv();
+        }
+    }
+    tracing::debug!("ledger writer stopped");
+}
+
+fn commit_batch(ledger: &Ledger, events: &[NewEvent], replies: Vec<oneshot::Sender<Result<Event>>>) {
+    match ledger.append_batch(events) {
+        Ok(results) => {
+            for (reply, result) in replies.into_iter().zip(results) {
+                let _ = reply.send(result);
+            }
+        }
+        Err(e) => {
+            tracing::error!(error = %e, events = events.len(), "ledger append batch failed");
+            for reply in replies {
+                let _ = reply.send(Err(anyhow::anyhow!("ledger append batch failed: {e:#}")));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn spawn_handle(dir: &tempfile::TempDir) -> LedgerHandle {
+        let path = dir.path().join("ledger.db");
+        let ledger = Ledger::new(path.to_str().unwrap()).unwrap();
+        LedgerHandle::spawn(ledger, DEFAULT_READERS).unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_appends_keep_chain_valid() {
+        let dir = tempfile::tempdir().unwrap();
+        let handle = spawn_handle(&dir);
+
+        let mut tasks = Vec::new();
+        for writer in 0..8 {
+            let handle = handle.clone();
+            tasks.push(tokio::spawn(async move {
+                for i in 0..50 {
+                    let payload = format!(r#"{{"writer":{writer},"i":{i}}}"#);
+                    handle.append("test.event", "tester", &payload).await.unwrap();
+                }
+            }));
+        }
+        // Reads are served while the writer is busy
+        for _ in 0..20 {
+            handle.read(|l| l.head_id()).await.unwrap();
+        }
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        assert_eq!(handle.read(|l| l.head_id()).await.unwrap(), 400);
+        let report = handle.verify_report(true).await.unwrap();
+        assert!(report.ok);
+        assert_eq!(report.events_checked, 400);
+        // The clean report moved the watermark through the writer
+        let watermark = handle.read(|l| l.watermark()).await.unwrap().unwrap();
+        assert_eq!(watermark.event_id, 400);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_event_does_not_fail_its_batch() {
+        let dir = tempfile::tempdir().unwrap();
+        let handle = spawn_handle(&dir);
+
+        let (good, bad, after) = tokio::join!(
+            handle.append("test.event", "tester", "first"),
+            handle.append("backup.create", "tester", r#"{"backup_id":"b1"}"#),
+            handle.append("test.event", "tester", "second"),
+        );
+        assert!(bad.is_err());
+        assert_eq!(good.unwrap().id + 1, after.unwrap().id);
+        assert!(handle.verify_report(true).await.unwrap().ok);
+    }
+
+    #[tokio::test]
+    async fn test_writes_run_in_order_with_appends() {
+        let dir = tempfile::tempdir().unwrap();
+        let handle = spawn_handle(&dir);
+
+        let append = handle.append("test.event", "tester", "before");
+        let write = handle.write(|l| l.head_id());
+        let (event, head) = tokio::join!(append, write);
+        assert_eq!(head.unwrap(), event.unwrap().id);
+
+        // Reader connections refuse writes
+        let err = handle.read(|l| l.append("test.event", "tester", "x")).await;
+        assert!(err.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_panicking_read_returns_its_reader() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ledger.db");
+        let handle = LedgerHandle::spawn(Ledger::new(path.to_str().unwrap()).unwrap(), 1).unwrap();
+        handle.append("test.event", "tester", "payload").await.unwrap();
+
+        let err = handle.read(|_| -> Result<()> { panic!("query blew up") }).await;
+        assert!(err.is_err());
+        // The only reader went back to the pool
+        assert_eq!(handle.read(|l| l.head_id()).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_subscribers_see_batched_events_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let handle = spawn_handle(&dir);
+        let mut rx = handle.subscribe();
+
+        let appends: Vec<_> = (0..10)
+            .map(|i| {
+                let handle = handle.clone();
+                tokio::spawn(async move { handle.append("test.event", "tester", &i.to_string()).await })
+            })
+            .collect();
+        for append in appends {
+            append.await.unwrap().unwrap();
+        }
+
+        let mut last = 0;
+        for _ in 0..10 {
+            let event = rx.recv().await.unwrap();
+            assert!(event.id > last);
+            last = event.id;
+        }
+    }
+
+    /// Throughput of concurrent appends through the batching writer versus one
+    /// transaction per event. Run with `cargo test -p agentd --release -- --ignored --nocapture`.
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    #[ignore]
+    async fn bench_append_throughput() {
+        const WRITERS: usize = 16;
+        const PER_WRITER: usize = 500;
+        let total = WRITERS * PER_WRITER;
+
+        let dir = tempfile::tempdir().unwrap();
+        let sequential = Ledger::new(dir.path().join("sequential.db").to_str().unwrap()).unwrap();
+        let start = Instant::now();
+        for i in 0..total {
+            sequential.append("test.event", "bench", &i.to_string()).unwrap();
+        }
+        let sequential_rate = total as f64 / start.elapsed().as_secs_f64();
+
+        let handle = spawn_handle(&dir);
+        let start = Instant::now();
+        let tasks: Vec<_> = (0..WRITERS)
+            .map(|w| {
+                let handle = handle.clone();
+                tokio::spawn(async move {
+                    for i in 0..PER_WRITER {
+                        handle.append("test.event", "bench", &format!("{w}:{i}")).await.unwrap();
+                    }
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+        let batched_rate = total as f64 / start.elapsed().as_secs_f64();
+
+        let start = Instant::now();
+        let reads: Vec<_> = (0..WRITERS)
+            .map(|_| {
+                let handle = handle.clone();
+                tokio::spawn(async move {
+                    for _ in 0..PER_WRITER / 10 {
+                        handle.read(|l| l.head_id()).await.unwrap();
+                    }
+                })
+            })
+            .collect();
+        for read in reads {
+            read.await.unwrap();
+        }
+        let read_rate = (WRITERS * PER_WRITER / 10) as f64 / start.elapsed().as_secs_f64();
+
+        println!("sequential appends: {sequential_rate:>10.0} events/sec");
+        println!("batched appends:    {batched_rate:>10.0} events/sec ({WRITERS} writers)");
+        println!("pooled reads:       {read_rate:>10.0} reads/sec ({DEFAULT_READERS} readers)");
+        assert!(handle.verify_report(true).await.unwrap().ok);
+    }
+}
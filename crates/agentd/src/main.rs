@@ -1,16 +1,28 @@
 mod api;
 mod approval;
+mod consolidate;
+mod correlate;
+mod embedding;
 mod ledger;
+mod ledger_handle;
+mod notify;
+mod peer;
+mod policy;
+mod postmortem;
 mod sandbox;
+mod schema;
+mod shell;
+mod signing;
 mod state;
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use axum::extract::DefaultBodyLimit;
 use axum::routing::{get, post};
 use axum::Router;
 use clap::Parser;
+use osmoda_ledger::archive;
 use tokio::net::UnixListener;
 use tokio::signal;
 use tokio::sync::Mutex;
@@ -38,6 +50,23 @@ struct Args {
     #[arg(long, default_value = "")]
     approval_patterns: String,
 
+    /// JSON approval policy (ordered rules: allow, require approval, require N
+    /// approvers or deny). Reloaded when the file changes.
+    #[arg(long)]
+    approval_policy: Option<PathBuf>,
+
+    /// POST each new approval request to this URL through the egress proxy (repeatable).
+    #[arg(long)]
+    approval_webhook: Vec<String>,
+
+    /// Send a mesh alert about each new approval request to this peer ID (repeatable).
+    #[arg(long)]
+    approval_notify_peer: Vec<String>,
+
+    /// osmoda-mesh Unix socket, for approval alerts to mesh peers.
+    #[arg(long, default_value = "/run/osmoda/mesh.sock")]
+    mesh_socket: String,
+
     /// Enable the sandbox engine for Tier 1/Tier 2 isolation.
     #[arg(long, default_value_t = false)]
     sandbox_enabled: bool,
@@ -45,6 +74,55 @@ struct Args {
     /// Egress proxy address for sandboxed network access.
     #[arg(long, default_value = "http://127.0.0.1:8443")]
     egress_proxy: String,
+
+    /// Sign a ledger checkpoint after this many new events.
+    #[arg(long, default_value_t = ledger::DEFAULT_SIGNED_CHECKPOINT_EVERY)]
+    checkpoint_every_events: i64,
+
+    /// Sign a ledger checkpoint (and seal pending Merkle leaves) at least this often, in minutes.
+    #[arg(long, default_value_t = 10)]
+    checkpoint_every_mins: u64,
+
+    /// Keep at least this many recent events in the live ledger; older ones are
+    /// moved into sealed archive segments (0 = never archive).
+    #[arg(long, default_value_t = ledger::DEFAULT_RETAIN_EVENTS)]
+    retain_events: i64,
+
+    /// Never archive events younger than this many days (0 = no age floor).
+    #[arg(long, default_value_t = 0)]
+    retain_days: i64,
+
+    /// Rehash the whole chain on startup instead of verifying from the last watermark.
+    #[arg(long, default_value_t = false)]
+    full_verify: bool,
+
+    /// Run a full chain verification this often, in hours (0 = never).
+    #[arg(long, default_value_t = 24)]
+    full_verify_every_hours: u64,
+
+    /// ONNX sentence-embedding model for semantic memory recall, with `tokenizer.json`
+    /// in the same directory (requires the `onnx` feature). Defaults to the hashing baseline.
+    #[arg(long)]
+    embedding_model: Option<String>,
+
+    /// Merge near-duplicate memories into consolidated entries this often, in minutes (0 = never).
+    #[arg(long, default_value_t = 30)]
+    consolidate_every_mins: u64,
+
+    /// Open incidents from watcher, MCP server and routine failures; a failure attaches to
+    /// its service's open incident if that saw activity within this many minutes (0 = off).
+    #[arg(long, default_value_t = correlate::DEFAULT_WINDOW_MINS)]
+    correlate_window_mins: u64,
+
+    /// Read-only ledger connections serving queries alongside the single writer.
+    #[arg(long, default_value_t = ledger_handle::DEFAULT_READERS)]
+    ledger_readers: usize,
+
+    /// uid a daemon's service runs as, as NAME=UID (repeatable). Connections are only
+    /// recognised as that daemon from this uid; unlisted daemons must run as root or
+    /// as agentd's own user.
+    #[arg(long)]
+    daemon_uid: Vec<String>,
 }
 
 #[tokio::main]
@@ -63,30 +141,51 @@ async fn main() {
 
     tracing::info!(socket = %args.socket, state_dir = %args.state_dir, "starting agentd");
 
+    peer::set_service_uids(&args.daemon_uid).expect("invalid --daemon-uid");
+
     // Ensure state directory exists
     std::fs::create_dir_all(&args.state_dir).expect("failed to create state directory");
 
     // Initialize SQLite ledger
     let ledger_path = Path::new(&args.state_dir).join("ledger.db");
-    let ledger = ledger::Ledger::new(
+    let mut ledger = ledger::Ledger::new(
         ledger_path
             .to_str()
             .expect("invalid ledger path"),
     )
     .expect("failed to initialize ledger");
+    ledger.set_archive_dir(Path::new(&args.state_dir).join(archive::ARCHIVE_DIR));
+    if let Err(e) = ledger.reconcile_archive() {
+        tracing::error!(error = %e, "failed to settle staged ledger archive segments");
+    }
 
-    // Verify chain integrity on startup
-    match ledger.verify() {
-        Ok(true) => tracing::info!("ledger chain integrity verified"),
-        Ok(false) => tracing::warn!("ledger chain integrity check FAILED — chain may be tampered"),
+    // Load (or create) the ledger signing identity; it also signs the verification watermark
+    let signer = signing::LedgerSigner::load_or_create(Path::new(&args.state_dir))
+        .expect("failed to load ledger signing key");
+    ledger.set_signer(signer, args.checkpoint_every_events);
+
+    // Verify chain integrity on startup — only events past the last watermark unless --full-verify
+    match ledger.verify_report(args.full_verify) {
+        Ok(report) if report.ok => tracing::info!(
+            full = report.full,
+            events_checked = report.events_checked,
+            signed_checkpoints_checked = report.signed_checkpoints_checked,
+            "ledger chain integrity verified"
+        ),
+        Ok(_) => tracing::warn!("ledger chain integrity check FAILED — see GET /ledger/verify for the full report"),
         Err(e) => tracing::error!(error = %e, "failed to verify ledger chain"),
     }
 
     // Log daemon startup event
-    if let Err(e) = ledger.append("daemon.start", "agentd", &format!("{{\"socket\":\"{}\",\"state_dir\":\"{}\"}}", args.socket, args.state_dir)) {
+    let start_payload = serde_json::json!({"socket": args.socket, "state_dir": args.state_dir});
+    if let Err(e) = ledger.append("daemon.start", "agentd", &start_payload.to_string()) {
         tracing::error!(error = %e, "failed to log daemon start event");
     }
 
+    // From here on the writer thread owns the read-write connection
+    let ledger = ledger_handle::LedgerHandle::spawn(ledger, args.ledger_readers)
+        .expect("failed to start ledger writer");
+
     // Initialize approval gate if enabled
     let approval_gate = if args.approval_required {
         let extra_patterns: Vec<String> = args
@@ -100,6 +199,15 @@ async fn main() {
             extra_patterns,
         )
         .expect("failed to initialize approval gate");
+        let gate = match &args.approval_policy {
+            Some(path) => {
+                let store = Arc::new(policy::PolicyStore::load(path).expect("failed to load approval policy"));
+                tracing::info!(path = %path.display(), rules = store.current().rule_count(), "approval policy loaded");
+                tokio::spawn(policy::reload_loop(store.clone(), ledger.clone()));
+                gate.with_policy(store)
+            }
+            None => gate,
+        };
         let gate = Arc::new(gate);
         tracing::info!("approval gate enabled");
 
@@ -115,6 +223,24 @@ async fn main() {
         None
     };
 
+    let sinks: Vec<notify::Sink> = args
+        .approval_webhook
+        .iter()
+        .cloned()
+        .map(notify::Sink::Webhook)
+        .chain(args.approval_notify_peer.iter().cloned().map(notify::Sink::Mesh))
+        .collect();
+    let approval_notifier = if sinks.is_empty() {
+        None
+    } else {
+        let notifier = notify::ApprovalNotifier::new(sinks, &args.egress_proxy, &args.mesh_socket)
+            .expect("invalid approval notification sink");
+        for sink in notifier.sinks() {
+            tracing::info!(sink = sink.kind(), destination = sink.destination(), "approval notification sink configured");
+        }
+        Some(Arc::new(notifier))
+    };
+
     // Initialize sandbox engine if enabled
     let sandbox_engine = if args.sandbox_enabled {
         let engine = sandbox::SandboxEngine::generate(&args.egress_proxy);
@@ -125,25 +251,103 @@ async fn main() {
         None
     };
 
+    let embedder = match embedding::load(args.embedding_model.as_deref().map(Path::new)) {
+        Ok(embedder) => embedder,
+        Err(e) => {
+            tracing::error!(error = %e, "failed to load embedding model — falling back to the hashing embedder");
+            Arc::new(embedding::HashingEmbedder::default())
+        }
+    };
+    tracing::info!(model = embedder.model_id(), dimensions = embedder.dimensions(), "memory embedder ready");
+
+    // Embed memories stored before vectors existed (or under another model)
+    tokio::spawn(embedding::backfill(ledger.clone(), embedder.clone()));
+
     // Build shared state
     let sys = sysinfo::System::new_all();
     let shared_state: SharedState = Arc::new(AppState {
-        ledger: Mutex::new(ledger),
+        ledger,
         sys: Mutex::new(sys),
         state_dir: args.state_dir.clone(),
         approval_gate,
+        approval_notifier,
         sandbox_engine,
+        embedder,
     });
 
+    // Periodically seal and sign the ledger head so recent receipts become provable
+    let checkpoint_state = shared_state.clone();
+    let checkpoint_interval = std::time::Duration::from_secs(args.checkpoint_every_mins.max(1) * 60);
+    tokio::spawn(async move {
+        checkpoint_loop(checkpoint_state, checkpoint_interval).await;
+    });
+
+    // Move events outside the retention window into archive segments
+    let retention_state = shared_state.clone();
+    let retention = ledger::RetentionPolicy {
+        keep_events: args.retain_events,
+        keep_days: args.retain_days,
+    };
+    tokio::spawn(async move {
+        retention_loop(retention_state, retention).await;
+    });
+
+    // Drop memories past their TTL from the memory store
+    let expiry_state = shared_state.clone();
+    tokio::spawn(async move {
+        memory_expiry_loop(expiry_state).await;
+    });
+
+    // Merge near-duplicate memories pushed by watch, mcpd, teachd, ...
+    if args.consolidate_every_mins > 0 {
+        let consolidate_state = shared_state.clone();
+        let consolidate_interval = std::time::Duration::from_secs(args.consolidate_every_mins * 60);
+        tokio::spawn(async move {
+            consolidation_loop(consolidate_state, consolidate_interval).await;
+        });
+    }
+
+    // Turn failure and recovery signals into incidents
+    if args.correlate_window_mins > 0 {
+        let correlate_ledger = shared_state.ledger.clone();
+        let window = chrono::Duration::minutes(args.correlate_window_mins as i64);
+        tokio::spawn(async move {
+            correlate::run(correlate_ledger, window).await;
+        });
+    }
+
+    // Periodically rehash the whole chain, catching tampering behind the watermark
+    if args.full_verify_every_hours > 0 {
+        let verify_state = shared_state.clone();
+        let verify_interval = std::time::Duration::from_secs(args.full_verify_every_hours * 3600);
+        tokio::spawn(async move {
+            full_verify_loop(verify_state, verify_interval).await;
+        });
+    }
+
     // Build the axum router
     let app = Router::new()
         .route("/health", get(api::health::health_handler))
         .route("/system/query", post(api::system::system_query_handler))
         .route("/events/log", get(api::events::events_log_handler))
+        .route("/events/stream", get(api::events::events_stream_handler))
+        .route("/events/schemas", get(api::events::events_schemas_handler))
+        // Ledger proofs
+        .route("/ledger/proof/{id}", get(api::ledger::ledger_proof_handler))
+        .route("/ledger/checkpoints", get(api::ledger::ledger_checkpoints_handler))
+        .route("/ledger/signed-checkpoints", get(api::ledger::ledger_signed_checkpoints_handler))
+        .route("/ledger/segments", get(api::ledger::ledger_segments_handler))
+        .route("/ledger/verify", get(api::ledger::ledger_verify_handler))
+        .route("/ledger/stats", get(api::ledger::ledger_stats_handler))
+        .route("/ledger/redact/{id}", post(api::ledger::ledger_redact_handler))
+        .route("/ledger/export", get(api::ledger::ledger_export_handler))
         .route("/memory/ingest", post(api::memory::memory_ingest_handler))
         .route("/memory/recall", post(api::memory::memory_recall_handler))
         .route("/memory/store", post(api::memory::memory_store_handler))
         .route("/memory/health", get(api::memory::memory_health_handler))
+        .route("/memory/list", get(api::memory::memory_list_handler))
+        .route("/memory/pin", post(api::memory::memory_pin_handler))
+        .route("/memory/forget", post(api::memory::memory_forget_handler))
         // Agent Card (EIP-8004)
         .route("/agent/card", get(api::agent_card::agent_card_handler))
         .route("/agent/card/generate", post(api::agent_card::agent_card_generate_handler))
@@ -151,13 +355,19 @@ async fn main() {
         .route("/receipts", get(api::receipts::receipts_handler))
         .route("/incident/create", post(api::receipts::incident_create_handler))
         .route("/incident/{id}/step", post(api::receipts::incident_step_handler))
+        .route("/incident/{id}/update", post(api::receipts::incident_update_handler))
+        .route("/incident/{id}/resolve", post(api::receipts::incident_resolve_handler))
+        .route("/incident/{id}/reopen", post(api::receipts::incident_reopen_handler))
+        .route("/incident/{id}/report", get(api::receipts::incident_report_handler))
         .route("/incident/{id}", get(api::receipts::incident_get_handler))
         .route("/incidents", get(api::receipts::incidents_list_handler))
         // Approval Gate
         .route("/approval/request", post(api::approval::approval_request_handler))
         .route("/approval/pending", get(api::approval::approval_pending_handler))
+        .route("/approval/execute", post(api::approval::approval_execute_handler))
         .route("/approval/{id}/approve", post(api::approval::approval_approve_handler))
         .route("/approval/{id}/deny", post(api::approval::approval_deny_handler))
+        .route("/approval/{id}/wait", get(api::approval::approval_wait_handler))
         .route("/approval/{id}", get(api::approval::approval_check_handler))
         // Sandbox
         .route("/sandbox/exec", post(api::sandbox::sandbox_exec_handler))
@@ -195,23 +405,136 @@ async fn main() {
 
     tracing::info!(socket = %args.socket, "agentd listening");
 
-    // Serve
-    axum::serve(listener, app)
+    // Serve — each connection carries its SO_PEERCRED identity for event authorship
+    axum::serve(listener, app.into_make_service_with_connect_info::<peer::PeerIdentity>())
         .with_graceful_shutdown(shutdown_signal())
         .await
         .expect("server error");
 
     // Flush WAL on shutdown for data integrity
-    {
-        let ledger = shared_state.ledger.lock().await;
-        if let Err(e) = ledger.flush() {
-            tracing::warn!(error = %e, "WAL flush failed during shutdown");
-        }
+    if let Err(e) = shared_state.ledger.write(|ledger| ledger.flush()).await {
+        tracing::warn!(error = %e, "WAL flush failed during shutdown");
     }
 
     tracing::info!("agentd shutdown complete");
 }
 
+/// Background task that seals a Merkle checkpoint over any uncovered events
+/// and signs the current head, regardless of how many events arrived.
+async fn checkpoint_loop(state: SharedState, period: std::time::Duration) {
+    let mut interval = tokio::time::interval(period);
+    interval.tick().await; // first tick fires immediately
+
+    loop {
+        interval.tick().await;
+        let result = state
+            .ledger
+            .write(|ledger| {
+                if let Err(e) = ledger.checkpoint_pending() {
+                    tracing::warn!(error = %e, "periodic merkle checkpoint failed");
+                }
+                ledger.sign_checkpoint()
+            })
+            .await;
+        if let Err(e) = result {
+            tracing::warn!(error = %e, "periodic signed checkpoint failed");
+        }
+    }
+}
+
+/// Background task that runs a full chain verification on a fixed period.
+async fn full_verify_loop(state: SharedState, period: std::time::Duration) {
+    let mut interval = tokio::time::interval(period);
+    // The first tick fires immediately; startup already verified
+    interval.tick().await;
+
+    loop {
+        interval.tick().await;
+        match state.ledger.verify_report(true).await {
+            Ok(report) if report.ok => tracing::info!("scheduled full ledger verification passed"),
+            Ok(_) => tracing::warn!("ledger chain integrity check FAILED — see GET /ledger/verify for the full report"),
+            Err(e) => tracing::error!(error = %e, "scheduled full ledger verification failed to run"),
+        }
+    }
+}
+
+/// Background task that removes memories past their TTL once a minute.
+/// Expired memories are already hidden from recall; this makes the removal auditable.
+async fn memory_expiry_loop(state: SharedState) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+
+    loop {
+        interval.tick().await;
+        match state.ledger.write(|ledger| ledger.expire_memories()).await {
+            Ok(events) => {
+                for event in events {
+                    tracing::info!(event_id = event.id, payload = %event.payload, "expired memories");
+                }
+            }
+            Err(e) => tracing::warn!(error = %e, "memory expiry pass failed"),
+        }
+    }
+}
+
+/// Background task that clusters near-duplicate memories into consolidated entries.
+async fn consolidation_loop(state: SharedState, period: std::time::Duration) {
+    let mut interval = tokio::time::interval(period);
+
+    loop {
+        interval.tick().await;
+        match consolidate::run_pass(&state.ledger, &state.embedder, consolidate::DEFAULT_SIMILARITY).await {
+            Ok(0) => {}
+            Ok(merged) => tracing::info!(merged, "consolidated near-duplicate memories"),
+            Err(e) => tracing::warn!(error = %e, "memory consolidation pass failed"),
+        }
+    }
+}
+
+/// Background task that archives expired events once an hour.
+/// Each pass drains the whole backlog, one bounded segment at a time.
+async fn retention_loop(state: SharedState, policy: ledger::RetentionPolicy) {
+    if policy.keep_events <= 0 {
+        tracing::info!("ledger retention disabled (--retain-events 0)");
+        return;
+    }
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(3600));
+
+    loop {
+        interval.tick().await;
+
+        // Drain first, then log: appending inside the drain would shift the window.
+        // Each segment is a separate write so appends queued meanwhile are not stalled.
+        let mut archived = Vec::new();
+        loop {
+            let result = state.ledger.write(move |ledger| ledger.archive_expired(&policy)).await;
+            match result {
+                Ok(Some(segment)) => archived.push(segment),
+                Ok(None) => break,
+                Err(e) => {
+                    tracing::warn!(error = %e, "ledger archive pass failed");
+                    break;
+                }
+            }
+        }
+
+        for segment in archived {
+            if let Err(e) = state.ledger.append(
+                "ledger.archive",
+                "agentd",
+                &serde_json::json!({
+                    "file": segment.file,
+                    "first_event_id": segment.first_event_id,
+                    "last_event_id": segment.last_event_id,
+                    "sha256": segment.sha256,
+                })
+                .to_string(),
+            ).await {
+                tracing::error!(error = %e, file = %segment.file, "failed to log ledger archive to ledger");
+            }
+        }
+    }
+}
+
 async fn shutdown_signal() {
     let ctrl_c = async {
         signal::ctrl_c().await.expect("failed to install Ctrl+C handler");
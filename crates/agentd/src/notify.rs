@@ -0,0 +1,254 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use http_body_util::{BodyExt, Full};
+use hyper::body::Bytes;
+use hyper::Request;
+use hyper_util::rt::TokioIo;
+use tokio::net::UnixStream;
+
+use crate::approval::PendingApproval;
+use crate::ledger_handle::LedgerHandle;
+
+/// Give up on a sink after this long; a slow webhook must not pile up tasks.
+const DELIVERY_TIMEOUT_SECS: u64 = 15;
+
+/// Where to announce new approval requests.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Sink {
+    /// POST the request as JSON to this URL, through the egress proxy.
+    Webhook(String),
+    /// Send a mesh alert to this peer via osmoda-mesh.
+    Mesh(String),
+}
+
+impl Sink {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Sink::Webhook(_) => "webhook",
+            Sink::Mesh(_) => "mesh",
+        }
+    }
+
+    pub fn destination(&self) -> &str {
+        match self {
+            Sink::Webhook(url) => url,
+            Sink::Mesh(peer_id) => peer_id,
+        }
+    }
+}
+
+/// Fires every configured sink when an approval request is created, and records
+/// each delivery attempt in the ledger as `approval.notify`, with `delivered` saying
+/// whether it got through.
+pub struct ApprovalNotifier {
+    sinks: Vec<Sink>,
+    egress_proxy: String,
+    mesh_socket: String,
+}
+
+impl ApprovalNotifier {
+    pub fn new(sinks: Vec<Sink>, egress_proxy: &str, mesh_socket: &str) -> Result<Self> {
+        for sink in &sinks {
+            match sink {
+                Sink::Webhook(url) => validate_webhook_url(url)?,
+                Sink::Mesh(peer_id) => {
+                    if peer_id.is_empty() || !peer_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+                        bail!("invalid mesh peer id '{peer_id}'");
+                    }
+                }
+            }
+        }
+        Ok(Self {
+            sinks,
+            egress_proxy: egress_proxy.to_string(),
+            mesh_socket: mesh_socket.to_string(),
+        })
+    }
+
+    pub fn sinks(&self) -> &[Sink] {
+        &self.sinks
+    }
+
+    /// Announce `approval` to every sink in the background. Failures are logged and
+    /// never hold up the request that triggered them.
+    pub fn notify_requested(self: &Arc<Self>, ledger: &LedgerHandle, approval: &PendingApproval) {
+        for sink in &self.sinks {
+            let sink = sink.clone();
+            let notifier = self.clone();
+            let ledger = ledger.clone();
+            let approval = approval.clone();
+            tokio::spawn(async move {
+                let delivery = tokio::time::timeout(
+                    Duration::from_secs(DELIVERY_TIMEOUT_SECS),
+                    notifier.deliver(&sink, &approval),
+                )
+                .await
+                .unwrap_or_else(|_| Err(anyhow::anyhow!("timed out after {DELIVERY_TIMEOUT_SECS}s")));
+
+                let mut payload = serde_json::json!({
+                    "approval_id": approval.id,
+                    "command": approval.command,
+                    "sink": sink.kind(),
+                    "destination": sink.destination(),
+                    "delivered": delivery.is_ok(),
+                });
+                if let Err(e) = &delivery {
+                    tracing::warn!(
+                        approval_id = %approval.id,
+                        sink = sink.kind(),
+                        destination = sink.destination(),
+                        error = %format!("{e:#}"),
+                        "approval notification not delivered"
+                    );
+                    payload["error"] = format!("{e:#}").into();
+                }
+                if let Err(e) = ledger.append("approval.notify", "agentd", &payload.to_string()).await {
+                    tracing::warn!(error = %e, "failed to record approval notification");
+                }
+            });
+        }
+    }
+
+    async fn deliver(&self, sink: &Sink, approval: &PendingApproval) -> Result<()> {
+        match sink {
+            Sink::Webhook(url) => self.post_webhook(url, &webhook_body(approval)).await,
+            Sink::Mesh(peer_id) => self.send_mesh_alert(peer_id, approval).await,
+        }
+    }
+
+    /// agentd itself has no TLS client, so webhooks go through curl via the egress
+    /// proxy, which enforces the domain allowlist.
+    async fn post_webhook(&self, url: &str, body: &serde_json::Value) -> Result<()> {
+        let output = tokio::process::Command::new("curl")
+            .args([
+                "-s",
+                "--proto", "=https",
+                "--max-time", "10",
+                "--proxy", &self.egress_proxy,
+                "-o", "/dev/null",
+                "-w", "%{http_code}",
+                "-X", "POST",
+                "-H", "Content-Type: application/json",
+                "-d", &body.to_string(),
+                url,
+            ])
+            .output()
+            .await
+            .context("failed to run curl")?;
+        let code = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if !output.status.success() || !code.starts_with('2') {
+            bail!("webhook returned HTTP {code} (curl exit {})", output.status);
+        }
+        Ok(())
+    }
+
+    async fn send_mesh_alert(&self, peer_id: &str, approval: &PendingApproval) -> Result<()> {
+        let body = serde_json::json!({
+            "message": {
+                "type": "alert",
+                "severity": "warning",
+                "title": format!("Approval requested: {}", approval.command),
+                "detail": format!(
+                    "{} asks to run '{}' ({}). Approval {} needs {} approver(s) before {}.",
+                    approval.actor,
+                    approval.command,
+                    approval.reason,
+                    approval.id,
+                    approval.required_approvals,
+                    approval.expires_at,
+                ),
+            },
+        });
+
+        let stream = UnixStream::connect(&self.mesh_socket)
+            .await
+            .with_context(|| format!("failed to connect to mesh at {}", self.mesh_socket))?;
+        let (mut sender, conn) = hyper::client::conn::http1::handshake(TokioIo::new(stream)).await?;
+        tokio::spawn(async move {
+            if let Err(e) = conn.await {
+                tracing::debug!(error = %e, "mesh connection closed");
+            }
+        });
+
+        let req = Request::builder()
+            .method("POST")
+            .uri(format!("/peer/{peer_id}/send"))
+            .header("Content-Type", "application/json")
+            .body(Full::new(Bytes::from(body.to_string())))?;
+        let resp = sender.send_request(req).await?;
+        let status = resp.status();
+        if !status.is_success() {
+            let detail = resp.into_body().collect().await?.to_bytes();
+            bail!("mesh returned {status}: {}", String::from_utf8_lossy(&detail));
+        }
+        Ok(())
+    }
+}
+
+/// Only `https://` webhooks on port 443: the egress proxy tunnels nothing else, and
+/// other schemes could read local files through curl.
+fn validate_webhook_url(url: &str) -> Result<()> {
+    let Some(rest) = url.strip_prefix("https://") else {
+        bail!("approval webhook URL must use https://, got '{url}'");
+    };
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host_port = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    // The port follows the last ':' unless that is inside an IPv6 literal
+    let port = host_port.rsplit_once(':').filter(|(_, port)| !port.contains(']')).map(|(_, port)| port);
+    if host_port.is_empty() || host_port.starts_with(':') {
+        bail!("approval webhook URL has no host: '{url}'");
+    }
+    if port.is_some_and(|port| port != "443") {
+        bail!("approval webhook URL must use port 443 (the egress proxy only connects there), got '{url}'");
+    }
+    Ok(())
+}
+
+fn webhook_body(approval: &PendingApproval) -> serde_json::Value {
+    serde_json::json!({
+        "event": "approval.requested",
+        "approval_id": approval.id,
+        "command": approval.command,
+        "actor": approval.actor,
+        "reason": approval.reason,
+        "rule": approval.rule,
+        "required_approvals": approval.required_approvals,
+        "approver_group": approval.approver_group,
+        "eligible_approvers": approval.eligible_approvers,
+        "created_at": approval.created_at,
+        "expires_at": approval.expires_at,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rejects_bad_sinks() {
+        for bad in [
+            "file:///etc/shadow",
+            "http://hooks.example.com/approvals",
+            "https://hooks.example.com:8443/approvals",
+            "https://user@hooks.example.com:80/x",
+            "https://[::1]:8080/x",
+            "https:///x",
+        ] {
+            assert!(ApprovalNotifier::new(vec![Sink::Webhook(bad.into())], "", "").is_err(), "{bad}");
+        }
+        for good in ["https://hooks.example.com:443/x", "https://[::1]/x", "https://hooks.example.com?a=b:1"] {
+            assert!(validate_webhook_url(good).is_ok(), "{good}");
+        }
+        assert!(ApprovalNotifier::new(vec![Sink::Mesh("../admin".into())], "", "").is_err());
+
+        let ok = ApprovalNotifier::new(
+            vec![Sink::Webhook("https://hooks.example.com/approvals".into()), Sink::Mesh("admin-peer_1".into())],
+            "http://127.0.0.1:19999",
+            "/run/osmoda/mesh.sock",
+        )
+        .unwrap();
+        assert_eq!(ok.sinks().len(), 2);
+    }
+}
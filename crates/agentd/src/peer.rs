@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use anyhow::{Context, Result};
+
+use axum::extract::connect_info::Connected;
+use axum::serve::IncomingStream;
+use serde::Serialize;
+use tokio::net::{UnixListener, UnixStream};
+
+/// osModa binaries whose names are reserved as actors: only the binary itself,
+/// identified over SO_PEERCRED, may claim one.
+pub const KNOWN_DAEMONS: &[&str] = &[
+    "agentd",
+    "agentctl",
+    "osmoda-egress",
+    "osmoda-keyd",
+    "osmoda-mcpd",
+    "osmoda-mesh",
+    "osmoda-routines",
+    "osmoda-teachd",
+    "osmoda-voice",
+    "osmoda-watch",
+];
+
+/// Service uids set with `--daemon-uid`. A daemon not listed must run as root or as
+/// agentd's own user, as the NixOS module runs them.
+static SERVICE_UIDS: OnceLock<HashMap<&'static str, u32>> = OnceLock::new();
+
+/// Record the uid each daemon's service runs as, from `NAME=UID` specs. Call once,
+/// before accepting connections.
+pub fn set_service_uids(specs: &[String]) -> Result<()> {
+    let mut uids = HashMap::new();
+    for spec in specs {
+        let (name, uid) = spec.split_once('=').with_context(|| format!("expected NAME=UID, got '{spec}'"))?;
+        let daemon = KNOWN_DAEMONS
+            .iter()
+            .find(|d| **d == name)
+            .with_context(|| format!("'{name}' is not an osModa daemon"))?;
+        let uid: u32 = uid.parse().with_context(|| format!("invalid uid in '{spec}'"))?;
+        uids.insert(*daemon, uid);
+    }
+    SERVICE_UIDS.set(uids).map_err(|_| anyhow::anyhow!("daemon service uids already set"))
+}
+
+/// Whether `uid` is the uid `daemon`'s service runs as.
+fn is_service_uid(daemon: &str, uid: u32) -> bool {
+    match SERVICE_UIDS.get().and_then(|uids| uids.get(daemon)) {
+        Some(service_uid) => uid == *service_uid,
+        // SAFETY: geteuid has no preconditions and cannot fail
+        None => uid == 0 || uid == unsafe { libc::geteuid() },
+    }
+}
+
+/// Who is on the other end of an agentd socket connection, resolved once at accept time
+/// from SO_PEERCRED and `/proc/<pid>/exe`.
+#[derive(Debug, Clone, Serialize)]
+pub struct PeerIdentity {
+    pub pid: Option<i32>,
+    pub uid: Option<u32>,
+    pub exe: Option<PathBuf>,
+    /// The osModa daemon this peer is, when its executable is one of `KNOWN_DAEMONS`,
+    /// owned by root or agentd's own user (so it cannot be a renamed copy), and it runs
+    /// as that daemon's service uid (so a user cannot simply start the real binary).
+    pub daemon: Option<&'static str>,
+}
+
+impl PeerIdentity {
+    pub fn from_stream(stream: &UnixStream) -> Self {
+        let Ok(cred) = stream.peer_cred() else {
+            return Self { pid: None, uid: None, exe: None, daemon: None };
+        };
+        let pid = cred.pid();
+        let exe = pid.and_then(|pid| std::fs::read_link(format!("/proc/{pid}/exe")).ok());
+        let daemon = exe.as_deref().and_then(trusted_daemon).filter(|d| is_service_uid(d, cred.uid()));
+        Self { pid, uid: Some(cred.uid()), exe, daemon }
+    }
+
+    /// Name recorded as the event's `verified_actor`: the daemon name when recognised,
+    /// otherwise `uid:<uid>:<executable name>`.
+    pub fn actor(&self) -> String {
+        if let Some(daemon) = self.daemon {
+            return daemon.to_string();
+        }
+        let exe = self
+            .exe
+            .as_deref()
+            .and_then(Path::file_name)
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "unknown".to_string());
+        match self.uid {
+            Some(uid) => format!("uid:{uid}:{exe}"),
+            None => format!("unknown:{exe}"),
+        }
+    }
+
+    /// The identity approvals are bound to: the daemon name when recognised, otherwise
+    /// `uid:<uid>`. Unlike `actor()` it ignores the executable, so a user cannot pose as
+    /// someone else by switching programs. `None` when the peer's uid is unknown.
+    pub fn principal(&self) -> Option<String> {
+        match (self.daemon, self.uid) {
+            (Some(daemon), _) => Some(daemon.to_string()),
+            (None, Some(uid)) => Some(format!("uid:{uid}")),
+            (None, None) => None,
+        }
+    }
+
+    /// Whether this peer may append events under the claimed `actor` name.
+    /// Reserved daemon names need a matching verified daemon; anything else is allowed
+    /// (and still recorded next to the verified identity).
+    pub fn may_claim(&self, actor: &str) -> bool {
+        !KNOWN_DAEMONS.contains(&actor) || self.daemon == Some(actor)
+    }
+}
+
+impl Connected<IncomingStream<'_, UnixListener>> for PeerIdentity {
+    fn connect_info(stream: IncomingStream<'_, UnixListener>) -> Self {
+        Self::from_stream(stream.io())
+    }
+}
+
+/// Whether `name` has the form of a `principal()`: `uid:<n>` or a known daemon name.
+pub fn is_principal(name: &str) -> bool {
+    KNOWN_DAEMONS.contains(&name) || name.strip_prefix("uid:").is_some_and(|uid| uid.parse::<u32>().is_ok())
+}
+
+/// Map an executable path to a known daemon name if the file is one we can trust.
+fn trusted_daemon(exe: &Path) -> Option<&'static str> {
+    let name = exe.file_name()?.to_str()?;
+    let daemon = KNOWN_DAEMONS.iter().find(|d| **d == name)?;
+
+    let meta = std::fs::metadata(exe).ok()?;
+    // SAFETY: geteuid has no preconditions and cannot fail
+    let own_uid = unsafe { libc::geteuid() };
+    let trusted_owner = meta.uid() == 0 || meta.uid() == own_uid;
+    let writable_by_others = meta.mode() & 0o022 != 0;
+    (trusted_owner && !writable_by_others).then_some(*daemon)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_peer_identity_of_unknown_process() {
+        let (a, _b) = UnixStream::pair().unwrap();
+        let peer = PeerIdentity::from_stream(&a);
+
+        // The test binary is not an osModa daemon, so it gets a uid-qualified name
+        assert_eq!(peer.pid, Some(std::process::id() as i32));
+        assert!(peer.daemon.is_none());
+        assert!(peer.actor().starts_with(&format!("uid:{}:", unsafe { libc::geteuid() })));
+
+        assert_eq!(peer.principal(), Some(format!("uid:{}", unsafe { libc::geteuid() })));
+        assert!(peer.may_claim("my-script"));
+        assert!(!peer.may_claim("osmoda-keyd"));
+        assert!(!peer.may_claim("agentd"));
+    }
+
+    #[test]
+    fn test_daemon_must_match_claim() {
+        let keyd = PeerIdentity { pid: Some(1), uid: Some(0), exe: None, daemon: Some("osmoda-keyd") };
+        assert_eq!(keyd.actor(), "osmoda-keyd");
+        assert!(keyd.may_claim("osmoda-keyd"));
+        assert!(!keyd.may_claim("osmoda-mcpd"));
+        assert_eq!(keyd.principal().as_deref(), Some("osmoda-keyd"));
+    }
+
+    #[test]
+    fn test_daemon_needs_its_service_uid() {
+        // Without --daemon-uid, a daemon must run as root or as agentd's user
+        let own = unsafe { libc::geteuid() };
+        assert!(is_service_uid("osmoda-watch", 0));
+        assert!(is_service_uid("osmoda-watch", own));
+        assert!(!is_service_uid("osmoda-watch", own.wrapping_add(4242)));
+    }
+
+    #[test]
+    fn test_is_principal() {
+        assert!(is_principal("uid:1000"));
+        assert!(is_principal("osmoda-watch"));
+        assert!(!is_principal("alice"));
+        assert!(!is_principal("uid:1000:curl"));
+        assert!(!is_principal("uid:"));
+    }
+
+    #[test]
+    fn test_untrusted_exe_not_recognised() {
+        let dir = tempfile::tempdir().unwrap();
+        let exe = dir.path().join("osmoda-keyd");
+        std::fs::write(&exe, b"").unwrap();
+
+        std::fs::set_permissions(&exe, std::fs::Permissions::from_mode(0o755)).unwrap();
+        assert_eq!(trusted_daemon(&exe), Some("osmoda-keyd"));
+
+        // A world-writable binary could have been swapped by anyone
+        std::fs::set_permissions(&exe, std::fs::Permissions::from_mode(0o777)).unwrap();
+        assert_eq!(trusted_daemon(&exe), None);
+    }
+
+    use std::os::unix::fs::PermissionsExt;
+}
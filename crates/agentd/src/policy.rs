@@ -0,0 +1,655 @@
+//! Declarative approval policy for the approval gate.
+//!
+//! A policy file holds ordered rules; the first rule whose conditions all match a
+//! request decides it. A rule can match on operation, a command regex, the caller's
+//! verified identity, target path and UTC time of day, and resolves to auto-allow, require approval (from one
+//! or more approvers) or hard deny. Requests no rule matches get the policy's
+//! `default`, or — without one — the gate's built-in destructive-command check.
+//! An `allow` only stands if it also holds for every destructive program the command
+//! runs, taken one at a time (see `Policy::allow_covers`).
+//! A rule that needs several approvers can restrict them to a named group. Rule
+//! actors and group members are verified identities, never claimed names: `uid:<n>`
+//! for the user a connection runs as, or an osModa daemon name.
+//!
+//! ```json
+//! {
+//!   "groups": { "treasury": ["uid:1001", "uid:1002", "uid:1003"] },
+//!   "rules": [
+//!     { "name": "no-disk-wipes", "command": "^(mkfs|wipefs|dd if=)", "decision": "deny" },
+//!     { "name": "wallet-quorum", "operation": ["wallet.send"], "decision": "require_approval",
+//!       "approvers": 2, "approver_group": "treasury" },
+//!     { "name": "etc-after-hours", "path": ["/etc"], "time": "18:00-08:00", "decision": "require_approval" },
+//!     { "name": "oncall-restarts", "actor": ["uid:1005"], "command": "^systemctl restart ", "decision": "allow" }
+//!   ],
+//!   "default": "builtin"
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
+
+use anyhow::{Context, Result};
+use chrono::Timelike;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::shell::Invocation;
+
+/// Rule name recorded when no policy rule matched and the built-in check decided.
+pub const BUILTIN_RULE: &str = "builtin";
+/// Rule name recorded when no policy rule matched and the policy's `default` decided.
+pub const DEFAULT_RULE: &str = "default";
+
+/// Programs whose effect stays within the paths they are given. An allow rule that
+/// names no program (no `command` or `operation`) only covers these.
+const PATH_SCOPED_PROGRAMS: &[&str] = &[
+    "rm", "unlink", "rmdir", "chmod", "chown", "chgrp", "truncate", "find", "dd", "tee", "cp", "mv",
+];
+
+/// What the gate does with a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "decision", rename_all = "snake_case")]
+pub enum Decision {
+    Allow,
+    RequireApproval { approvers: u32 },
+    Deny,
+}
+
+impl Decision {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Decision::Allow => "allow",
+            Decision::RequireApproval { .. } => "require_approval",
+            Decision::Deny => "deny",
+        }
+    }
+}
+
+/// A decision and the rule that produced it (`builtin`/`default` when none did).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyDecision {
+    pub decision: Decision,
+    pub rule: String,
+    /// Who may approve, when the rule names an approver group; otherwise anyone
+    /// but the requester.
+    pub group: Option<ApproverGroup>,
+}
+
+/// A named set of approvers, as defined in the policy's `groups`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ApproverGroup {
+    pub name: String,
+    pub members: Vec<String>,
+}
+
+/// What a rule is matched against.
+#[derive(Debug, Clone, Copy)]
+pub struct PolicyRequest<'a> {
+    /// The requested command or operation, as sent.
+    pub command: &'a str,
+    /// Normalized form of `command` (see `ApprovalGate::normalize_command`).
+    pub normalized: &'a str,
+    /// Verified identity of the caller (`PeerIdentity::principal`), if agentd could
+    /// establish one. The actor name a caller claims is never matched.
+    pub actor: Option<&'a str>,
+    /// Path the request acts on, if the caller named one.
+    pub target: Option<&'a str>,
+    pub now: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PolicyFile {
+    #[serde(default)]
+    groups: BTreeMap<String, Vec<String>>,
+    rules: Vec<RuleSpec>,
+    #[serde(default)]
+    default: Option<DefaultSpec>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum DefaultSpec {
+    Builtin,
+    Allow,
+    RequireApproval,
+    Deny,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum DecisionSpec {
+    Allow,
+    RequireApproval,
+    Deny,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleSpec {
+    name: String,
+    #[serde(default)]
+    operation: Vec<String>,
+    command: Option<String>,
+    #[serde(default)]
+    actor: Vec<String>,
+    #[serde(default)]
+    path: Vec<String>,
+    /// `HH:MM-HH:MM` in UTC; may wrap past midnight.
+    time: Option<String>,
+    decision: DecisionSpec,
+    /// Distinct approvers needed (require_approval only, default 1).
+    approvers: Option<u32>,
+    /// Group the approvers must belong to (require_approval only).
+    approver_group: Option<String>,
+}
+
+#[derive(Debug)]
+struct Rule {
+    name: String,
+    operations: Vec<String>,
+    command: Option<Regex>,
+    actors: Vec<String>,
+    paths: Vec<String>,
+    /// Minutes after UTC midnight, start inclusive, end exclusive.
+    window: Option<(u32, u32)>,
+    decision: Decision,
+    group: Option<String>,
+}
+
+/// A parsed policy file.
+#[derive(Debug)]
+pub struct Policy {
+    groups: BTreeMap<String, Vec<String>>,
+    rules: Vec<Rule>,
+    /// None falls back to the built-in check.
+    default: Option<Decision>,
+    /// SHA-256 of the file contents, recorded when it is loaded.
+    pub sha256: String,
+}
+
+impl Policy {
+    pub fn parse(text: &str) -> Result<Self> {
+        let file: PolicyFile = serde_json::from_str(text).context("invalid approval policy")?;
+
+        for (group, members) in &file.groups {
+            anyhow::ensure!(!members.is_empty(), "approver group '{group}' has no members");
+            anyhow::ensure!(
+                members.iter().enumerate().all(|(i, m)| !m.is_empty() && !members[..i].contains(m)),
+                "approver group '{group}' has empty or duplicate members",
+            );
+            if let Some(bad) = members.iter().find(|m| !crate::peer::is_principal(m)) {
+                anyhow::bail!("approver group '{group}': member '{bad}' must be uid:<n> or an osModa daemon name");
+            }
+        }
+
+        let mut rules: Vec<Rule> = Vec::with_capacity(file.rules.len());
+        for spec in file.rules {
+            let name = spec.name.trim().to_string();
+            if name.is_empty() || name == BUILTIN_RULE || name == DEFAULT_RULE {
+                anyhow::bail!("rule name '{name}' is empty or reserved");
+            }
+            if rules.iter().any(|r| r.name == name) {
+                anyhow::bail!("duplicate rule name '{name}'");
+            }
+            let command = spec
+                .command
+                .as_deref()
+                .map(Regex::new)
+                .transpose()
+                .with_context(|| format!("rule '{name}': invalid command regex"))?;
+            let window = spec
+                .time
+                .as_deref()
+                .map(parse_window)
+                .transpose()
+                .with_context(|| format!("rule '{name}': invalid time window"))?;
+            let decision = match (spec.decision, spec.approvers) {
+                (DecisionSpec::RequireApproval, None) => Decision::RequireApproval { approvers: 1 },
+                (DecisionSpec::RequireApproval, Some(n)) if n >= 1 => Decision::RequireApproval { approvers: n },
+                (DecisionSpec::RequireApproval, Some(_)) => anyhow::bail!("rule '{name}': approvers must be at least 1"),
+                (_, Some(_)) => anyhow::bail!("rule '{name}': approvers only applies to require_approval"),
+                (DecisionSpec::Allow, None) => Decision::Allow,
+                (DecisionSpec::Deny, None) => Decision::Deny,
+            };
+            if let Some(bad) = spec.actor.iter().find(|a| !crate::peer::is_principal(a)) {
+                anyhow::bail!("rule '{name}': actor '{bad}' must be uid:<n> or an osModa daemon name");
+            }
+            if let Some(group) = &spec.approver_group {
+                let Decision::RequireApproval { approvers } = decision else {
+                    anyhow::bail!("rule '{name}': approver_group only applies to require_approval");
+                };
+                let members = file
+                    .groups
+                    .get(group)
+                    .with_context(|| format!("rule '{name}': unknown approver group '{group}'"))?;
+                anyhow::ensure!(
+                    approvers as usize <= members.len(),
+                    "rule '{name}': needs {approvers} approvers but group '{group}' has {}",
+                    members.len(),
+                );
+            }
+            rules.push(Rule {
+                name,
+                operations: spec.operation.iter().map(|o| o.to_lowercase()).collect(),
+                command,
+                actors: spec.actor,
+                paths: spec.path.iter().map(|p| normalize_prefix(p)).collect::<Result<_>>()?,
+                window,
+                decision,
+                group: spec.approver_group,
+            });
+        }
+
+        let default = match file.default.unwrap_or(DefaultSpec::Builtin) {
+            DefaultSpec::Builtin => None,
+            DefaultSpec::Allow => Some(Decision::Allow),
+            DefaultSpec::RequireApproval => Some(Decision::RequireApproval { approvers: 1 }),
+            DefaultSpec::Deny => Some(Decision::Deny),
+        };
+
+        Ok(Self { groups: file.groups, rules, default, sha256: hex::encode(Sha256::digest(text.as_bytes())) })
+    }
+
+    pub fn rule_count(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// The first matching rule's decision, else the policy default (None: use the built-in check).
+    pub fn evaluate(&self, request: &PolicyRequest<'_>) -> Option<PolicyDecision> {
+        let mut paths = command_paths(request.command);
+        paths.extend(request.target.and_then(|t| absolute(t, None)));
+        if let Some(rule) = self.rules.iter().find(|r| r.matches(request, &paths)) {
+            let group = rule.group.as_ref().map(|name| ApproverGroup {
+                name: name.clone(),
+                members: self.groups[name].clone(),
+            });
+            return Some(PolicyDecision { decision: rule.decision, rule: rule.name.clone(), group });
+        }
+        self.default.map(|decision| PolicyDecision { decision, rule: DEFAULT_RULE.to_string(), group: None })
+    }
+
+    /// Whether an `allow` for `request` holds for every program its command runs. Each
+    /// invocation `destructive` flags is evaluated on its own, and must itself be allowed,
+    /// so `systemctl restart x; rm -rf /` is not covered by a rule for `^systemctl restart `.
+    /// A command the tokenizer cannot follow is never covered.
+    pub fn allow_covers(&self, request: &PolicyRequest<'_>, destructive: impl Fn(&Invocation) -> bool) -> bool {
+        let Ok(invocations) = crate::shell::invocations(request.command) else {
+            return false;
+        };
+        let target = request.target.and_then(|t| absolute(t, None));
+        invocations.iter().zip(invocation_paths(&invocations)).all(|(inv, mut paths)| {
+            if !destructive(inv) {
+                return true;
+            }
+            let command = inv.command_line();
+            let words = std::iter::once(&inv.binary).chain(&inv.args);
+            let normalized = words.flat_map(|w| w.split_whitespace()).collect::<Vec<_>>().join(" ").to_lowercase();
+            let single = PolicyRequest { command: &command, normalized: &normalized, ..*request };
+            paths.extend(target.clone());
+            match self.rules.iter().find(|r| r.matches(&single, &paths)) {
+                Some(rule) => {
+                    rule.decision == Decision::Allow
+                        && (rule.names_program() || PATH_SCOPED_PROGRAMS.contains(&inv.binary.as_str()))
+                }
+                None => self.default == Some(Decision::Allow),
+            }
+        })
+    }
+}
+
+impl Rule {
+    /// Whether the rule says what may run, not only where, by whom or when.
+    fn names_program(&self) -> bool {
+        self.command.is_some() || !self.operations.is_empty()
+    }
+
+    /// `paths` are the absolute paths the request touches (see `command_paths`).
+    fn matches(&self, request: &PolicyRequest<'_>, paths: &[String]) -> bool {
+        let lower = request.command.to_lowercase();
+        let operation_ok = self.operations.is_empty()
+            || self.operations.iter().any(|op| lower == *op || lower.starts_with(&format!("{op}.")));
+        let command_ok = self.command.as_ref().is_none_or(|re| re.is_match(request.normalized));
+        let actor_ok = self.actors.is_empty() || request.actor.is_some_and(|actor| self.actors.iter().any(|a| a == actor));
+        let path_ok = self.paths.is_empty() || {
+            let under = |path: &String| self.paths.iter().any(|prefix| is_under(path, prefix));
+            // Allowing must not extend to paths outside the rule; holding back or
+            // denying applies if any path is inside it
+            if self.decision == Decision::Allow {
+                !paths.is_empty() && paths.iter().all(under)
+            } else {
+                paths.iter().any(under)
+            }
+        };
+        let time_ok = self.window.is_none_or(|(start, end)| {
+            let minute = request.now.hour() * 60 + request.now.minute();
+            if start <= end {
+                (start..end).contains(&minute)
+            } else {
+                minute >= start || minute < end
+            }
+        });
+        operation_ok && command_ok && actor_ok && path_ok && time_ok
+    }
+}
+
+/// `HH:MM-HH:MM` → minutes after midnight.
+fn parse_window(text: &str) -> Result<(u32, u32)> {
+    let minutes = |hhmm: &str| -> Result<u32> {
+        let (h, m) = hhmm.trim().split_once(':').context("expected HH:MM")?;
+        let (h, m): (u32, u32) = (h.parse()?, m.parse()?);
+        anyhow::ensure!(h < 24 && m < 60, "{hhmm} is not a time of day");
+        Ok(h * 60 + m)
+    };
+    let (start, end) = text.split_once('-').context("expected HH:MM-HH:MM")?;
+    let window = (minutes(start)?, minutes(end)?);
+    anyhow::ensure!(window.0 != window.1, "empty time window");
+    Ok(window)
+}
+
+fn normalize_prefix(path: &str) -> Result<String> {
+    anyhow::ensure!(path.starts_with('/'), "path '{path}' must be absolute");
+    let trimmed = path.trim_end_matches('/');
+    Ok(if trimmed.is_empty() { "/".to_string() } else { trimmed.to_string() })
+}
+
+/// The absolute paths `command` names, as the shell would see them: arguments and
+/// redirection targets of every program it runs, the value after `=` in words like
+/// `--file=/etc/x` or `of=/dev/sda`, and relative paths resolved against the
+/// directory the last `cd` moved to. Relative paths before any `cd` (or after one to
+/// somewhere unknown) are skipped, since the caller's directory is not known. A
+/// command the tokenizer cannot follow falls back to its absolute-looking words.
+fn command_paths(command: &str) -> Vec<String> {
+    let Ok(invocations) = crate::shell::invocations(command) else {
+        return command.split_whitespace().filter_map(|word| absolute(word, None)).collect();
+    };
+    let mut paths: Vec<String> = invocation_paths(&invocations).into_iter().flatten().collect();
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+/// The absolute paths each of `invocations` names, in order (see `command_paths`).
+fn invocation_paths(invocations: &[Invocation]) -> Vec<Vec<String>> {
+    let mut cwd: Option<String> = None;
+    let mut all = Vec::with_capacity(invocations.len());
+    for inv in invocations {
+        let mut paths = Vec::new();
+        for word in inv.args.iter().chain(&inv.redirects) {
+            if !word.starts_with('-') {
+                paths.extend(absolute(word, cwd.as_deref()));
+            }
+            if let Some((_, value)) = word.split_once('=') {
+                paths.extend(absolute(value, cwd.as_deref()));
+            }
+        }
+        if matches!(inv.binary.as_str(), "cd" | "pushd") {
+            cwd = match inv.args.iter().find(|a| !a.starts_with('-')) {
+                Some(dir) if !dir.starts_with('~') => absolute(dir, cwd.as_deref()),
+                _ => None,
+            };
+        }
+        all.push(paths);
+    }
+    all
+}
+
+/// `path` made absolute against `cwd` (None if it is relative and `cwd` is unknown),
+/// with `.` and `..` resolved so `/tmp/../etc` cannot pass for a path under `/tmp`.
+fn absolute(path: &str, cwd: Option<&str>) -> Option<String> {
+    if path.is_empty() {
+        return None;
+    }
+    let joined = if path.starts_with('/') { path.to_string() } else { format!("{}/{path}", cwd?) };
+    let mut parts: Vec<&str> = Vec::new();
+    for part in joined.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+    Some(format!("/{}", parts.join("/")))
+}
+
+/// Whether `path` is `prefix` or inside it, by whole components.
+fn is_under(path: &str, prefix: &str) -> bool {
+    prefix == "/" || path == prefix || path.strip_prefix(prefix).is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// A policy file and the policy last loaded from it. The file is re-read when its
+/// modification time changes; a file that fails to parse leaves the last good policy
+/// in force.
+pub struct PolicyStore {
+    path: PathBuf,
+    current: RwLock<(Arc<Policy>, Option<SystemTime>)>,
+}
+
+impl PolicyStore {
+    /// Load the policy at `path`; a missing or invalid file is an error.
+    pub fn load(path: &Path) -> Result<Self> {
+        let (policy, modified) = Self::read(path)?;
+        Ok(Self { path: path.to_path_buf(), current: RwLock::new((Arc::new(policy), modified)) })
+    }
+
+    fn read(path: &Path) -> Result<(Policy, Option<SystemTime>)> {
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read approval policy {}", path.display()))?;
+        let policy = Policy::parse(&text).with_context(|| format!("in {}", path.display()))?;
+        Ok((policy, modified))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn current(&self) -> Arc<Policy> {
+        self.current.read().expect("policy lock poisoned").0.clone()
+    }
+
+    /// Re-read the file if it changed since the last load. Returns the new policy when
+    /// one was loaded; on a parse error the previous policy stays and the error is returned.
+    pub fn reload_if_changed(&self) -> Result<Option<Arc<Policy>>> {
+        let modified = std::fs::metadata(&self.path).and_then(|m| m.modified()).ok();
+        if modified.is_none() || modified == self.current.read().expect("policy lock poisoned").1 {
+            return Ok(None);
+        }
+        let loaded = Self::read(&self.path);
+        let mut current = self.current.write().expect("policy lock poisoned");
+        // Don't retry a broken file until it changes again
+        current.1 = modified;
+        let (policy, _) = loaded?;
+        current.0 = Arc::new(policy);
+        Ok(Some(current.0.clone()))
+    }
+}
+
+/// How often the policy file is checked for changes.
+pub const RELOAD_CHECK_INTERVAL_SECS: u64 = 5;
+
+/// Record the loaded policy, then reload it whenever the file changes. Each load is
+/// logged as a `policy.reload` event with the file's SHA-256, so the ledger shows
+/// which policy was in force when a decision was made.
+pub async fn reload_loop(store: Arc<PolicyStore>, ledger: crate::ledger_handle::LedgerHandle) {
+    let record = |policy: Arc<Policy>| {
+        let payload = serde_json::json!({
+            "path": store.path().display().to_string(),
+            "rule_count": policy.rule_count(),
+            "sha256": policy.sha256,
+        });
+        let ledger = ledger.clone();
+        async move {
+            if let Err(e) = ledger.append("policy.reload", "agentd", &payload.to_string()).await {
+                tracing::warn!(error = %e, "failed to record approval policy load");
+            }
+        }
+    };
+    record(store.current()).await;
+
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(RELOAD_CHECK_INTERVAL_SECS));
+    loop {
+        interval.tick().await;
+        match store.reload_if_changed() {
+            Ok(Some(policy)) => {
+                tracing::info!(path = %store.path().display(), rules = policy.rule_count(), "approval policy reloaded");
+                record(policy).await;
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!(error = %format!("{e:#}"), "approval policy reload failed — keeping the previous policy"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32, minute: u32) -> chrono::DateTime<chrono::Utc> {
+        chrono::Utc.with_ymd_and_hms(2026, 3, 2, hour, minute, 0).unwrap()
+    }
+
+    fn decide(policy: &Policy, command: &str, actor: &str, target: Option<&str>, now: chrono::DateTime<chrono::Utc>) -> Option<PolicyDecision> {
+        let normalized = command.to_lowercase();
+        policy.evaluate(&PolicyRequest { command, normalized: &normalized, actor: Some(actor), target, now })
+    }
+
+    const POLICY: &str = r#"{
+        "groups": { "treasury": ["uid:1001", "uid:1002", "uid:1003"] },
+        "rules": [
+            { "name": "no-disk-wipes", "command": "^(mkfs|wipefs)", "decision": "deny" },
+            { "name": "wallet-quorum", "operation": ["wallet.send"], "decision": "require_approval", "approvers": 2, "approver_group": "treasury" },
+            { "name": "etc-after-hours", "path": ["/etc/"], "time": "18:00-08:00", "decision": "require_approval" },
+            { "name": "oncall-restarts", "actor": ["uid:1005"], "command": "^systemctl restart ", "decision": "allow" },
+            { "name": "everything-else-restarts", "command": "^systemctl restart ", "decision": "require_approval" }
+        ]
+    }"#;
+
+    #[test]
+    fn test_first_matching_rule_wins() {
+        let policy = Policy::parse(POLICY).unwrap();
+        let noon = at(12, 0);
+
+        let d = decide(&policy, "mkfs.ext4 /dev/sda1", "uid:1000", None, noon).unwrap();
+        assert_eq!((d.decision, d.rule.as_str()), (Decision::Deny, "no-disk-wipes"));
+
+        let d = decide(&policy, "wallet.send", "uid:1000", None, noon).unwrap();
+        assert_eq!(d.decision, Decision::RequireApproval { approvers: 2 });
+        assert_eq!(d.group.unwrap().members, ["uid:1001", "uid:1002", "uid:1003"]);
+        assert_eq!(decide(&policy, "wallet.send.eth", "uid:1000", None, noon).unwrap().rule, "wallet-quorum");
+        assert!(decide(&policy, "wallet.sender", "uid:1000", None, noon).is_none());
+
+        let d = decide(&policy, "systemctl restart nginx", "uid:1005", None, noon).unwrap();
+        assert_eq!((d.decision, d.rule.as_str()), (Decision::Allow, "oncall-restarts"));
+        let d = decide(&policy, "systemctl restart nginx", "uid:1000", None, noon).unwrap();
+        assert_eq!(d.rule, "everything-else-restarts");
+
+        // Actors match only the verified identity; an unidentified caller matches none
+        let verified = Policy::parse(r#"{"rules":[{"name":"me","actor":["uid:1000"],"decision":"allow"}]}"#).unwrap();
+        assert_eq!(decide(&verified, "ls", "uid:1000", None, noon).unwrap().rule, "me");
+        assert!(decide(&verified, "ls", "uid:1001", None, noon).is_none());
+        let normalized = "ls".to_string();
+        let anonymous = PolicyRequest { command: "ls", normalized: &normalized, actor: None, target: None, now: noon };
+        assert!(verified.evaluate(&anonymous).is_none());
+
+        // No match and no default: the built-in check decides
+        assert!(decide(&policy, "ls -la", "uid:1000", None, noon).is_none());
+    }
+
+    #[test]
+    fn test_path_and_time_of_day() {
+        let policy = Policy::parse(POLICY).unwrap();
+        let evening = at(22, 30);
+
+        assert_eq!(decide(&policy, "vim /etc/hosts", "uid:1000", None, evening).unwrap().rule, "etc-after-hours");
+        assert_eq!(decide(&policy, "config.write", "uid:1000", Some("/etc/nginx/nginx.conf"), at(7, 59)).unwrap().rule, "etc-after-hours");
+        assert!(decide(&policy, "vim /etc/hosts", "uid:1000", None, at(8, 0)).is_none());
+        assert!(decide(&policy, "vim /etcetera/x", "uid:1000", None, evening).is_none());
+        assert!(decide(&policy, "config.write", "uid:1000", Some("/var/etc"), evening).is_none());
+
+        // Paths come from the shell's view of the command, not whitespace
+        for command in [
+            "echo x >/etc/hosts",
+            "tee --output=/etc/hosts",
+            "dd if=/dev/zero of=/etc/hosts",
+            "cd /etc && rm -r nginx",
+            "cd /srv; cd ../etc/nginx; rm sites.conf",
+            "cat '/etc/shadow'",
+            "rm /tmp/../etc/hosts",
+        ] {
+            assert_eq!(decide(&policy, command, "uid:1000", None, evening).map(|d| d.rule), Some("etc-after-hours".into()), "{command}");
+        }
+        assert!(decide(&policy, "rm -r nginx", "uid:1000", None, evening).is_none(), "unknown directory");
+    }
+
+    #[test]
+    fn test_allow_by_path_covers_every_path() {
+        let policy = Policy::parse(r#"{"rules":[{"name":"tmp","path":["/tmp"],"decision":"allow"}]}"#).unwrap();
+        let noon = at(12, 0);
+        assert_eq!(decide(&policy, "rm -rf /tmp/build", "uid:1000", None, noon).unwrap().rule, "tmp");
+        assert_eq!(decide(&policy, "cd /tmp && rm -rf build", "uid:1000", None, noon).unwrap().rule, "tmp");
+        assert!(decide(&policy, "rm -rf /tmp/build /etc", "uid:1000", None, noon).is_none());
+        assert!(decide(&policy, "rm -rf /tmp/../etc", "uid:1000", None, noon).is_none());
+        assert!(decide(&policy, "cd /tmp && rm -rf ../etc", "uid:1000", None, noon).is_none());
+        assert!(decide(&policy, "reboot", "uid:1000", None, noon).is_none(), "no paths at all");
+    }
+
+    #[test]
+    fn test_default_decision() {
+        let policy = Policy::parse(r#"{"rules": [], "default": "deny"}"#).unwrap();
+        let d = decide(&policy, "ls", "uid:1000", None, at(12, 0)).unwrap();
+        assert_eq!((d.decision, d.rule.as_str()), (Decision::Deny, DEFAULT_RULE));
+    }
+
+    #[test]
+    fn test_invalid_policies_are_rejected() {
+        for bad in [
+            r#"{"rules":[{"name":"x","command":"(","decision":"deny"}]}"#,
+            r#"{"rules":[{"name":"x","time":"25:00-01:00","decision":"deny"}]}"#,
+            r#"{"rules":[{"name":"x","decision":"allow","approvers":2}]}"#,
+            r#"{"rules":[{"name":"x","decision":"require_approval","approvers":0}]}"#,
+            r#"{"rules":[{"name":"x","decision":"deny"},{"name":"x","decision":"allow"}]}"#,
+            r#"{"rules":[{"name":"builtin","decision":"deny"}]}"#,
+            r#"{"rules":[{"name":"x","path":["etc"],"decision":"deny"}]}"#,
+            r#"{"rules":[{"name":"x","decison":"deny"}]}"#,
+            r#"{"rules":[{"name":"x","decision":"require_approval","approver_group":"nobody"}]}"#,
+            r#"{"groups":{"g":["uid:1"]},"rules":[{"name":"x","decision":"deny","approver_group":"g"}]}"#,
+            r#"{"groups":{"g":["uid:1"]},"rules":[{"name":"x","decision":"require_approval","approvers":2,"approver_group":"g"}]}"#,
+            r#"{"groups":{"g":["uid:1","uid:1"]},"rules":[]}"#,
+            r#"{"groups":{"g":["alice"]},"rules":[]}"#,
+            r#"{"rules":[{"name":"x","actor":["oncall"],"decision":"allow"}]}"#,
+            r#"{"groups":{"g":[]},"rules":[]}"#,
+        ] {
+            assert!(Policy::parse(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_store_reloads_changed_file_and_keeps_last_good() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("policy.json");
+        std::fs::write(&path, r#"{"rules":[{"name":"a","decision":"allow"}]}"#).unwrap();
+        let store = PolicyStore::load(&path).unwrap();
+        assert!(store.reload_if_changed().unwrap().is_none());
+
+        let bump = |secs| {
+            let file = std::fs::File::options().write(true).open(&path).unwrap();
+            file.set_modified(SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(secs)).unwrap();
+        };
+        std::fs::write(&path, r#"{"rules":[{"name":"a","decision":"deny"},{"name":"b","decision":"allow"}]}"#).unwrap();
+        bump(1_000);
+        assert_eq!(store.reload_if_changed().unwrap().unwrap().rule_count(), 2);
+
+        std::fs::write(&path, "{ not json").unwrap();
+        bump(2_000);
+        assert!(store.reload_if_changed().is_err());
+        assert_eq!(store.current().rule_count(), 2, "broken file keeps the last good policy");
+        assert!(store.reload_if_changed().unwrap().is_none(), "unchanged broken file is not retried");
+    }
+}
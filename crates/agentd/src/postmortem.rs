@@ -0,0 +1,288 @@
+//! Markdown postmortems rendered from an incident workspace and the ledger.
+//!
+//! Everything in a report comes from recorded state — the incident row, its steps,
+//! the events behind linked receipts and the ledger events in the incident's window —
+//! and nothing (not even the render time) comes from the moment of rendering, so a
+//! resolved incident always renders the same document. `agentctl incident report`
+//! fetches it from GET /incident/{id}/report rather than rendering its own.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use anyhow::Result;
+
+use crate::ledger::{Event, IncidentRow, Ledger};
+
+/// Most actor events listed in the audit trail; the earliest ones are kept.
+pub const MAX_TRAIL_EVENTS: i64 = 1000;
+
+/// Payload characters shown per audit trail row.
+const PAYLOAD_PREVIEW_CHARS: usize = 120;
+
+#[derive(Debug)]
+pub struct Postmortem {
+    pub incident: IncidentRow,
+    /// Each distinct step `receipt_id`, in step order, with the event it points at.
+    pub receipts: Vec<(String, Option<Event>)>,
+    /// Actors whose events in the incident window make up the audit trail.
+    pub actors: Vec<String>,
+    /// The incident's own events plus the actors' events in its window, oldest first.
+    pub trail: Vec<Event>,
+    /// More than `MAX_TRAIL_EVENTS` actor events matched.
+    pub truncated: bool,
+}
+
+/// Event id of a receipt id as issued by GET /receipts (`receipt-{event_id}`).
+pub fn receipt_event_id(receipt_id: &str) -> Option<i64> {
+    receipt_id.strip_prefix("receipt-")?.parse().ok()
+}
+
+/// Collect what a postmortem for `incident_id` needs. The affected actors are the
+/// actors of linked receipts, whoever drove the incident's own events (their verified
+/// actor — agentd writes them on the caller's behalf), the assignee and the affected
+/// services. The window runs from creation to the last resolution, or is open-ended.
+pub fn gather(ledger: &Ledger, incident_id: &str) -> Result<Option<Postmortem>> {
+    let Some(incident) = ledger.get_incident(incident_id)? else {
+        return Ok(None);
+    };
+
+    let mut actors = BTreeSet::new();
+    let mut receipts: Vec<(String, Option<Event>)> = Vec::new();
+    for receipt_id in incident.steps.iter().filter_map(|s| s.receipt_id.as_deref()) {
+        if receipts.iter().any(|(id, _)| id == receipt_id) {
+            continue;
+        }
+        let event = match receipt_event_id(receipt_id) {
+            Some(event_id) => ledger.get_event(event_id)?,
+            None => None,
+        };
+        if let Some(event) = &event {
+            actors.insert(event.actor.clone());
+            actors.extend(event.verified_actor.clone());
+        }
+        receipts.push((receipt_id.to_string(), event));
+    }
+
+    let own = ledger.incident_events(incident_id)?;
+    actors.extend(own.iter().filter_map(|e| e.verified_actor.clone()));
+    actors.extend(incident.assignee.clone());
+    actors.extend(incident.affected_services.iter().cloned());
+    let actors: Vec<String> = actors.into_iter().collect();
+
+    let mut window = ledger.events_by_actors(
+        &actors,
+        &incident.created_at,
+        incident.resolved_at.as_deref(),
+        MAX_TRAIL_EVENTS + 1,
+    )?;
+    let truncated = window.len() as i64 > MAX_TRAIL_EVENTS;
+    window.truncate(MAX_TRAIL_EVENTS as usize);
+
+    let mut trail: BTreeMap<i64, Event> = own.into_iter().map(|e| (e.id, e)).collect();
+    trail.extend(window.into_iter().map(|e| (e.id, e)));
+
+    Ok(Some(Postmortem {
+        incident,
+        receipts,
+        actors,
+        trail: trail.into_values().collect(),
+        truncated,
+    }))
+}
+
+/// Render a postmortem as Markdown.
+pub fn render(report: &Postmortem) -> String {
+    let incident = &report.incident;
+    let mut out = String::new();
+
+    let _ = writeln!(out, "# Postmortem: {}\n", cell(&incident.name));
+    out.push_str("| Field | Value |\n|---|---|\n");
+    let services = if incident.affected_services.is_empty() {
+        "none".to_string()
+    } else {
+        incident.affected_services.iter().map(|s| format!("`{s}`")).collect::<Vec<_>>().join(", ")
+    };
+    let fields = [
+        ("Incident", format!("`{}`", incident.id)),
+        ("Status", incident.status.clone()),
+        ("Severity", incident.severity.clone()),
+        ("Affected services", services),
+        ("Assignee", incident.assignee.as_deref().map_or("unassigned".into(), |a| format!("`{a}`"))),
+        ("Opened", incident.created_at.clone()),
+        ("Resolved", incident.resolved_at.clone().unwrap_or_else(|| "unresolved".into())),
+        ("Time to resolve", incident.time_to_resolve_secs.map_or("—".into(), format_duration)),
+        ("Reopened", format!("{} time(s)", incident.reopen_count)),
+    ];
+    for (field, value) in fields {
+        let _ = writeln!(out, "| {field} | {} |", cell(&value));
+    }
+
+    out.push_str("\n## Resolution\n\n");
+    match &incident.resolution {
+        Some(resolution) => { let _ = writeln!(out, "{resolution}"); }
+        None => out.push_str("_Not resolved._\n"),
+    }
+
+    out.push_str("\n## Timeline\n\n");
+    if incident.steps.is_empty() {
+        out.push_str("_No steps recorded._\n");
+    } else {
+        out.push_str("| # | Time | Action | Result | Receipt |\n|---|---|---|---|---|\n");
+        for step in &incident.steps {
+            let receipt = step.receipt_id.as_deref().map_or(String::new(), |r| format!("`{r}`"));
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} | {} |",
+                step.step_number,
+                step.timestamp,
+                cell(&step.action),
+                cell(&step.result),
+                cell(&receipt),
+            );
+        }
+    }
+
+    out.push_str("\n## Linked receipts\n");
+    if report.receipts.is_empty() {
+        out.push_str("\n_No step references a receipt._\n");
+    }
+    for (receipt_id, event) in &report.receipts {
+        let _ = writeln!(out, "\n### `{receipt_id}`\n");
+        let Some(event) = event else {
+            out.push_str("_No matching ledger event._\n");
+            continue;
+        };
+        let _ = writeln!(out, "- Event: #{} `{}` at {}", event.id, event.event_type, event.ts);
+        let _ = writeln!(out, "- Actor: `{}`", event.actor);
+        if let Some(verified) = &event.verified_actor {
+            let _ = writeln!(out, "- Verified actor: `{verified}`");
+        }
+        let _ = writeln!(out, "- Hash: `{}`", event.hash);
+        let payload = serde_json::from_str::<serde_json::Value>(&event.payload)
+            .and_then(|v| serde_json::to_string_pretty(&v))
+            .unwrap_or_else(|_| event.payload.clone());
+        let _ = writeln!(out, "\n```json\n{payload}\n```");
+    }
+
+    out.push_str("\n## Audit trail\n\n");
+    let until = incident.resolved_at.as_deref().unwrap_or("now (incident open)");
+    let actors = if report.actors.is_empty() {
+        "no affected actors".to_string()
+    } else {
+        report.actors.iter().map(|a| format!("`{a}`")).collect::<Vec<_>>().join(", ")
+    };
+    let _ = writeln!(
+        out,
+        "The incident's own events, plus ledger events from {} to {until} by {actors}.\n",
+        incident.created_at,
+    );
+    out.push_str("| Event | Time | Type | Actor | Verified actor | Payload |\n|---|---|---|---|---|---|\n");
+    for event in &report.trail {
+        let _ = writeln!(
+            out,
+            "| #{} | {} | `{}` | `{}` | {} | {} |",
+            event.id,
+            event.ts,
+            event.event_type,
+            cell(&event.actor),
+            event.verified_actor.as_deref().map_or(String::new(), |v| format!("`{}`", cell(v))),
+            cell(&preview(&event.payload)),
+        );
+    }
+    if report.truncated {
+        let _ = writeln!(out, "\n_Only the first {MAX_TRAIL_EVENTS} actor events in the window are listed._");
+    }
+
+    if let (Some(first), Some(last)) = (report.trail.first(), report.trail.last()) {
+        let _ = writeln!(
+            out,
+            "\n---\n\nRendered from ledger events #{}–#{}. Check them against the hash chain with `agentctl verify-ledger`.",
+            first.id, last.id,
+        );
+    }
+    out
+}
+
+/// Make text safe inside a Markdown table cell.
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+fn preview(payload: &str) -> String {
+    match payload.char_indices().nth(PAYLOAD_PREVIEW_CHARS) {
+        Some((end, _)) => format!("{}…", &payload[..end]),
+        None => payload.to_string(),
+    }
+}
+
+/// `3725` → `1h 2m 5s`; leading zero units are dropped.
+fn format_duration(secs: i64) -> String {
+    let units = [("d", secs / 86_400), ("h", secs % 86_400 / 3600), ("m", secs % 3600 / 60), ("s", secs % 60)];
+    let parts: Vec<String> = units
+        .iter()
+        .skip_while(|(_, n)| *n == 0)
+        .map(|(unit, n)| format!("{n}{unit}"))
+        .collect();
+    if parts.is_empty() { "0s".into() } else { parts.join(" ") }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::{IncidentFields, NewIncidentStep};
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(0), "0s");
+        assert_eq!(format_duration(45), "45s");
+        assert_eq!(format_duration(3725), "1h 2m 5s");
+        assert_eq!(format_duration(90_000), "1d 1h 0m 0s");
+    }
+
+    #[test]
+    fn test_report_links_receipts_and_actor_events() {
+        let ledger = Ledger::new(":memory:").unwrap();
+        let fields = IncidentFields {
+            severity: "high".into(),
+            affected_services: vec!["nginx".into()],
+            assignee: None,
+            ..Default::default()
+        };
+        ledger.create_incident("inc-1", "nginx down", &fields, Some("uid:1000")).unwrap();
+        let receipt = ledger.append("switch.rollback", "osmoda-deploy", r#"{"summary":"rolled back"}"#).unwrap();
+        let unrelated = ledger.append("config.change", "someone-else", "{}").unwrap();
+        let service = ledger.append("service.restart", "nginx", r#"{"summary":"restarted"}"#).unwrap();
+        let receipt_id = format!("receipt-{}", receipt.id);
+        let step = NewIncidentStep { action: "rollback", result: "ok | healthy", receipt_id: Some(&receipt_id) };
+        ledger.add_incident_step("inc-1", &step, None).unwrap();
+        let step = NewIncidentStep { action: "check", result: "fine", receipt_id: Some("receipt-999999") };
+        ledger.add_incident_step("inc-1", &step, None).unwrap();
+        ledger.resolve_incident("inc-1", "Rolled back the bad switch", Some("uid:1000")).unwrap();
+        // Timestamps have millisecond resolution
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let after = ledger.append("switch.commit", "osmoda-deploy", "{}").unwrap();
+
+        let report = gather(&ledger, "inc-1").unwrap().unwrap();
+        assert_eq!(report.actors, vec!["nginx", "osmoda-deploy", "uid:1000"]);
+        assert_eq!(report.receipts.len(), 2);
+        assert_eq!(report.receipts[0].1.as_ref().map(|e| e.id), Some(receipt.id));
+        assert!(report.receipts[1].1.is_none());
+
+        let ids: Vec<i64> = report.trail.iter().map(|e| e.id).collect();
+        assert!(ids.contains(&receipt.id) && ids.contains(&service.id));
+        assert!(!ids.contains(&unrelated.id), "other actors are not in the trail");
+        assert!(!ids.contains(&after.id), "events after resolution are outside the window");
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+        let types: Vec<&str> = report.trail.iter().map(|e| e.event_type.as_str()).collect();
+        assert!(types.contains(&"incident.create") && types.contains(&"incident.resolve"));
+
+        let markdown = render(&report);
+        assert!(markdown.starts_with("# Postmortem: nginx down\n"));
+        assert!(markdown.contains("Rolled back the bad switch"));
+        assert!(markdown.contains("| 1 | "));
+        assert!(markdown.contains("ok \\| healthy"));
+        assert!(markdown.contains(&format!("### `{receipt_id}`")));
+        assert!(markdown.contains("_No matching ledger event._"));
+        assert_eq!(render(&gather(&ledger, "inc-1").unwrap().unwrap()), markdown, "rendering is reproducible");
+    }
+}
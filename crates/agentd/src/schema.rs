@@ -0,0 +1,394 @@
+use serde::Serialize;
+use serde_json::Value;
+
+/// JSON type expected for a payload field.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldType {
+    String,
+    Integer,
+    Boolean,
+    Array,
+    /// Any JSON value (free-form metadata).
+    Any,
+}
+
+impl FieldType {
+    fn name(self) -> &'static str {
+        match self {
+            FieldType::String => "string",
+            FieldType::Integer => "integer",
+            FieldType::Boolean => "boolean",
+            FieldType::Array => "array",
+            FieldType::Any => "any",
+        }
+    }
+
+    fn matches(self, value: &Value) -> bool {
+        match self {
+            FieldType::String => value.is_string(),
+            FieldType::Integer => value.is_i64() || value.is_u64(),
+            FieldType::Boolean => value.is_boolean(),
+            FieldType::Array => value.is_array(),
+            FieldType::Any => true,
+        }
+    }
+}
+
+/// One named field in an event payload.
+#[derive(Debug, Clone, Serialize)]
+pub struct Field {
+    pub name: &'static str,
+    #[serde(rename = "type")]
+    pub field_type: FieldType,
+    /// Must be present in the payload.
+    pub required: bool,
+    /// May be JSON null.
+    pub nullable: bool,
+}
+
+/// Payload schema for an event type, or a family of types when `event_type` ends in `.*`.
+/// Extra fields beyond those listed are allowed so producers can add data compatibly.
+#[derive(Debug, Clone, Serialize)]
+pub struct EventSchema {
+    pub event_type: &'static str,
+    pub description: &'static str,
+    pub fields: &'static [Field],
+}
+
+/// Outcome of checking a payload against the registry.
+#[derive(Debug, PartialEq)]
+pub enum Validation {
+    Valid,
+    /// No schema registered for this type — allowed, but flagged.
+    Unregistered,
+    Invalid(Vec<String>),
+}
+
+const fn req(name: &'static str, field_type: FieldType) -> Field {
+    Field { name, field_type, required: true, nullable: false }
+}
+
+const fn opt(name: &'static str, field_type: FieldType) -> Field {
+    Field { name, field_type, required: false, nullable: true }
+}
+
+/// Every event type agentd itself writes. Keep in sync with the `ledger.append` call sites.
+pub static REGISTRY: &[EventSchema] = &[
+    EventSchema {
+        event_type: "daemon.start",
+        description: "agentd started",
+        fields: &[req("socket", FieldType::String), req("state_dir", FieldType::String)],
+    },
+    EventSchema {
+        event_type: "system.query",
+        description: "Structured system query via /system/query",
+        fields: &[req("query", FieldType::String), opt("args", FieldType::Any)],
+    },
+    EventSchema {
+        event_type: "system.discover",
+        description: "Service discovery scan",
+        fields: &[
+            req("services_found", FieldType::Integer),
+            req("listening_ports", FieldType::Integer),
+            req("systemd_services", FieldType::Integer),
+        ],
+    },
+    EventSchema {
+        event_type: "memory.ingest",
+        description: "Memory ingested from a daemon or the agent (actor = source)",
+        fields: &[
+            req("content", FieldType::String),
+            opt("source", FieldType::String),
+            opt("category", FieldType::String),
+            opt("tags", FieldType::Array),
+            opt("metadata", FieldType::Any),
+            opt("namespace", FieldType::String),
+            opt("ttl_secs", FieldType::Integer),
+            opt("consolidates", FieldType::Array),
+        ],
+    },
+    EventSchema {
+        event_type: "memory.store",
+        description: "Explicit memory stored by the agent",
+        fields: &[
+            req("summary", FieldType::String),
+            opt("detail", FieldType::String),
+            opt("category", FieldType::String),
+            opt("tags", FieldType::Array),
+            opt("namespace", FieldType::String),
+            opt("ttl_secs", FieldType::Integer),
+        ],
+    },
+    EventSchema {
+        event_type: "memory.forget",
+        description: "Memories removed from the memory store via /memory/forget",
+        fields: &[
+            req("event_ids", FieldType::Array),
+            req("reason", FieldType::String),
+            opt("namespace", FieldType::String),
+        ],
+    },
+    EventSchema {
+        event_type: "memory.expire",
+        description: "Memories past their TTL removed from the memory store",
+        fields: &[req("namespace", FieldType::String), req("event_ids", FieldType::Array)],
+    },
+    EventSchema {
+        event_type: "memory.pin",
+        description: "Memory pinned (exempt from expiry) or unpinned",
+        fields: &[req("event_id", FieldType::Integer), req("pinned", FieldType::Boolean)],
+    },
+    EventSchema {
+        event_type: "agent.card.generate",
+        description: "EIP-8004 agent card regenerated",
+        fields: &[req("name", FieldType::String), req("services", FieldType::Integer)],
+    },
+    EventSchema {
+        event_type: "backup.create",
+        description: "State directory backup written",
+        fields: &[
+            req("backup_id", FieldType::String),
+            req("path", FieldType::String),
+            req("size_bytes", FieldType::Integer),
+        ],
+    },
+    EventSchema {
+        event_type: "backup.restore",
+        description: "State directory restore started",
+        fields: &[req("backup_id", FieldType::String), req("path", FieldType::String)],
+    },
+    EventSchema {
+        event_type: "incident.create",
+        description: "Incident workspace opened",
+        fields: &[
+            req("incident_id", FieldType::String),
+            req("name", FieldType::String),
+            opt("severity", FieldType::String),
+            opt("affected_services", FieldType::Array),
+            opt("assignee", FieldType::String),
+            opt("correlation_key", FieldType::String),
+        ],
+    },
+    EventSchema {
+        event_type: "incident.update",
+        description: "Incident severity, affected services or assignee changed (changed fields only)",
+        fields: &[
+            req("incident_id", FieldType::String),
+            opt("severity", FieldType::String),
+            opt("affected_services", FieldType::Array),
+            opt("assignee", FieldType::String),
+        ],
+    },
+    EventSchema {
+        event_type: "incident.resolve",
+        description: "Incident resolved with a resolution summary",
+        fields: &[
+            req("incident_id", FieldType::String),
+            req("resolution", FieldType::String),
+            req("time_to_resolve_secs", FieldType::Integer),
+        ],
+    },
+    EventSchema {
+        event_type: "incident.reopen",
+        description: "Resolved incident reopened",
+        fields: &[
+            req("incident_id", FieldType::String),
+            req("reason", FieldType::String),
+            opt("previous_resolution", FieldType::String),
+        ],
+    },
+    EventSchema {
+        event_type: "incident.step",
+        description: "Step recorded in an incident workspace",
+        fields: &[
+            req("incident_id", FieldType::String),
+            req("step_number", FieldType::Integer),
+            req("action", FieldType::String),
+            req("result", FieldType::String),
+        ],
+    },
+    EventSchema {
+        event_type: "sandbox.exec",
+        description: "Command run in the sandbox engine",
+        fields: &[
+            req("command", FieldType::String),
+            req("ring", FieldType::String),
+            req("network", FieldType::Boolean),
+        ],
+    },
+    EventSchema {
+        event_type: "capability.mint",
+        description: "Capability token minted",
+        fields: &[
+            req("token_id", FieldType::String),
+            req("granted_to", FieldType::String),
+            req("permissions", FieldType::Array),
+            req("ttl_secs", FieldType::Integer),
+        ],
+    },
+    EventSchema {
+        event_type: "approval.*",
+        description: "Approval gate lifecycle (requested, vote, approved, denied, executed)",
+        fields: &[
+            req("approval_id", FieldType::String),
+            req("command", FieldType::String),
+            opt("target", FieldType::String),
+            opt("token_id", FieldType::String),
+            opt("reason", FieldType::String),
+            opt("decided_by", FieldType::String),
+            opt("rule", FieldType::String),
+            opt("required_approvals", FieldType::Integer),
+            opt("approver_group", FieldType::String),
+            opt("approvers", FieldType::Array),
+        ],
+    },
+    EventSchema {
+        event_type: "approval.notify",
+        description: "New approval request announced to a notification sink, or the failed attempt",
+        fields: &[
+            req("approval_id", FieldType::String),
+            req("command", FieldType::String),
+            req("sink", FieldType::String),
+            req("destination", FieldType::String),
+            opt("delivered", FieldType::Boolean),
+            opt("error", FieldType::String),
+        ],
+    },
+    EventSchema {
+        event_type: "approval.policy",
+        description: "Approval policy rule allowed or denied a request outright",
+        fields: &[
+            req("command", FieldType::String),
+            req("decision", FieldType::String),
+            req("rule", FieldType::String),
+            opt("target", FieldType::String),
+        ],
+    },
+    EventSchema {
+        event_type: "policy.reload",
+        description: "Approval policy file loaded at startup or reloaded after a change",
+        fields: &[
+            req("path", FieldType::String),
+            req("rule_count", FieldType::Integer),
+            req("sha256", FieldType::String),
+        ],
+    },
+    EventSchema {
+        event_type: "ledger.export",
+        description: "Audit history exported via /ledger/export",
+        fields: &[
+            req("event_count", FieldType::Integer),
+            req("incident_count", FieldType::Integer),
+            req("head_hash", FieldType::String),
+        ],
+    },
+    EventSchema {
+        event_type: "ledger.archive",
+        description: "Expired events moved into a sealed archive segment",
+        fields: &[
+            req("file", FieldType::String),
+            req("first_event_id", FieldType::Integer),
+            req("last_event_id", FieldType::Integer),
+            req("sha256", FieldType::String),
+        ],
+    },
+    EventSchema {
+        event_type: "ledger.redact",
+        description: "Event payload replaced by a tombstone via /ledger/redact/{id}",
+        fields: &[
+            req("event_id", FieldType::Integer),
+            req("payload_digest", FieldType::String),
+            req("reason", FieldType::String),
+            opt("approval_id", FieldType::String),
+        ],
+    },
+];
+
+/// Find the schema for an event type: exact match first, then the longest `prefix.*` family.
+pub fn lookup(event_type: &str) -> Option<&'static EventSchema> {
+    if let Some(exact) = REGISTRY.iter().find(|s| s.event_type == event_type) {
+        return Some(exact);
+    }
+    REGISTRY
+        .iter()
+        .filter_map(|s| s.event_type.strip_suffix('*').map(|prefix| (prefix, s)))
+        .filter(|(prefix, _)| event_type.starts_with(prefix))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, s)| s)
+}
+
+/// Check a payload string against the schema registered for its event type.
+pub fn validate(event_type: &str, payload: &str) -> Validation {
+    let Some(schema) = lookup(event_type) else {
+        return Validation::Unregistered;
+    };
+
+    let value: Value = match serde_json::from_str(payload) {
+        Ok(v) => v,
+        Err(e) => return Validation::Invalid(vec![format!("payload is not valid JSON: {e}")]),
+    };
+    let Some(object) = value.as_object() else {
+        return Validation::Invalid(vec!["payload must be a JSON object".to_string()]);
+    };
+
+    let mut errors = Vec::new();
+    for field in schema.fields {
+        match object.get(field.name) {
+            None if field.required => errors.push(format!("missing required field '{}'", field.name)),
+            None => {}
+            Some(Value::Null) if field.nullable => {}
+            Some(v) if field.field_type.matches(v) => {}
+            Some(_) => errors.push(format!(
+                "field '{}' must be {}",
+                field.name,
+                field.field_type.name()
+            )),
+        }
+    }
+
+    if errors.is_empty() {
+        Validation::Valid
+    } else {
+        Validation::Invalid(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_payload_valid() {
+        let payload = r#"{"backup_id":"b1","path":"/var/backups/b1.tar.gz","size_bytes":42}"#;
+        assert_eq!(validate("backup.create", payload), Validation::Valid);
+    }
+
+    #[test]
+    fn test_missing_and_mistyped_fields() {
+        let Validation::Invalid(errors) = validate("backup.create", r#"{"backup_id":"b1","size_bytes":"big"}"#) else {
+            panic!("expected invalid");
+        };
+        assert_eq!(errors.len(), 2);
+        assert!(errors.iter().any(|e| e.contains("'path'")));
+        assert!(errors.iter().any(|e| e.contains("'size_bytes'")));
+
+        assert!(matches!(validate("backup.create", "not json"), Validation::Invalid(_)));
+        assert!(matches!(validate("backup.create", "[1,2]"), Validation::Invalid(_)));
+    }
+
+    #[test]
+    fn test_prefix_family_and_nullable() {
+        let payload = r#"{"approval_id":"a1","command":"rm -rf /tmp/x","decided_by":null}"#;
+        assert_eq!(validate("approval.denied", payload), Validation::Valid);
+        assert_eq!(lookup("approval.requested").unwrap().event_type, "approval.*");
+
+        let ingest = r#"{"source":null,"content":"hi","category":null,"tags":null,"metadata":null}"#;
+        assert_eq!(validate("memory.ingest", ingest), Validation::Valid);
+    }
+
+    #[test]
+    fn test_unregistered_type_is_flagged_not_rejected() {
+        assert_eq!(validate("custom.thing", "free-form text"), Validation::Unregistered);
+    }
+}
@@ -0,0 +1,731 @@
+//! Shell-aware splitting of commands for the approval gate.
+//!
+//! A command is tokenized the way a POSIX shell reads it — quotes, escapes, comments,
+//! redirections, here-documents and `$(...)`/backtick substitutions — and split into
+//! simple commands on `;`, `&`, `&&`, `||`, `|`, parentheses and newlines. The
+//! bodies of unquoted here-documents are scanned for substitutions like the rest. Programs
+//! that run another program (`sudo`, `env`, `nice`, `timeout`, `xargs`, `find -exec`,
+//! `sh -c`, `eval`, …) are resolved, so each [`Invocation`] names the program that
+//! actually runs and the arguments it gets. Substitutions and `sh -c` scripts are
+//! analysed as commands of their own.
+
+use anyhow::{bail, Result};
+
+/// How deep substitutions, `sh -c` scripts and `eval` may nest before we give up.
+const MAX_DEPTH: usize = 8;
+
+/// Words that can precede a command without being one.
+const KEYWORDS: &[&str] = &["!", "{", "}", "if", "then", "else", "elif", "fi", "do", "done", "while", "until"];
+
+/// Shells that run a script given with `-c`, or read one from stdin.
+pub const SHELLS: &[&str] = &["sh", "bash", "dash", "zsh", "ksh", "mksh", "ash", "fish"];
+
+/// One program a command line would run.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Invocation {
+    /// Lowercase file name of the program (`/usr/bin/RM` → `rm`).
+    pub binary: String,
+    /// Arguments after quote removal, as given.
+    pub args: Vec<String>,
+    /// Targets of output redirections (`>`, `>>`, `&>`, `>|`).
+    pub redirects: Vec<String>,
+    /// Reads the output of the previous pipeline stage, or a here-document or
+    /// here-string.
+    pub piped_into: bool,
+    /// Run once per input item, by `xargs` or `find -exec`.
+    pub bulk: bool,
+    /// The program name is computed (`$VAR`, `$(...)`, a glob), so it cannot be known.
+    pub dynamic: bool,
+    /// An argument before any `--` is computed, so it could expand to any flag.
+    pub dynamic_args: bool,
+}
+
+impl Invocation {
+    /// The program, its arguments and output redirections as one command line, each
+    /// word quoted so it re-tokenizes as itself. Pipes and `bulk` are not kept.
+    pub fn command_line(&self) -> String {
+        let mut line = quote(&self.binary);
+        for arg in &self.args {
+            line.push(' ');
+            line.push_str(&quote(arg));
+        }
+        for target in &self.redirects {
+            line.push_str(" > ");
+            line.push_str(&quote(target));
+        }
+        line
+    }
+}
+
+#[derive(Debug, Default)]
+struct Word {
+    text: String,
+    /// Contains a parameter expansion or command substitution.
+    dynamic: bool,
+    /// Some part of it was quoted or escaped.
+    quoted: bool,
+}
+
+/// A here-document whose body starts after the next newline.
+#[derive(Debug)]
+struct Heredoc {
+    delimiter: String,
+    /// `<<-`: leading tabs are stripped from body lines.
+    strip_tabs: bool,
+    /// A quoted delimiter makes the body literal; otherwise it is expanded.
+    quoted: bool,
+}
+
+#[derive(Debug, Default)]
+struct Simple {
+    words: Vec<Word>,
+    redirects: Vec<String>,
+    piped_into: bool,
+}
+
+/// Every program `command` would run, including those in substitutions and scripts.
+/// Input the tokenizer cannot follow (an unterminated quote or substitution, nesting
+/// deeper than `MAX_DEPTH`) is an error.
+pub fn invocations(command: &str) -> Result<Vec<Invocation>> {
+    let mut out = Vec::new();
+    expand(command, 0, false, &mut out)?;
+    Ok(out)
+}
+
+fn expand(script: &str, depth: usize, bulk: bool, out: &mut Vec<Invocation>) -> Result<()> {
+    if depth > MAX_DEPTH {
+        bail!("commands nested more than {MAX_DEPTH} deep");
+    }
+    let mut substitutions = Vec::new();
+    for simple in split(script, &mut substitutions)? {
+        resolve(simple, depth, bulk, out)?;
+    }
+    for inner in substitutions {
+        expand(&inner, depth + 1, false, out)?;
+    }
+    Ok(())
+}
+
+/// Split a script into simple commands. The bodies of command substitutions are
+/// collected into `substitutions`; in the words they stand in for they are kept
+/// verbatim and mark the word dynamic.
+fn split(script: &str, substitutions: &mut Vec<String>) -> Result<Vec<Simple>> {
+    let chars: Vec<char> = script.chars().collect();
+    let mut commands = Vec::new();
+    let mut current = Simple::default();
+    let mut word = Word::default();
+    let mut in_word = false;
+    // Some(is_output) while the next word is a redirection target
+    let mut redirect: Option<bool> = None;
+    let mut heredocs: Vec<Heredoc> = Vec::new();
+    let mut heredoc_next = None;
+
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            ' ' | '\t' | '\r' => {
+                finish_word(&mut current, &mut word, &mut in_word, &mut redirect, &mut heredoc_next, &mut heredocs);
+                i += 1;
+            }
+            '#' if !in_word => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '\n' | ';' | '&' | '|' | '(' | ')' => {
+                if c == '&' && chars.get(i + 1) == Some(&'>') {
+                    // &> and &>> redirect both stdout and stderr
+                    finish_word(&mut current, &mut word, &mut in_word, &mut redirect, &mut heredoc_next, &mut heredocs);
+                    i += 2;
+                    if chars.get(i) == Some(&'>') {
+                        i += 1;
+                    }
+                    redirect = Some(true);
+                    continue;
+                }
+                finish_word(&mut current, &mut word, &mut in_word, &mut redirect, &mut heredoc_next, &mut heredocs);
+                let doubled = matches!(c, '&' | '|') && chars.get(i + 1) == Some(&c);
+                let piped = c == '|' && !doubled;
+                i += if doubled { 2 } else { 1 };
+                if piped && chars.get(i) == Some(&'&') {
+                    i += 1;
+                }
+                end_command(&mut commands, &mut current, piped);
+                if c == '\n' {
+                    i = heredoc_bodies(&chars, i, &mut heredocs, substitutions)?;
+                }
+            }
+            '<' | '>' if chars.get(i + 1) == Some(&'(') => {
+                // Process substitution
+                let (inner, end) = capture_parens(&chars, i + 2)?;
+                word.text.extend(&chars[i..end]);
+                word.dynamic = true;
+                in_word = true;
+                substitutions.push(inner);
+                i = end;
+            }
+            '<' | '>' => {
+                // A bare number right before the operator is a file descriptor
+                if in_word && !word.text.is_empty() && word.text.chars().all(|d| d.is_ascii_digit()) {
+                    word = Word::default();
+                    in_word = false;
+                }
+                finish_word(&mut current, &mut word, &mut in_word, &mut redirect, &mut heredoc_next, &mut heredocs);
+                let output = c == '>';
+                i += 1;
+                if !output && chars.get(i) == Some(&'<') {
+                    current.piped_into = true;
+                    i += 1;
+                    if chars.get(i) == Some(&'<') {
+                        // Here-string: the word is input, not a delimiter
+                        i += 1;
+                    } else {
+                        let strip_tabs = chars.get(i) == Some(&'-');
+                        if strip_tabs {
+                            i += 1;
+                        }
+                        heredoc_next = Some(strip_tabs);
+                    }
+                } else if matches!(chars.get(i), Some('>' | '|' | '&')) {
+                    i += 1;
+                }
+                redirect = Some(output);
+            }
+            '\'' => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&q| q == '\'')
+                    .map(|p| i + 1 + p)
+                    .ok_or_else(|| anyhow::anyhow!("unterminated single quote"))?;
+                word.text.extend(&chars[i + 1..end]);
+                word.quoted = true;
+                in_word = true;
+                i = end + 1;
+            }
+            '"' => {
+                word.quoted = true;
+                in_word = true;
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => bail!("unterminated double quote"),
+                        Some('"') => {
+                            i += 1;
+                            break;
+                        }
+                        Some('\\') if matches!(chars.get(i + 1), Some('$' | '`' | '"' | '\\' | '\n')) => {
+                            if chars[i + 1] != '\n' {
+                                word.text.push(chars[i + 1]);
+                            }
+                            i += 2;
+                        }
+                        Some('$') | Some('`') => i = expansion(&chars, i, &mut word, substitutions)?,
+                        Some(&other) => {
+                            word.text.push(other);
+                            i += 1;
+                        }
+                    }
+                }
+            }
+            '\\' => {
+                match chars.get(i + 1) {
+                    // Line continuation
+                    Some('\n') => {}
+                    Some(&escaped) => {
+                        word.text.push(escaped);
+                        word.quoted = true;
+                        in_word = true;
+                    }
+                    None => {}
+                }
+                i += 2;
+            }
+            '$' | '`' => {
+                in_word = true;
+                i = expansion(&chars, i, &mut word, substitutions)?;
+            }
+            _ => {
+                word.text.push(c);
+                in_word = true;
+                i += 1;
+            }
+        }
+    }
+    finish_word(&mut current, &mut word, &mut in_word, &mut redirect, &mut heredoc_next, &mut heredocs);
+    end_command(&mut commands, &mut current, false);
+    Ok(commands)
+}
+
+fn finish_word(
+    current: &mut Simple,
+    word: &mut Word,
+    in_word: &mut bool,
+    redirect: &mut Option<bool>,
+    heredoc_next: &mut Option<bool>,
+    heredocs: &mut Vec<Heredoc>,
+) {
+    if !std::mem::take(in_word) {
+        return;
+    }
+    let word = std::mem::take(word);
+    if let Some(strip_tabs) = heredoc_next.take() {
+        heredocs.push(Heredoc { delimiter: word.text, strip_tabs, quoted: word.quoted });
+        *redirect = None;
+    } else if let Some(output) = redirect.take() {
+        if output {
+            current.redirects.push(word.text);
+        }
+    } else {
+        current.words.push(word);
+    }
+}
+
+fn end_command(commands: &mut Vec<Simple>, current: &mut Simple, piped: bool) {
+    let done = std::mem::replace(current, Simple { piped_into: piped, ..Simple::default() });
+    if !done.words.is_empty() || !done.redirects.is_empty() {
+        commands.push(done);
+    } else if done.piped_into {
+        // `a | | b` is a syntax error, but don't let an empty stage hide the pipe
+        current.piped_into = true;
+    }
+}
+
+/// Read past the bodies of pending here-documents, which start at `start` (after a
+/// newline), collecting the substitutions in those with an unquoted delimiter.
+/// Returns the index after the last body.
+fn heredoc_bodies(
+    chars: &[char],
+    start: usize,
+    heredocs: &mut Vec<Heredoc>,
+    substitutions: &mut Vec<String>,
+) -> Result<usize> {
+    let mut i = start;
+    for heredoc in heredocs.drain(..) {
+        let body_start = i;
+        let mut body_end = chars.len();
+        while i < chars.len() {
+            let end = chars[i..].iter().position(|&c| c == '\n').map_or(chars.len(), |p| i + p);
+            let line: String = chars[i..end].iter().collect();
+            let line = if heredoc.strip_tabs { line.trim_start_matches('\t') } else { line.as_str() };
+            let line_start = i;
+            i = (end + 1).min(chars.len());
+            if line == heredoc.delimiter {
+                body_end = line_start;
+                break;
+            }
+        }
+        if !heredoc.quoted {
+            let body = &chars[body_start..body_end];
+            let mut j = 0;
+            while j < body.len() {
+                match body[j] {
+                    '\\' => j += 2,
+                    '$' | '`' => j = expansion(body, j, &mut Word::default(), substitutions)?,
+                    _ => j += 1,
+                }
+            }
+        }
+    }
+    Ok(i)
+}
+
+/// Read a `$...` or backtick expansion starting at `start` into `word`; returns the
+/// index after it.
+fn expansion(chars: &[char], start: usize, word: &mut Word, substitutions: &mut Vec<String>) -> Result<usize> {
+    let end = if chars[start] == '`' {
+        let mut i = start + 1;
+        let mut inner = String::new();
+        loop {
+            match chars.get(i) {
+                None => bail!("unterminated backtick substitution"),
+                Some('`') => break,
+                Some('\\') if i + 1 < chars.len() => {
+                    inner.push(chars[i + 1]);
+                    i += 2;
+                }
+                Some(&c) => {
+                    inner.push(c);
+                    i += 1;
+                }
+            }
+        }
+        substitutions.push(inner);
+        i + 1
+    } else {
+        match chars.get(start + 1) {
+            // $(( arithmetic )) runs no commands; $( command ) does
+            Some('(') => {
+                let (inner, end) = capture_parens(chars, start + 2)?;
+                if !inner.starts_with('(') {
+                    substitutions.push(inner);
+                }
+                end
+            }
+            Some('{') => {
+                let close = chars[start + 2..]
+                    .iter()
+                    .position(|&c| c == '}')
+                    .ok_or_else(|| anyhow::anyhow!("unterminated parameter expansion"))?;
+                start + 2 + close + 1
+            }
+            Some(c) if c.is_ascii_alphabetic() || *c == '_' => {
+                let len = chars[start + 1..].iter().take_while(|c| c.is_ascii_alphanumeric() || **c == '_').count();
+                start + 1 + len
+            }
+            Some(c) if c.is_ascii_digit() || "@*#?$!-".contains(*c) => start + 2,
+            _ => {
+                word.text.push('$');
+                return Ok(start + 1);
+            }
+        }
+    };
+    word.text.extend(&chars[start..end]);
+    word.dynamic = true;
+    Ok(end)
+}
+
+/// The text up to the `)` that balances an already-consumed `(`, skipping quoted
+/// parts. Returns it and the index after the `)`.
+fn capture_parens(chars: &[char], start: usize) -> Result<(String, usize)> {
+    let mut depth = 1;
+    let mut i = start;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 1,
+            '\'' => {
+                i += chars[i + 1..]
+                    .iter()
+                    .position(|&q| q == '\'')
+                    .ok_or_else(|| anyhow::anyhow!("unterminated single quote"))?
+                    + 1;
+            }
+            '"' => {
+                i += 1;
+                while i < chars.len() && chars[i] != '"' {
+                    if chars[i] == '\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+            }
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok((chars[start..i].iter().collect(), i + 1));
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    bail!("unterminated command substitution")
+}
+
+/// Resolve wrappers in front of a simple command and record what it runs.
+fn resolve(simple: Simple, depth: usize, mut bulk: bool, out: &mut Vec<Invocation>) -> Result<()> {
+    let Simple { words, redirects, piped_into } = simple;
+    let mut rest: &[Word] = &words;
+
+    loop {
+        while rest.first().is_some_and(|w| is_assignment(&w.text) || KEYWORDS.contains(&w.text.as_str())) {
+            rest = &rest[1..];
+        }
+        let Some(first) = rest.first() else {
+            if !redirects.is_empty() {
+                out.push(Invocation { redirects, piped_into, bulk, ..Invocation::default() });
+            }
+            return Ok(());
+        };
+        let binary = basename(&first.text);
+        let args = &rest[1..];
+        let invocation = || Invocation {
+            binary: binary.clone(),
+            args: args.iter().map(|w| w.text.clone()).collect(),
+            redirects: redirects.clone(),
+            piped_into,
+            bulk,
+            dynamic: first.dynamic || first.text.contains(['*', '?']),
+            dynamic_args: args.iter().take_while(|w| w.text != "--").any(|w| w.dynamic),
+        };
+
+        rest = match binary.as_str() {
+            "sudo" | "doas" => skip_options(
+                args,
+                &["-u", "-g", "-h", "-p", "-c", "-C", "-D", "-r", "-t", "-T", "-U", "-R",
+                  "--user", "--group", "--host", "--prompt", "--close-from", "--chdir", "--role", "--type",
+                  "--other-user", "--chroot", "--command-timeout"],
+            ),
+            "nice" => skip_options(args, &["-n", "--adjustment"]),
+            "ionice" => skip_options(args, &["-c", "-n", "-p", "-P", "-u", "--class", "--classdata"]),
+            "stdbuf" => skip_options(args, &["-i", "-o", "-e", "--input", "--output", "--error"]),
+            "nohup" | "time" | "builtin" | "setsid" | "unbuffer" | "busybox" | "command" => skip_options(args, &[]),
+            "exec" => skip_options(args, &["-a"]),
+            "timeout" => skip_positional(skip_options(args, &["-s", "-k", "--signal", "--kill-after"]), 1),
+            "chrt" => skip_positional(skip_options(args, &[]), 1),
+            "taskset" => skip_positional(skip_options(args, &[]), 1),
+            "chroot" => skip_positional(skip_options(args, &["--userspec", "--groups"]), 1),
+            "flock" => skip_positional(skip_options(args, &["-w", "-E", "--timeout", "--conflict-exit-code"]), 1),
+            "xargs" => {
+                bulk = true;
+                skip_options(
+                    args,
+                    &["-a", "-d", "-E", "-I", "-L", "-n", "-P", "-s",
+                      "--arg-file", "--delimiter", "--max-lines", "--max-args", "--max-procs", "--max-chars"],
+                )
+            }
+            "env" => {
+                let (script, after) = env_split_string(args);
+                match script {
+                    Some(script) => {
+                        out.push(invocation());
+                        let mut line = script;
+                        for word in after {
+                            line.push(' ');
+                            line.push_str(&quote(&word.text));
+                        }
+                        return expand(&line, depth + 1, bulk, out);
+                    }
+                    None => after,
+                }
+            }
+            "eval" => {
+                out.push(invocation());
+                let line = args.iter().map(|w| w.text.as_str()).collect::<Vec<_>>().join(" ");
+                return expand(&line, depth + 1, bulk, out);
+            }
+            "watch" => {
+                out.push(invocation());
+                let command = skip_options(args, &["-n", "-d", "-q", "--interval", "--differences"]);
+                let line = command.iter().map(|w| w.text.as_str()).collect::<Vec<_>>().join(" ");
+                return expand(&line, depth + 1, bulk, out);
+            }
+            "su" | "runuser" => {
+                let mut i = 0;
+                while let Some(arg) = args.get(i).map(|w| w.text.as_str()) {
+                    if arg == "-c" || arg == "--command" {
+                        out.push(invocation());
+                        return match args.get(i + 1) {
+                            Some(script) => expand(&script.text, depth + 1, bulk, out),
+                            None => Ok(()),
+                        };
+                    }
+                    if let Some(script) = arg.strip_prefix("--command=") {
+                        out.push(invocation());
+                        return expand(script, depth + 1, bulk, out);
+                    }
+                    if arg == "--" || !arg.starts_with('-') {
+                        break;
+                    }
+                    i += if ["-u", "-g", "-G", "-s", "--user", "--group", "--supp-group", "--shell"].contains(&arg) { 2 } else { 1 };
+                }
+                if binary == "runuser" {
+                    // runuser -u <user> [--] <command>
+                    skip_options(args, &["-u", "-g", "-G", "--user", "--group", "--supp-group"])
+                } else {
+                    out.push(invocation());
+                    return Ok(());
+                }
+            }
+            "find" => {
+                out.push(invocation());
+                let mut i = 0;
+                while i < args.len() {
+                    if ["-exec", "-execdir", "-ok", "-okdir"].contains(&args[i].text.as_str()) {
+                        let end = args[i + 1..]
+                            .iter()
+                            .position(|w| w.text == ";" || w.text == "+")
+                            .map_or(args.len(), |p| i + 1 + p);
+                        let words = args[i + 1..end]
+                            .iter()
+                            .map(|w| Word { text: w.text.clone(), dynamic: w.dynamic, quoted: w.quoted })
+                            .collect();
+                        resolve(Simple { words, ..Simple::default() }, depth + 1, true, out)?;
+                        i = end;
+                    }
+                    i += 1;
+                }
+                return Ok(());
+            }
+            shell if SHELLS.contains(&shell) => {
+                if let Some(script) = shell_script(args) {
+                    out.push(invocation());
+                    return expand(script, depth + 1, bulk, out);
+                }
+                out.push(invocation());
+                return Ok(());
+            }
+            _ => {
+                out.push(invocation());
+                return Ok(());
+            }
+        };
+    }
+}
+
+/// `NAME=value` in front of a command.
+fn is_assignment(word: &str) -> bool {
+    word.split_once('=').is_some_and(|(name, _)| {
+        name.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    })
+}
+
+fn basename(word: &str) -> String {
+    word.rsplit('/').next().unwrap_or(word).to_lowercase()
+}
+
+/// Skip leading options; those listed in `with_value` take the next word as their value.
+fn skip_options<'a>(args: &'a [Word], with_value: &[&str]) -> &'a [Word] {
+    let mut i = 0;
+    while let Some(arg) = args.get(i).map(|w| w.text.as_str()) {
+        if arg == "--" {
+            return &args[i + 1..];
+        }
+        if !arg.starts_with('-') || arg == "-" {
+            break;
+        }
+        i += if with_value.contains(&arg) { 2 } else { 1 };
+    }
+    &args[i.min(args.len())..]
+}
+
+fn skip_positional(args: &[Word], n: usize) -> &[Word] {
+    &args[n.min(args.len())..]
+}
+
+/// `env -S "<command line>"`: the split string, and the words after env's options.
+fn env_split_string(args: &[Word]) -> (Option<String>, &[Word]) {
+    let mut i = 0;
+    while let Some(arg) = args.get(i).map(|w| w.text.as_str()) {
+        if arg == "-S" || arg == "--split-string" {
+            return (args.get(i + 1).map(|w| w.text.clone()), args.get(i + 2..).unwrap_or(&[]));
+        }
+        if let Some(script) = arg.strip_prefix("--split-string=").or_else(|| arg.strip_prefix("-S").filter(|s| !s.is_empty())) {
+            return (Some(script.to_string()), &args[i + 1..]);
+        }
+        if arg == "--" {
+            return (None, &args[i + 1..]);
+        }
+        if !arg.starts_with('-') || arg == "-" {
+            break;
+        }
+        i += if ["-u", "-C", "--unset", "--chdir"].contains(&arg) { 2 } else { 1 };
+    }
+    (None, &args[i.min(args.len())..])
+}
+
+/// The script a shell runs with `-c` (also in clusters such as `-ec`).
+fn shell_script(args: &[Word]) -> Option<&str> {
+    let mut run_script = false;
+    let mut i = 0;
+    while let Some(arg) = args.get(i).map(|w| w.text.as_str()) {
+        if arg == "--" {
+            i += 1;
+            break;
+        }
+        let is_option = (arg.starts_with('-') || arg.starts_with('+')) && arg.len() > 1;
+        if !is_option {
+            break;
+        }
+        if !arg.starts_with("--") && arg[1..].contains('c') {
+            run_script = true;
+        }
+        // -o/+o take an option name
+        i += if arg == "-o" || arg == "+o" { 2 } else { 1 };
+    }
+    if run_script {
+        args.get(i).map(|w| w.text.as_str())
+    } else {
+        None
+    }
+}
+
+/// Single-quote a word so it re-tokenizes as itself.
+fn quote(word: &str) -> String {
+    format!("'{}'", word.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn binaries(command: &str) -> Vec<String> {
+        invocations(command).unwrap().into_iter().map(|i| i.binary).collect()
+    }
+
+    #[test]
+    fn test_splits_on_operators() {
+        assert_eq!(binaries("a; b && c || d | e & f\ng"), ["a", "b", "c", "d", "e", "f", "g"]);
+        assert_eq!(binaries("(a; b) | c"), ["a", "b", "c"]);
+        let piped: Vec<bool> = invocations("a | b |& c").unwrap().iter().map(|i| i.piped_into).collect();
+        assert_eq!(piped, [false, true, true]);
+    }
+
+    #[test]
+    fn test_quotes_and_escapes() {
+        let inv = &invocations(r#"'r'"m" -r\f "a b" 'c;d' e\ f"#).unwrap()[0];
+        assert_eq!(inv.binary, "rm");
+        assert_eq!(inv.args, ["-rf", "a b", "c;d", "e f"]);
+        assert!(invocations("echo 'unterminated").is_err());
+        assert!(invocations("echo \"unterminated").is_err());
+        assert!(invocations("echo $(unterminated").is_err());
+    }
+
+    #[test]
+    fn test_substitutions_are_commands() {
+        assert_eq!(binaries("echo $(rm -rf /) `reboot` \"$(halt)\" <(shutdown)"), ["echo", "rm", "reboot", "halt", "shutdown"]);
+        assert_eq!(binaries("echo $((1 + 2))"), ["echo"]);
+        let inv = &invocations("$(which rm) -rf /").unwrap()[0];
+        assert!(inv.dynamic);
+        assert!(invocations("$CMD x").unwrap()[0].dynamic);
+        assert!(!invocations("echo $HOME").unwrap()[0].dynamic);
+        assert!(invocations("rm \"$@\" /").unwrap()[0].dynamic_args);
+        assert!(invocations("rm -r$(echo f) /").unwrap()[0].dynamic_args);
+        assert!(!invocations("rm -- \"$f\"").unwrap()[0].dynamic_args);
+    }
+
+    #[test]
+    fn test_redirects_and_heredocs() {
+        let inv = &invocations("echo x 2>/dev/null >> /dev/sda < input").unwrap()[0];
+        assert_eq!(inv.args, ["x"]);
+        assert_eq!(inv.redirects, ["/dev/null", "/dev/sda"]);
+        assert_eq!(invocations("&> /dev/sdb").unwrap()[0].redirects, ["/dev/sdb"]);
+        assert_eq!(binaries("cat <<EOF > notes\nreboot tonight\nEOF\nls"), ["cat", "ls"]);
+        assert_eq!(binaries("cat <<-END\n\thalt\n\tEND\nls"), ["cat", "ls"]);
+        assert_eq!(binaries("cat <<< 'reboot'"), ["cat"]);
+        assert!(invocations("cat <<EOF\nx\nEOF").unwrap()[0].piped_into);
+
+        // Unquoted bodies are expanded by the shell; quoted ones are literal
+        assert_eq!(binaries("cat <<EOF\n$(rm -rf /)\nEOF"), ["cat", "rm"]);
+        assert_eq!(binaries("cat <<-EOF\n\tnow `reboot`\n\tEOF\nls"), ["cat", "ls", "reboot"]);
+        assert_eq!(binaries("cat <<A <<'B'\n$(halt)\nA\n$(reboot)\nB"), ["cat", "halt"]);
+        assert_eq!(binaries("cat <<\\EOF\n$(reboot)\nEOF"), ["cat"]);
+        assert_eq!(binaries("cat <<E\"O\"F\n$(reboot)\nEOF"), ["cat"]);
+        assert_eq!(binaries("cat <<EOF\n\\$(reboot)\nEOF"), ["cat"]);
+    }
+
+    #[test]
+    fn test_wrappers_resolve_to_the_real_program() {
+        let inv = |c: &str| invocations(c).unwrap().pop().unwrap();
+        assert_eq!(inv("sudo -u root -E env A=1 nice -n 10 ionice -c 3 /bin/rm -r x").binary, "rm");
+        assert_eq!(inv("timeout -s KILL 10s nohup stdbuf -o L rm x").args, ["x"]);
+        let xargs = inv("find . -name '*.log' | xargs -0 -n 10 rm");
+        assert!(xargs.bulk && xargs.piped_into && xargs.binary == "rm");
+        assert!(inv("find / -type f -exec rm {} \\;").bulk);
+        assert_eq!(binaries("bash -ec 'cd /; reboot'"), ["bash", "cd", "reboot"]);
+        assert_eq!(binaries("su -c 'halt' root"), ["su", "halt"]);
+        assert_eq!(binaries("env -S 'rm -r' /tmp"), ["env", "rm"]);
+        assert_eq!(inv("env -S 'rm -r' /tmp").args, ["-r", "/tmp"]);
+        assert_eq!(binaries("eval rm -rf /"), ["eval", "rm"]);
+        assert_eq!(binaries("X=1 Y=2 if true; then poweroff; fi"), ["true", "poweroff"]);
+    }
+
+    #[test]
+    fn test_nesting_is_bounded() {
+        let nest = |levels| (0..levels).fold("ls".to_string(), |acc, _| format!("echo $({acc})"));
+        assert_eq!(invocations(&nest(MAX_DEPTH)).unwrap().len(), MAX_DEPTH + 1);
+        assert!(invocations(&nest(MAX_DEPTH + 1)).is_err());
+    }
+}
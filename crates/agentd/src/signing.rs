@@ -0,0 +1,100 @@
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use ed25519_dalek::{Signer, SigningKey};
+use rand::rngs::OsRng;
+
+pub use osmoda_ledger::signing::{checkpoint_message, merkle_root_message, verify_signature, watermark_message};
+
+/// File holding the raw 32-byte Ed25519 secret (0600).
+const KEY_FILE: &str = "ledger-signing.key";
+/// File holding the hex-encoded public key, read by `agentctl verify-ledger`.
+const PUB_FILE: &str = "ledger-signing.pub";
+
+/// agentd's persistent Ed25519 identity used to sign ledger checkpoints.
+#[derive(Clone)]
+pub struct LedgerSigner {
+    signing_key: SigningKey,
+}
+
+impl LedgerSigner {
+    pub fn new(signing_key: SigningKey) -> Self {
+        Self { signing_key }
+    }
+
+    /// Load the signing key from the state directory, or generate one on first boot.
+    pub fn load_or_create(state_dir: &Path) -> Result<Self> {
+        let key_path = state_dir.join(KEY_FILE);
+
+        let signer = if key_path.exists() {
+            let bytes = std::fs::read(&key_path).context("failed to read ledger signing key")?;
+            let secret: [u8; 32] = bytes
+                .as_slice()
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("ledger signing key has invalid length: {}", bytes.len()))?;
+            Self::new(SigningKey::from_bytes(&secret))
+        } else {
+            let signer = Self::new(SigningKey::generate(&mut OsRng));
+            // Created 0600 in one step, so the secret is never readable by others
+            let mut file = std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .mode(0o600)
+                .open(&key_path)
+                .context("failed to create ledger signing key")?;
+            file.write_all(&signer.signing_key.to_bytes())
+                .and_then(|()| file.sync_all())
+                .context("failed to write ledger signing key")?;
+            tracing::info!(public_key = %signer.public_key_hex(), "generated new ledger signing key");
+            signer
+        };
+
+        // Always (re)write the public half so verifiers can pin it
+        std::fs::write(state_dir.join(PUB_FILE), signer.public_key_hex())
+            .context("failed to write ledger signing public key")?;
+
+        Ok(signer)
+    }
+
+    /// Hex-encoded Ed25519 public key.
+    pub fn public_key_hex(&self) -> String {
+        hex::encode(self.signing_key.verifying_key().as_bytes())
+    }
+
+    /// Sign a message, returning the hex-encoded signature.
+    pub fn sign(&self, message: &str) -> String {
+        hex::encode(self.signing_key.sign(message.as_bytes()).to_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let signer = LedgerSigner::new(SigningKey::from_bytes(&[7u8; 32]));
+        let msg = checkpoint_message(10, "abc", "2026-01-01T00:00:00Z");
+        let sig = signer.sign(&msg);
+        assert!(verify_signature(&signer.public_key_hex(), &msg, &sig));
+        assert!(!verify_signature(&signer.public_key_hex(), &checkpoint_message(11, "abc", "2026-01-01T00:00:00Z"), &sig));
+        assert!(!verify_signature("not-hex", &msg, &sig));
+    }
+
+    #[test]
+    fn test_load_or_create_is_stable() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = LedgerSigner::load_or_create(dir.path()).unwrap();
+        let second = LedgerSigner::load_or_create(dir.path()).unwrap();
+        assert_eq!(first.public_key_hex(), second.public_key_hex());
+
+        let published = std::fs::read_to_string(dir.path().join(PUB_FILE)).unwrap();
+        assert_eq!(published, first.public_key_hex());
+
+        let mode = std::fs::metadata(dir.path().join(KEY_FILE)).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
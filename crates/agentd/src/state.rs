@@ -2,16 +2,23 @@ use std::sync::Arc;
 use tokio::sync::Mutex;
 
 use crate::approval::ApprovalGate;
-use crate::ledger::Ledger;
+use crate::embedding::Embedder;
+use crate::ledger_handle::LedgerHandle;
+use crate::notify::ApprovalNotifier;
 use crate::sandbox::SandboxEngine;
 
 /// Shared application state passed to all axum handlers via State extractor.
 pub struct AppState {
-    pub ledger: Mutex<Ledger>,
+    /// Writes are queued to a single writer thread; reads use a pool of connections.
+    pub ledger: LedgerHandle,
     pub sys: Mutex<sysinfo::System>,
     pub state_dir: String,
     pub approval_gate: Option<Arc<ApprovalGate>>,
+    /// Announces new approval requests to webhooks and mesh peers, if any are configured.
+    pub approval_notifier: Option<Arc<ApprovalNotifier>>,
     pub sandbox_engine: Option<Arc<SandboxEngine>>,
+    /// Embeds memory.ingest content and recall queries for semantic search.
+    pub embedder: Arc<dyn Embedder>,
 }
 
 /// Type alias for the shared state used across the application.
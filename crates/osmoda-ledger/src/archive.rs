@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::chain::Event;
+
+/// Directory (under the state dir) holding sealed ledger segments.
+pub const ARCHIVE_DIR: &str = "ledger-archive";
+
+/// A sealed, compressed file holding a contiguous run of archived events.
+/// `first_prev_hash` and `last_hash` let verification link it into the chain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LedgerSegment {
+    pub id: i64,
+    pub first_event_id: i64,
+    pub last_event_id: i64,
+    pub event_count: i64,
+    pub first_prev_hash: String,
+    pub last_hash: String,
+    pub file: String,
+    pub sha256: String,
+    pub created_at: String,
+}
+
+/// File name for the segment covering events `first..=last`.
+pub fn segment_file_name(first_event_id: i64, last_event_id: i64) -> String {
+    format!("events-{first_event_id:012}-{last_event_id:012}.jsonl.gz")
+}
+
+/// A segment written and fsynced under a temporary name. Nothing reads it until
+/// `publish` moves it into place, which the ledger does only once it has committed
+/// the segment, so a failed commit never leaves a segment the index does not know.
+pub struct StagedSegment {
+    pub file: String,
+    /// SHA-256 of the compressed bytes.
+    pub sha256: String,
+    dir: PathBuf,
+}
+
+impl StagedSegment {
+    /// Move the segment to its final name and seal it read-only.
+    pub fn publish(&self) -> Result<()> {
+        publish(&self.dir, &self.file)
+    }
+
+    /// Remove the staged file after the ledger declined to commit it.
+    pub fn discard(&self) {
+        if let Err(e) = std::fs::remove_file(self.dir.join(staged_name(&self.file))) {
+            tracing::warn!(file = %self.file, error = %e, "failed to remove staged ledger segment");
+        }
+    }
+}
+
+fn staged_name(file_name: &str) -> String {
+    format!("{file_name}.tmp")
+}
+
+fn publish(dir: &Path, file_name: &str) -> Result<()> {
+    let path = dir.join(file_name);
+    std::fs::rename(dir.join(staged_name(file_name)), &path).context("failed to move ledger segment into place")?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o400))?;
+    }
+    Ok(())
+}
+
+/// Write events as gzip-compressed JSONL under a temporary name; see `StagedSegment`.
+pub fn stage_segment(dir: &Path, events: &[Event]) -> Result<StagedSegment> {
+    let (first, last) = match (events.first(), events.last()) {
+        (Some(f), Some(l)) => (f.id, l.id),
+        _ => anyhow::bail!("refusing to write an empty ledger segment"),
+    };
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    for event in events {
+        serde_json::to_writer(&mut encoder, event)?;
+        encoder.write_all(b"\n")?;
+    }
+    let bytes = encoder.finish().context("failed to compress ledger segment")?;
+    let sha256 = hex::encode(Sha256::digest(&bytes));
+
+    std::fs::create_dir_all(dir).context("failed to create ledger archive directory")?;
+    let file_name = segment_file_name(first, last);
+    let tmp_path = dir.join(staged_name(&file_name));
+
+    let mut file = std::fs::File::create(&tmp_path)
+        .with_context(|| format!("failed to create {}", tmp_path.display()))?;
+    file.write_all(&bytes)?;
+    file.sync_all().context("failed to fsync ledger segment")?;
+
+    Ok(StagedSegment { file: file_name, sha256, dir: dir.to_path_buf() })
+}
+
+/// Settle segments a crash left staged: publish those the ledger committed (`committed`
+/// maps file name to recorded digest) and remove the rest, including rewrites of a
+/// published segment that never committed. Returns the published names.
+pub fn reconcile_staged(dir: &Path, committed: &HashMap<String, String>) -> Result<Vec<String>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).context("failed to list ledger archive directory"),
+    };
+
+    let mut published = Vec::new();
+    for entry in entries {
+        let name = entry?.file_name().to_string_lossy().into_owned();
+        let Some(file_name) = name.strip_suffix(".tmp") else { continue };
+        match committed.get(file_name) {
+            Some(expected) => {
+                let bytes = std::fs::read(dir.join(&name)).with_context(|| format!("failed to read {name}"))?;
+                let matches = hex::encode(Sha256::digest(&bytes)) == *expected;
+                if !matches && dir.join(file_name).exists() {
+                    std::fs::remove_file(dir.join(&name)).with_context(|| format!("failed to remove {name}"))?;
+                    continue;
+                }
+                if !matches {
+                    // Leave it for an operator; verification reports the segment as unreadable
+                    tracing::warn!(file = file_name, "staged ledger segment does not match its recorded digest");
+                    continue;
+                }
+                publish(dir, file_name)?;
+                published.push(file_name.to_string());
+            }
+            None => std::fs::remove_file(dir.join(&name)).with_context(|| format!("failed to remove {name}"))?,
+        }
+    }
+    Ok(published)
+}
+
+/// Read a sealed segment back, refusing it if the file no longer matches its recorded digest.
+pub fn read_segment(dir: &Path, file_name: &str, expected_sha256: &str) -> Result<Vec<Event>> {
+    let path = dir.join(file_name);
+    let bytes = std::fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+
+    let actual = hex::encode(Sha256::digest(&bytes));
+    if actual != expected_sha256 {
+        anyhow::bail!("ledger segment {file_name} digest mismatch: expected {expected_sha256}, got {actual}");
+    }
+
+    let mut events = Vec::new();
+    for line in std::io::BufReader::new(GzDecoder::new(bytes.as_slice())).lines() {
+        let line = line.context("failed to decompress ledger segment")?;
+        if line.is_empty() {
+            continue;
+        }
+        events.push(serde_json::from_str(&line).context("malformed event in ledger segment")?);
+    }
+    Ok(events)
+}
+
+/// List archive segments, oldest first. Ledgers from before retention have no
+/// segment index, and so no segments.
+pub fn segments(conn: &Connection) -> Result<Vec<LedgerSegment>> {
+    let indexed: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'ledger_segments'",
+            [],
+            |row| row.get(0),
+        )
+        .context("failed to look for the ledger segment index")?;
+    if indexed == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT id, first_event_id, last_event_id, event_count, first_prev_hash, last_hash, file, sha256, created_at
+         FROM ledger_segments ORDER BY first_event_id ASC",
+    )?;
+    let segments = stmt
+        .query_map([], |row| {
+            Ok(LedgerSegment {
+                id: row.get(0)?,
+                first_event_id: row.get(1)?,
+                last_event_id: row.get(2)?,
+                event_count: row.get(3)?,
+                first_prev_hash: row.get(4)?,
+                last_hash: row.get(5)?,
+                file: row.get(6)?,
+                sha256: row.get(7)?,
+                created_at: row.get(8)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .context("failed to list ledger segments")?;
+    Ok(segments)
+}
+
+/// Read an indexed segment from the archive directory `dir`.
+pub fn read_indexed_segment(dir: Option<&Path>, segment: &LedgerSegment) -> Result<Vec<Event>> {
+    let dir = dir.context("ledger has archive segments but no archive directory is configured")?;
+    read_segment(dir, &segment.file, &segment.sha256)
+}
+
+/// Look up an event that has been moved into an archive segment.
+pub fn archived_event(conn: &Connection, dir: Option<&Path>, id: i64) -> Result<Option<Event>> {
+    let Some(segment) = segments(conn)?
+        .into_iter()
+        .find(|s| s.first_event_id <= id && s.last_event_id >= id)
+    else {
+        return Ok(None);
+    };
+    Ok(read_indexed_segment(dir, &segment)?.into_iter().find(|e| e.id == id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: i64) -> Event {
+        Event {
+            id,
+            ts: "2026-01-01T00:00:00.000Z".to_string(),
+            event_type: "test".to_string(),
+            actor: "tester".to_string(),
+            payload: format!("{{\"n\":{id}}}"),
+            prev_hash: "0".repeat(64),
+            hash: format!("{id:064x}"),
+            verified_actor: None,
+            payload_digest: None,
+        }
+    }
+
+    #[test]
+    fn test_segment_roundtrip_and_tamper() {
+        let dir = tempfile::tempdir().unwrap();
+        let events: Vec<Event> = (1..=5).map(event).collect();
+
+        let staged = stage_segment(dir.path(), &events).unwrap();
+        let (name, sha) = (staged.file.clone(), staged.sha256.clone());
+        assert_eq!(name, segment_file_name(1, 5));
+        assert!(read_segment(dir.path(), &name, &sha).is_err(), "not readable until published");
+        staged.publish().unwrap();
+
+        let back = read_segment(dir.path(), &name, &sha).unwrap();
+        assert_eq!(back.len(), 5);
+        assert_eq!(back[4].hash, events[4].hash);
+
+        assert!(read_segment(dir.path(), &name, &"0".repeat(64)).is_err());
+    }
+
+    #[test]
+    fn test_reconcile_staged_segments() {
+        let dir = tempfile::tempdir().unwrap();
+        let committed = stage_segment(dir.path(), &(1..=3).map(event).collect::<Vec<_>>()).unwrap();
+        let abandoned = stage_segment(dir.path(), &(4..=6).map(event).collect::<Vec<_>>()).unwrap();
+
+        let index = HashMap::from([(committed.file.clone(), committed.sha256.clone())]);
+        assert_eq!(reconcile_staged(dir.path(), &index).unwrap(), vec![committed.file.clone()]);
+
+        assert_eq!(read_segment(dir.path(), &committed.file, &committed.sha256).unwrap().len(), 3);
+        let left: Vec<_> = std::fs::read_dir(dir.path()).unwrap().map(|e| e.unwrap().file_name()).collect();
+        assert_eq!(left, vec![std::ffi::OsString::from(&committed.file)]);
+        assert!(!dir.path().join(staged_name(&abandoned.file)).exists());
+    }
+}
@@ -0,0 +1,659 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::archive::{self, LedgerSegment};
+use crate::signing::{self, SignedCheckpoint};
+
+/// The zero hash used as prev_hash for the genesis event.
+pub const GENESIS_PREV_HASH: &str =
+    "0000000000000000000000000000000000000000000000000000000000000000";
+
+
+/// Most issues kept in a `VerifyReport`; the total is still counted.
+const MAX_VERIFY_ISSUES: usize = 100;
+
+/// A single event in the hash-chained ledger.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Event {
+    pub id: i64,
+    pub ts: String,
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub actor: String,
+    pub payload: String,
+    pub prev_hash: String,
+    pub hash: String,
+    /// Identity of the connected process that submitted the event (from SO_PEERCRED),
+    /// as opposed to the self-declared `actor`. None for events agentd writes itself.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verified_actor: Option<String>,
+    /// SHA-256 of the payload as written. Events that carry one commit to the digest
+    /// instead of the raw payload, so the payload can be redacted without breaking the
+    /// chain. None for events written before schema v5.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload_digest: Option<String>,
+}
+
+impl Event {
+    /// Read an event from a row selected with `event_columns`.
+    pub fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            ts: row.get(1)?,
+            event_type: row.get(2)?,
+            actor: row.get(3)?,
+            payload: row.get(4)?,
+            prev_hash: row.get(5)?,
+            hash: row.get(6)?,
+            verified_actor: row.get(7)?,
+            payload_digest: row.get(8)?,
+        })
+    }
+}
+
+/// Compute the SHA-256 hash for an event row.
+/// Uses pipe delimiters to prevent field-boundary collisions
+/// (e.g., id="12" + ts="3abc" would otherwise equal id="123" + ts="abc").
+/// The verified actor is appended only when present, so events without one
+/// keep the original format and older chains still verify.
+pub fn compute_hash(
+    id: i64,
+    ts: &str,
+    event_type: &str,
+    actor: &str,
+    payload: &str,
+    prev_hash: &str,
+    verified_actor: Option<&str>,
+) -> String {
+    let mut input = format!("{id}|{ts}|{event_type}|{actor}|{payload}|{prev_hash}");
+    if let Some(verified) = verified_actor {
+        input.push('|');
+        input.push_str(verified);
+    }
+    let mut hasher = Sha256::new();
+    hasher.update(input.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// SHA-256 of a payload, as stored in `payload_digest`.
+pub fn payload_digest(payload: &str) -> String {
+    hex::encode(Sha256::digest(payload.as_bytes()))
+}
+
+/// Hash of an event as stored. Events with a `payload_digest` put `sha256:<digest>`
+/// in the payload position; older events hash the raw payload.
+pub fn event_hash(event: &Event) -> String {
+    let committed = match event.payload_digest {
+        Some(ref digest) => format!("sha256:{digest}"),
+        None => event.payload.clone(),
+    };
+    compute_hash(
+        event.id,
+        &event.ts,
+        &event.event_type,
+        &event.actor,
+        &committed,
+        &event.prev_hash,
+        event.verified_actor.as_deref(),
+    )
+}
+
+/// Payload left in place of a redacted event's original one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tombstone {
+    pub redacted: bool,
+    /// Digest of the original payload, still committed to by the event's hash.
+    pub payload_digest: String,
+    /// The `ledger.redact` event recording who removed it and why.
+    pub redaction_event_id: i64,
+}
+
+impl Tombstone {
+    pub fn parse(payload: &str) -> Option<Self> {
+        serde_json::from_str::<Self>(payload).ok().filter(|t| t.redacted)
+    }
+}
+
+/// Which verification check an event failed.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VerifyCheck {
+    /// `prev_hash` does not equal the previous event's hash.
+    PrevHashMismatch,
+    /// Stored hash does not match the recomputed one — the row was edited.
+    HashMismatch,
+    /// Ids are not consecutive — events were deleted or inserted.
+    IdGap,
+    /// Timestamp earlier than the previous event's.
+    NonMonotonicTimestamp,
+    /// Archive segment missing, altered, or inconsistent with its index row.
+    SegmentMismatch,
+    /// The event the verification watermark points at changed or disappeared.
+    WatermarkMismatch,
+    /// Payload does not match its committed digest and is not a tombstone backed by a
+    /// `ledger.redact` event.
+    PayloadDigestMismatch,
+    /// A signed checkpoint is untrusted, badly signed, or disagrees with the chain.
+    CheckpointMismatch,
+    /// The head is further past the newest valid signed checkpoint than the signing
+    /// interval allows — checkpoints were deleted or signing stopped.
+    CheckpointMissing,
+}
+
+/// One failed check.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyIssue {
+    pub event_id: i64,
+    pub check: VerifyCheck,
+    pub detail: String,
+}
+
+/// Inclusive range of event ids.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EventRange {
+    pub first_event_id: i64,
+    pub last_event_id: i64,
+}
+
+/// Outcome of a ledger verification, served by `/ledger/verify`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyReport {
+    pub ok: bool,
+    /// Rehashed from genesis (false = started at the watermark).
+    pub full: bool,
+    /// Event the walk started after (0 = genesis).
+    pub from_event_id: i64,
+    pub head_event_id: i64,
+    pub head_hash: String,
+    pub events_checked: u64,
+    pub first_broken: Option<VerifyIssue>,
+    /// Events that can no longer be trusted: from the earliest break to the head.
+    pub untrusted: Option<EventRange>,
+    pub issue_count: u64,
+    /// At most 100 issues, in chain order.
+    pub issues: Vec<VerifyIssue>,
+    /// Signed checkpoints checked against the trusted key; None when there was no key.
+    pub signed_checkpoints_checked: Option<u64>,
+}
+
+/// Running state of a chain walk: checks each event against its predecessor.
+pub struct ChainWalker {
+    expected_prev_hash: String,
+    last_id: i64,
+    last_ts: Option<String>,
+    checked: u64,
+    untrusted_from: Option<i64>,
+    issue_count: u64,
+    issues: Vec<VerifyIssue>,
+    /// Tombstoned event id -> (redaction event id, digest) still waiting for its `ledger.redact`.
+    pending_redactions: HashMap<i64, (i64, String)>,
+    /// Signed checkpoints by event id, still waiting for the walk to reach their event.
+    pending_checkpoints: HashMap<i64, Vec<SignedCheckpoint>>,
+    checkpoints_checked: Option<u64>,
+    /// Newest event vouched for by a valid signed checkpoint, or the anchor of the walk.
+    checkpointed_to: i64,
+    checkpoint_every: Option<i64>,
+}
+
+impl Default for ChainWalker {
+    /// A walk starting at genesis.
+    fn default() -> Self {
+        Self::new(GENESIS_PREV_HASH.to_string(), 0)
+    }
+}
+
+impl ChainWalker {
+    fn new(expected_prev_hash: String, last_id: i64) -> Self {
+        Self {
+            expected_prev_hash,
+            last_id,
+            last_ts: None,
+            checked: 0,
+            untrusted_from: None,
+            issue_count: 0,
+            issues: Vec::new(),
+            pending_redactions: HashMap::new(),
+            pending_checkpoints: HashMap::new(),
+            checkpoints_checked: None,
+            checkpointed_to: last_id,
+            checkpoint_every: None,
+        }
+    }
+
+    /// Check `checkpoints` as the walk goes: each must be signed by `trusted_key` and
+    /// name the hash the chain has at its event. Without a trusted key only the hashes
+    /// are compared, and the report says no signed checkpoints were checked.
+    pub fn expect_checkpoints(&mut self, checkpoints: Vec<SignedCheckpoint>, trusted_key: Option<&str>) {
+        if trusted_key.is_some() {
+            *self.checkpoints_checked.get_or_insert(0) += checkpoints.len() as u64;
+        }
+        for cp in checkpoints {
+            if let Some(problem) = trusted_key.and_then(|key| cp.signature_problem(key)) {
+                self.flag(cp.event_id, VerifyCheck::CheckpointMismatch, problem);
+                continue;
+            }
+            self.pending_checkpoints.entry(cp.event_id).or_default().push(cp);
+        }
+    }
+
+    /// Fail the walk when, with a trusted key, the head is more than `every` events past
+    /// the newest checkpoint that checked out. Checks only the rows present would
+    /// otherwise pass a ledger whose checkpoints were deleted.
+    pub fn require_checkpoint_every(&mut self, every: i64) {
+        self.checkpoint_every = Some(every);
+    }
+
+    /// Hash the next event would have to link to.
+    pub fn head_hash(&self) -> &str {
+        &self.expected_prev_hash
+    }
+
+    pub fn check(&mut self, event: &Event) {
+        if event.id != self.last_id + 1 {
+            self.flag(
+                event.id,
+                VerifyCheck::IdGap,
+                format!("expected event #{}, found #{}", self.last_id + 1, event.id),
+            );
+        }
+        if self.last_ts.as_ref().is_some_and(|ts| event.ts < *ts) {
+            let detail = format!("ts {} is earlier than the previous event's {}", event.ts, self.last_ts.as_deref().unwrap_or(""));
+            self.flag(event.id, VerifyCheck::NonMonotonicTimestamp, detail);
+        }
+        if event.prev_hash != self.expected_prev_hash {
+            // Either this link or the previous event was rewritten — distrust both
+            if self.last_id > 0 {
+                self.distrust_from(self.last_id);
+            }
+            let detail = format!("expected prev_hash={}, got {}", self.expected_prev_hash, event.prev_hash);
+            self.flag(event.id, VerifyCheck::PrevHashMismatch, detail);
+        }
+
+        let computed = event_hash(event);
+        if computed != event.hash {
+            let detail = format!("computed={computed}, stored={}", event.hash);
+            self.flag(event.id, VerifyCheck::HashMismatch, detail);
+        }
+        self.check_payload(event);
+
+        for cp in self.pending_checkpoints.remove(&event.id).unwrap_or_default() {
+            if cp.head_hash != event.hash {
+                let detail = format!("rewrite detected: checkpoint #{} signed {}, chain has {}", cp.id, cp.head_hash, event.hash);
+                self.flag(event.id, VerifyCheck::CheckpointMismatch, detail);
+            } else if self.checkpoints_checked.is_some() {
+                self.checkpointed_to = self.checkpointed_to.max(event.id);
+            }
+        }
+
+        // Resync on the stored values so one bad row is reported once
+        self.expected_prev_hash = event.hash.clone();
+        self.last_id = event.id;
+        self.last_ts = Some(event.ts.clone());
+        self.checked += 1;
+    }
+
+    /// The payload must match its digest, or be a tombstone for it that a later
+    /// `ledger.redact` event accounts for.
+    fn check_payload(&mut self, event: &Event) {
+        if event.event_type == "ledger.redact" {
+            let record: serde_json::Value = serde_json::from_str(&event.payload).unwrap_or_default();
+            let target = record["event_id"].as_i64().unwrap_or(0);
+            if self.pending_redactions.get(&target).is_some_and(|(redaction_id, digest)| {
+                *redaction_id == event.id && record["payload_digest"].as_str() == Some(digest)
+            }) {
+                self.pending_redactions.remove(&target);
+            }
+        }
+
+        let Some(ref digest) = event.payload_digest else {
+            return;
+        };
+        if payload_digest(&event.payload) == *digest {
+            return;
+        }
+        match Tombstone::parse(&event.payload) {
+            Some(t) if t.payload_digest == *digest && t.redaction_event_id > event.id => {
+                self.pending_redactions.insert(event.id, (t.redaction_event_id, t.payload_digest));
+            }
+            _ => self.flag(
+                event.id,
+                VerifyCheck::PayloadDigestMismatch,
+                format!("payload does not match committed digest {digest}"),
+            ),
+        }
+    }
+
+    /// Check one sealed segment's link and index row, then every event in it.
+    /// An unusable segment is reported once and skipped, resyncing on its recorded last hash.
+    fn check_segment(&mut self, segment: &LedgerSegment, events: Result<Vec<Event>>) {
+        if segment.first_prev_hash != self.expected_prev_hash {
+            self.flag(
+                segment.first_event_id,
+                VerifyCheck::PrevHashMismatch,
+                format!("segment {} does not link to previous history", segment.file),
+            );
+            self.expected_prev_hash = segment.first_prev_hash.clone();
+        }
+
+        let events = match events {
+            Ok(events) => events,
+            Err(e) => {
+                self.flag(segment.first_event_id, VerifyCheck::SegmentMismatch, format!("segment unreadable: {e}"));
+                self.skip_to(segment.last_event_id, &segment.last_hash);
+                return;
+            }
+        };
+
+        let ids_match = events.len() as i64 == segment.event_count
+            && events.first().map(|e| e.id) == Some(segment.first_event_id)
+            && events.last().map(|e| e.id) == Some(segment.last_event_id);
+        if !ids_match {
+            self.flag(
+                segment.first_event_id,
+                VerifyCheck::SegmentMismatch,
+                format!("segment {} does not match its index row", segment.file),
+            );
+        }
+
+        for event in &events {
+            self.check(event);
+        }
+
+        if segment.last_hash != self.expected_prev_hash {
+            self.flag(
+                segment.last_event_id,
+                VerifyCheck::SegmentMismatch,
+                format!("segment {} last hash does not match its index row", segment.file),
+            );
+            self.expected_prev_hash = segment.last_hash.clone();
+        }
+    }
+
+    /// Continue after a span that could not be checked (an unreadable segment).
+    fn skip_to(&mut self, last_id: i64, last_hash: &str) {
+        self.last_id = last_id;
+        self.expected_prev_hash = last_hash.to_string();
+    }
+
+    /// Record a failed check. Everything from the earliest flagged event to the head is
+    /// untrusted, whatever order problems are found in.
+    pub fn flag(&mut self, event_id: i64, check: VerifyCheck, detail: String) {
+        self.distrust_from(event_id);
+        self.issue_count += 1;
+        if self.issues.len() < MAX_VERIFY_ISSUES {
+            tracing::warn!(event_id, ?check, %detail, "chain break");
+            self.issues.push(VerifyIssue { event_id, check, detail });
+        }
+    }
+
+    fn distrust_from(&mut self, event_id: i64) {
+        self.untrusted_from = Some(self.untrusted_from.map_or(event_id, |first| first.min(event_id)));
+    }
+
+    pub fn finish(mut self, full: bool, from_event_id: i64) -> VerifyReport {
+        let mut unmatched: Vec<_> = self.pending_redactions.drain().collect();
+        unmatched.sort();
+        for (event_id, (redaction_id, _)) in unmatched {
+            self.flag(
+                event_id,
+                VerifyCheck::PayloadDigestMismatch,
+                format!("tombstone names redaction event #{redaction_id}, which does not record it"),
+            );
+        }
+
+        let mut missing: Vec<_> = self.pending_checkpoints.drain().flat_map(|(_, cps)| cps).collect();
+        missing.sort_by_key(|cp| cp.id);
+        for cp in missing {
+            self.flag(
+                cp.event_id,
+                VerifyCheck::CheckpointMismatch,
+                format!("event referenced by signed checkpoint #{} is missing", cp.id),
+            );
+        }
+
+        if let (Some(every), Some(_)) = (self.checkpoint_every, self.checkpoints_checked) {
+            if self.last_id - self.checkpointed_to > every {
+                let detail = format!(
+                    "no valid signed checkpoint covers events #{}..#{}; one is due every {every} events",
+                    self.checkpointed_to + 1,
+                    self.last_id
+                );
+                self.flag(self.checkpointed_to + 1, VerifyCheck::CheckpointMissing, detail);
+            }
+        }
+
+        self.issues.sort_by_key(|issue| issue.event_id);
+        let untrusted = self.untrusted_from.map(|first| EventRange {
+            first_event_id: first,
+            last_event_id: self.last_id.max(first),
+        });
+        VerifyReport {
+            ok: self.issue_count == 0,
+            full,
+            from_event_id,
+            head_event_id: self.last_id,
+            head_hash: self.expected_prev_hash,
+            events_checked: self.checked,
+            first_broken: self.issues.first().cloned(),
+            untrusted,
+            issue_count: self.issue_count,
+            issues: self.issues,
+            signed_checkpoints_checked: self.checkpoints_checked,
+        }
+    }
+}
+
+/// Columns read by `Event::from_row`, in order. Columns added by later schema versions
+/// read as NULL when `conn` is a ledger agentd has not migrated yet.
+pub fn event_columns(conn: &Connection) -> Result<String> {
+    let mut columns = String::from("id, ts, type, actor, payload, prev_hash, hash");
+    for column in ["verified_actor", "payload_digest"] {
+        columns.push_str(if has_column(conn, column)? { ", " } else { ", NULL AS " });
+        columns.push_str(column);
+    }
+    Ok(columns)
+}
+
+/// Whether the `events` table has `column`.
+pub fn has_column(conn: &Connection, column: &str) -> Result<bool> {
+    let present: i64 = conn
+        .query_row("SELECT COUNT(*) FROM pragma_table_info('events') WHERE name = ?1", [column], |row| row.get(0))
+        .context("failed to read events table columns")?;
+    Ok(present > 0)
+}
+
+/// Fetch a single event by id: from the live table, or from its segment once archived.
+pub fn get_event(conn: &Connection, archive_dir: Option<&Path>, id: i64) -> Result<Option<Event>> {
+    let result = conn.query_row(
+        &format!("SELECT {} FROM events WHERE id = ?1", event_columns(conn)?),
+        [id],
+        Event::from_row,
+    );
+    match result {
+        Ok(event) => Ok(Some(event)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => archive::archived_event(conn, archive_dir, id),
+        Err(e) => Err(e).context("failed to fetch event"),
+    }
+}
+
+/// Walk the chain in id order — archived segments first, then the live table — and
+/// check every event against its predecessor. With an `anchor` (id and hash of an event
+/// verified earlier) only later events are rehashed, and the anchor itself must still
+/// be in the chain unchanged. With a `trusted_key`, the signed checkpoints over the
+/// walked events are checked too. Flag anything else worth reporting on the returned
+/// walker, then `finish` it.
+pub fn walk(
+    conn: &Connection,
+    archive_dir: Option<&Path>,
+    anchor: Option<(i64, &str)>,
+    trusted_key: Option<&str>,
+) -> Result<ChainWalker> {
+    let mut walker = ChainWalker::default();
+    let mut after = 0;
+    if let Some((event_id, hash)) = anchor {
+        walker = ChainWalker::new(hash.to_string(), event_id);
+        after = event_id;
+        let event = get_event(conn, archive_dir, event_id)?;
+        if event.as_ref().map(|e| e.hash.as_str()) != Some(hash) {
+            walker.flag(event_id, VerifyCheck::WatermarkMismatch, "watermarked event changed or missing".to_string());
+        }
+        walker.last_ts = event.map(|e| e.ts);
+    }
+    if trusted_key.is_some() {
+        walker.expect_checkpoints(signing::signed_checkpoints(conn, after)?, trusted_key);
+    }
+
+    for segment in archive::segments(conn)? {
+        if segment.last_event_id <= after {
+            continue;
+        }
+        let events = archive::read_indexed_segment(archive_dir, &segment);
+        if segment.first_event_id > after {
+            walker.check_segment(&segment, events);
+            continue;
+        }
+        // The anchor is inside this segment; only the events after it are new
+        match events {
+            Ok(events) => {
+                for event in events.iter().filter(|e| e.id > after) {
+                    walker.check(event);
+                }
+            }
+            Err(e) => {
+                walker.flag(after + 1, VerifyCheck::SegmentMismatch, format!("segment unreadable: {e}"));
+                walker.skip_to(segment.last_event_id, &segment.last_hash);
+            }
+        }
+    }
+
+    let mut stmt = conn
+        .prepare(&format!("SELECT {} FROM events WHERE id > ?1 ORDER BY id ASC", event_columns(conn)?))
+        .context("failed to prepare verify query")?;
+    let rows = stmt.query_map([after], Event::from_row).context("failed to execute verify query")?;
+    for event in rows {
+        let event = event.context("failed to read event during verify")?;
+        walker.check(&event);
+    }
+    Ok(walker)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(n: i64) -> Vec<Event> {
+        let mut events = Vec::new();
+        let mut prev_hash = GENESIS_PREV_HASH.to_string();
+        for id in 1..=n {
+            let payload = format!("{{\"n\":{id}}}");
+            let mut event = Event {
+                id,
+                ts: format!("2026-01-01T00:00:{id:02}.000Z"),
+                event_type: "test".to_string(),
+                actor: "tester".to_string(),
+                payload_digest: Some(payload_digest(&payload)),
+                payload,
+                prev_hash,
+                hash: String::new(),
+                verified_actor: None,
+            };
+            event.hash = event_hash(&event);
+            prev_hash = event.hash.clone();
+            events.push(event);
+        }
+        events
+    }
+
+    #[test]
+    fn test_untrusted_range_starts_at_earliest_issue() {
+        let mut events = chain(6);
+        events[4].payload = "{\"n\":\"edited\"}".to_string();
+
+        let mut walker = ChainWalker::default();
+        for event in &events {
+            walker.check(event);
+        }
+        // Problems found after the walk (e.g. a signed checkpoint) can point further back
+        walker.flag(2, VerifyCheck::CheckpointMismatch, "rewrite detected".to_string());
+
+        let report = walker.finish(true, 0);
+        assert_eq!(report.issue_count, 2);
+        assert_eq!(report.first_broken.map(|i| i.event_id), Some(2));
+        assert_eq!(report.untrusted, Some(EventRange { first_event_id: 2, last_event_id: 6 }));
+        assert_eq!(report.head_hash, events[5].hash);
+    }
+
+    #[test]
+    fn test_checkpoints_compared_during_walk() {
+        let events = chain(4);
+        let checkpoint = |id, event_id, head_hash: &str| SignedCheckpoint {
+            id,
+            event_id,
+            head_hash: head_hash.to_string(),
+            signed_at: "2026-01-01T00:01:00.000Z".to_string(),
+            public_key: String::new(),
+            signature: String::new(),
+        };
+
+        let mut walker = ChainWalker::default();
+        walker.expect_checkpoints(
+            vec![checkpoint(1, 2, &events[1].hash), checkpoint(2, 3, "forged"), checkpoint(3, 9, "gone")],
+            None,
+        );
+        for event in &events {
+            walker.check(event);
+        }
+
+        let report = walker.finish(true, 0);
+        assert_eq!(report.signed_checkpoints_checked, None);
+        let flagged: Vec<_> = report.issues.iter().map(|i| (i.event_id, i.check)).collect();
+        assert_eq!(flagged, vec![(3, VerifyCheck::CheckpointMismatch), (9, VerifyCheck::CheckpointMismatch)]);
+        assert!(report.issues[0].detail.starts_with("rewrite detected"));
+        assert!(report.issues[1].detail.contains("missing"));
+    }
+
+    #[test]
+    fn test_walks_ledger_without_later_columns() {
+        // A ledger from before verified_actor and payload_digest existed
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE events (id INTEGER PRIMARY KEY, ts TEXT, type TEXT, actor TEXT,
+                                  payload TEXT, prev_hash TEXT, hash TEXT)",
+        )
+        .unwrap();
+        let mut events = chain(3);
+        for event in &mut events {
+            event.payload_digest = None;
+            event.prev_hash = events_prev_hash(&conn);
+            event.hash = event_hash(event);
+            conn.execute(
+                "INSERT INTO events VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                rusqlite::params![
+                    event.id,
+                    event.ts,
+                    event.event_type,
+                    event.actor,
+                    event.payload,
+                    event.prev_hash,
+                    event.hash
+                ],
+            )
+            .unwrap();
+        }
+
+        let report = walk(&conn, None, None, None).unwrap().finish(true, 0);
+        assert!(report.ok, "{:?}", report.issues);
+        assert_eq!(report.events_checked, 3);
+        assert_eq!(get_event(&conn, None, 2).unwrap().map(|e| e.hash), Some(events[1].hash.clone()));
+    }
+
+    fn events_prev_hash(conn: &Connection) -> String {
+        conn.query_row("SELECT hash FROM events ORDER BY id DESC LIMIT 1", [], |row| row.get(0))
+            .unwrap_or_else(|_| GENESIS_PREV_HASH.to_string())
+    }
+}
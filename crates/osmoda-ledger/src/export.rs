@@ -0,0 +1,134 @@
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::archive;
+use crate::chain::{self, Event, GENESIS_PREV_HASH};
+use crate::signing;
+
+/// Identifier written into the header line of JSONL exports.
+pub const EXPORT_FORMAT: &str = "osmoda-ledger-export";
+/// Version of the JSONL export layout.
+pub const EXPORT_VERSION: i64 = 1;
+
+/// Counts written to the footer of a JSONL export.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportSummary {
+    pub event_count: i64,
+    pub incident_count: i64,
+    pub step_count: i64,
+    pub checkpoint_count: i64,
+    pub head_hash: String,
+}
+
+/// Write the full audit history as JSONL: a header, every event with its chain
+/// hashes, incidents, incident steps, signed checkpoints, and a footer with counts.
+/// Rows are written as they are read, so `out` sees the export line by line.
+/// The format is checked standalone by `agentctl ledger verify-export`.
+pub fn write_jsonl<W: Write>(conn: &Connection, archive_dir: Option<&Path>, out: &mut W) -> Result<ExportSummary> {
+    let mut write_line = |value: Value| -> Result<()> {
+        serde_json::to_writer(&mut *out, &value)?;
+        out.write_all(b"\n")?;
+        Ok(())
+    };
+
+    write_line(json!({
+        "kind": "header",
+        "format": EXPORT_FORMAT,
+        "version": EXPORT_VERSION,
+        "exported_at": chrono::Utc::now().to_rfc3339(),
+        "genesis_prev_hash": GENESIS_PREV_HASH,
+    }))?;
+
+    let mut summary = ExportSummary {
+        event_count: 0,
+        incident_count: 0,
+        step_count: 0,
+        checkpoint_count: 0,
+        head_hash: GENESIS_PREV_HASH.to_string(),
+    };
+    let mut write_event = |event: Event| -> Result<()> {
+        let mut value = serde_json::to_value(&event)?;
+        value["kind"] = json!("event");
+        write_line(value)?;
+        summary.event_count += 1;
+        summary.head_hash = event.hash;
+        Ok(())
+    };
+
+    // Archived history first so the export always starts at genesis
+    for segment in archive::segments(conn)? {
+        let events = archive::read_indexed_segment(archive_dir, &segment)
+            .context("refusing to export: an archive segment failed its integrity check")?;
+        for event in events {
+            write_event(event)?;
+        }
+    }
+
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM events ORDER BY id ASC", chain::event_columns(conn)?))?;
+    let rows = stmt.query_map([], Event::from_row)?;
+    for event in rows {
+        write_event(event?)?;
+    }
+    drop(stmt);
+
+    let mut stmt = conn.prepare(
+        "SELECT id, name, status, created_at, resolved_at FROM incidents ORDER BY created_at ASC, id ASC",
+    )?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        write_line(json!({
+            "kind": "incident",
+            "id": row.get::<_, String>(0)?,
+            "name": row.get::<_, String>(1)?,
+            "status": row.get::<_, String>(2)?,
+            "created_at": row.get::<_, String>(3)?,
+            "resolved_at": row.get::<_, Option<String>>(4)?,
+        }))?;
+        summary.incident_count += 1;
+    }
+    drop(rows);
+    drop(stmt);
+
+    let mut stmt = conn.prepare(
+        "SELECT incident_id, step_number, action, result, receipt_id, timestamp
+         FROM incident_steps ORDER BY id ASC",
+    )?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        write_line(json!({
+            "kind": "incident_step",
+            "incident_id": row.get::<_, String>(0)?,
+            "step_number": row.get::<_, i64>(1)?,
+            "action": row.get::<_, String>(2)?,
+            "result": row.get::<_, String>(3)?,
+            "receipt_id": row.get::<_, Option<String>>(4)?,
+            "timestamp": row.get::<_, String>(5)?,
+        }))?;
+        summary.step_count += 1;
+    }
+    drop(rows);
+    drop(stmt);
+
+    for checkpoint in signing::signed_checkpoints(conn, 0)? {
+        let mut value = serde_json::to_value(&checkpoint)?;
+        value["kind"] = json!("checkpoint");
+        write_line(value)?;
+        summary.checkpoint_count += 1;
+    }
+
+    write_line(json!({
+        "kind": "footer",
+        "event_count": summary.event_count,
+        "incident_count": summary.incident_count,
+        "step_count": summary.step_count,
+        "checkpoint_count": summary.checkpoint_count,
+        "head_hash": summary.head_hash,
+    }))?;
+
+    Ok(summary)
+}
@@ -0,0 +1,12 @@
+//! The ledger's storage format, shared by agentd, which writes it, and agentctl, which
+//! audits it offline: event hashing, chain verification, archive segments, Merkle
+//! inclusion proofs and the messages the ledger key signs, plus the event filters and counts both report.
+//! Keeping them in one place means the two can never disagree about what a valid
+//! chain is.
+
+pub mod archive;
+pub mod chain;
+pub mod export;
+pub mod merkle;
+pub mod query;
+pub mod signing;
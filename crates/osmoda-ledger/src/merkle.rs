@@ -0,0 +1,197 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::signing;
+
+/// Domain separation prefixes (RFC 6962) so a leaf can never be confused with an inner node.
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+/// Which side of the running hash a sibling sits on when walking an audit path.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Side {
+    Left,
+    Right,
+}
+
+/// One step of an inclusion proof: the sibling hash and its position.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProofStep {
+    pub side: Side,
+    pub hash: String,
+}
+
+/// A sealed Merkle root over a contiguous range of events, signed by the ledger key
+/// when agentd had one at sealing time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MerkleCheckpoint {
+    pub id: i64,
+    pub first_event_id: i64,
+    pub last_event_id: i64,
+    pub leaf_count: i64,
+    pub root: String,
+    pub created_at: String,
+    pub public_key: Option<String>,
+    pub signature: Option<String>,
+}
+
+impl MerkleCheckpoint {
+    /// Why the root is not vouched for by `trusted_key`, if it is not.
+    pub fn signature_problem(&self, trusted_key: &str) -> Option<String> {
+        let (Some(public_key), Some(signature)) = (&self.public_key, &self.signature) else {
+            return Some(format!("merkle checkpoint #{} is not signed", self.id));
+        };
+        if !public_key.eq_ignore_ascii_case(trusted_key) {
+            return Some(format!("merkle checkpoint #{} signed by untrusted key {public_key}", self.id));
+        }
+        let message = signing::merkle_root_message(self.first_event_id, self.last_event_id, self.leaf_count, &self.root);
+        if !signing::verify_signature(trusted_key, &message, signature) {
+            return Some(format!("bad signature on merkle checkpoint #{}", self.id));
+        }
+        None
+    }
+}
+
+/// Hash a ledger event hash into a Merkle leaf.
+pub fn leaf_hash(event_hash: &str) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([LEAF_PREFIX]);
+    hasher.update(event_hash.as_bytes());
+    hasher.finalize().into()
+}
+
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([NODE_PREFIX]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Largest power of two strictly less than `n` (n must be > 1).
+fn split_point(n: usize) -> usize {
+    let mut k = 1;
+    while k << 1 < n {
+        k <<= 1;
+    }
+    k
+}
+
+fn subtree_root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    match leaves.len() {
+        0 => Sha256::digest([]).into(),
+        1 => leaves[0],
+        n => {
+            let k = split_point(n);
+            node_hash(&subtree_root(&leaves[..k]), &subtree_root(&leaves[k..]))
+        }
+    }
+}
+
+/// Compute the Merkle root over a list of event hashes (RFC 6962 tree shape).
+pub fn root(event_hashes: &[String]) -> String {
+    let leaves: Vec<[u8; 32]> = event_hashes.iter().map(|h| leaf_hash(h)).collect();
+    hex::encode(subtree_root(&leaves))
+}
+
+fn subtree_path(leaves: &[[u8; 32]], index: usize, path: &mut Vec<ProofStep>) {
+    if leaves.len() <= 1 {
+        return;
+    }
+    let k = split_point(leaves.len());
+    if index < k {
+        subtree_path(&leaves[..k], index, path);
+        path.push(ProofStep {
+            side: Side::Right,
+            hash: hex::encode(subtree_root(&leaves[k..])),
+        });
+    } else {
+        subtree_path(&leaves[k..], index - k, path);
+        path.push(ProofStep {
+            side: Side::Left,
+            hash: hex::encode(subtree_root(&leaves[..k])),
+        });
+    }
+}
+
+/// Build the audit path for the leaf at `index`, ordered from the leaf up to the root.
+/// Returns None if `index` is out of range.
+pub fn proof(event_hashes: &[String], index: usize) -> Option<Vec<ProofStep>> {
+    if index >= event_hashes.len() {
+        return None;
+    }
+    let leaves: Vec<[u8; 32]> = event_hashes.iter().map(|h| leaf_hash(h)).collect();
+    let mut path = Vec::new();
+    subtree_path(&leaves, index, &mut path);
+    Some(path)
+}
+
+/// Fold an audit path up from an event hash to the root it implies. None if a sibling
+/// hash is not 32 bytes of hex.
+pub fn fold_proof(event_hash: &str, path: &[ProofStep]) -> Option<String> {
+    let mut acc = leaf_hash(event_hash);
+    for step in path {
+        let sibling: [u8; 32] = hex::decode(&step.hash).ok().and_then(|b| b.try_into().ok())?;
+        acc = match step.side {
+            Side::Left => node_hash(&sibling, &acc),
+            Side::Right => node_hash(&acc, &sibling),
+        };
+    }
+    Some(hex::encode(acc))
+}
+
+/// Fold an audit path starting from an event hash and compare against the expected root.
+pub fn verify_proof(event_hash: &str, path: &[ProofStep], expected_root: &str) -> bool {
+    fold_proof(event_hash, path).is_some_and(|root| root == expected_root.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hashes(n: usize) -> Vec<String> {
+        (0..n).map(|i| format!("{i:064x}")).collect()
+    }
+
+    #[test]
+    fn test_single_leaf_root_is_leaf_hash() {
+        let h = hashes(1);
+        assert_eq!(root(&h), hex::encode(leaf_hash(&h[0])));
+        assert!(proof(&h, 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_proofs_verify_for_every_leaf() {
+        for n in 1..=17 {
+            let h = hashes(n);
+            let r = root(&h);
+            for i in 0..n {
+                let path = proof(&h, i).unwrap();
+                assert!(verify_proof(&h[i], &path, &r), "leaf {i} of {n} should verify");
+            }
+        }
+    }
+
+    #[test]
+    fn test_proof_rejects_wrong_leaf_and_root() {
+        let h = hashes(7);
+        let r = root(&h);
+        let path = proof(&h, 3).unwrap();
+        assert!(!verify_proof(&h[4], &path, &r));
+        assert!(!verify_proof(&h[3], &path, &root(&hashes(8))));
+    }
+
+    #[test]
+    fn test_proof_out_of_range() {
+        assert!(proof(&hashes(3), 3).is_none());
+    }
+
+    #[test]
+    fn test_leaf_and_node_domains_differ() {
+        // A two-leaf tree's root must not equal the leaf hash of the concatenation.
+        let h = hashes(2);
+        let r = root(&h);
+        assert_ne!(r, hex::encode(leaf_hash(&format!("{}{}", h[0], h[1]))));
+    }
+}
@@ -0,0 +1,243 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::{archive, chain};
+
+/// Filter criteria for querying events.
+#[derive(Debug, Default, Deserialize)]
+pub struct EventFilter {
+    /// Exact type, or a prefix when it ends in `*` (e.g. `switch.*`).
+    #[serde(rename = "type")]
+    pub event_type: Option<String>,
+    pub actor: Option<String>,
+    /// Identity agentd verified for the submitting connection (e.g. `osmoda-keyd`).
+    pub verified_actor: Option<String>,
+    pub limit: Option<i64>,
+    /// Only events at or after this RFC 3339 timestamp.
+    pub since: Option<String>,
+    /// Only events strictly before this RFC 3339 timestamp.
+    pub until: Option<String>,
+    /// Cursor: only events with a greater id.
+    pub after_id: Option<i64>,
+    /// Cursor: only events with a smaller id.
+    pub before_id: Option<i64>,
+    /// Sort by id; defaults to newest first.
+    pub order: Option<SortOrder>,
+}
+
+/// What `stats` counts events by.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatsGroup {
+    #[default]
+    Type,
+    Actor,
+    VerifiedActor,
+}
+
+impl StatsGroup {
+    fn column(self) -> &'static str {
+        match self {
+            StatsGroup::Type => "type",
+            StatsGroup::Actor => "actor",
+            StatsGroup::VerifiedActor => "COALESCE(verified_actor, '(unverified)')",
+        }
+    }
+}
+
+/// Histogram bucket width for `stats`. Buckets are UTC; weeks start on Monday.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatsBucket {
+    Hour,
+    Day,
+    Week,
+}
+
+impl StatsBucket {
+    /// SQLite expression for the start of the bucket containing `ts`.
+    fn expression(self) -> &'static str {
+        match self {
+            StatsBucket::Hour => "strftime('%Y-%m-%dT%H:00:00Z', ts)",
+            StatsBucket::Day => "strftime('%Y-%m-%dT00:00:00Z', ts)",
+            // 'weekday 0' moves forward to Sunday (or stays), then back to that week's Monday
+            StatsBucket::Week => "strftime('%Y-%m-%dT00:00:00Z', ts, 'weekday 0', '-6 days')",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct GroupCount {
+    pub key: String,
+    pub count: i64,
+}
+
+/// Event counts for one time bucket, per group key.
+#[derive(Debug, Clone, Serialize)]
+pub struct HistogramBucket {
+    pub start: String,
+    pub total: i64,
+    pub counts: BTreeMap<String, i64>,
+}
+
+/// Aggregate view of the ledger, served by `/ledger/stats`.
+#[derive(Debug, Clone, Serialize)]
+pub struct LedgerStats {
+    /// Live events matching the filter.
+    pub total: i64,
+    /// Events moved into archive segments (not filtered or grouped).
+    pub archived: i64,
+    pub first_ts: Option<String>,
+    pub last_ts: Option<String>,
+    pub group_by: StatsGroup,
+    /// Largest groups first.
+    pub groups: Vec<GroupCount>,
+    pub bucket: Option<StatsBucket>,
+    /// Oldest bucket first; empty buckets are omitted.
+    pub histogram: Vec<HistogramBucket>,
+}
+
+/// Ordering of query results by ledger id.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+/// Normalize an RFC 3339 timestamp (offset-less values and bare `YYYY-MM-DD`
+/// are taken as UTC) to the ledger's `ts` format so SQL string comparison orders correctly.
+pub fn normalize_timestamp(input: &str) -> Result<String> {
+    let input = input.trim();
+    let parsed = if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(input) {
+        dt.with_timezone(&chrono::Utc)
+    } else if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(input, "%Y-%m-%dT%H:%M:%S%.f") {
+        dt.and_utc()
+    } else {
+        chrono::NaiveDate::parse_from_str(input, "%Y-%m-%d")
+            .map(|d| d.and_time(chrono::NaiveTime::MIN).and_utc())
+            .with_context(|| format!("invalid timestamp '{input}': expected RFC 3339 or YYYY-MM-DD"))?
+    };
+    Ok(parsed.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string())
+}
+
+/// SQL `AND ...` conditions (numbered placeholders) for the non-paging parts of a filter.
+/// `limit` and `order` are left to the caller.
+pub fn filter_conditions(filter: &EventFilter) -> Result<(String, Vec<Box<dyn rusqlite::types::ToSql>>)> {
+    let mut sql = String::new();
+    let mut param_values: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
+
+    if let Some(ref t) = filter.event_type {
+        match t.strip_suffix('*') {
+            Some(prefix) => {
+                let n = param_values.len();
+                sql.push_str(&format!(" AND substr(type, 1, ?{}) = ?{}", n + 1, n + 2));
+                param_values.push(Box::new(prefix.chars().count() as i64));
+                param_values.push(Box::new(prefix.to_string()));
+            }
+            None => {
+                sql.push_str(&format!(" AND type = ?{}", param_values.len() + 1));
+                param_values.push(Box::new(t.clone()));
+            }
+        }
+    }
+
+    if let Some(ref a) = filter.actor {
+        sql.push_str(&format!(" AND actor = ?{}", param_values.len() + 1));
+        param_values.push(Box::new(a.clone()));
+    }
+
+    if let Some(ref v) = filter.verified_actor {
+        sql.push_str(&format!(" AND verified_actor = ?{}", param_values.len() + 1));
+        param_values.push(Box::new(v.clone()));
+    }
+
+    if let Some(ref since) = filter.since {
+        sql.push_str(&format!(" AND ts >= ?{}", param_values.len() + 1));
+        param_values.push(Box::new(normalize_timestamp(since)?));
+    }
+
+    if let Some(ref until) = filter.until {
+        sql.push_str(&format!(" AND ts < ?{}", param_values.len() + 1));
+        param_values.push(Box::new(normalize_timestamp(until)?));
+    }
+
+    if let Some(after_id) = filter.after_id {
+        sql.push_str(&format!(" AND id > ?{}", param_values.len() + 1));
+        param_values.push(Box::new(after_id));
+    }
+
+    if let Some(before_id) = filter.before_id {
+        sql.push_str(&format!(" AND id < ?{}", param_values.len() + 1));
+        param_values.push(Box::new(before_id));
+    }
+
+    Ok((sql, param_values))
+}
+
+/// Counts over the live table matching `filter` (`limit`/`order` ignored), grouped by
+/// `group_by` and, when `bucket` is set, split into a time histogram. All aggregation
+/// runs in SQLite. Archived events are only reported as a total.
+pub fn stats(
+    conn: &Connection,
+    filter: &EventFilter,
+    group_by: StatsGroup,
+    bucket: Option<StatsBucket>,
+) -> Result<LedgerStats> {
+    let (conditions, param_values) = filter_conditions(filter)?;
+    let params_refs: Vec<&dyn rusqlite::types::ToSql> = param_values.iter().map(|p| p.as_ref()).collect();
+
+    let (total, first_ts, last_ts): (i64, Option<String>, Option<String>) = conn
+        .query_row(
+            &format!("SELECT COUNT(*), MIN(ts), MAX(ts) FROM events WHERE 1=1{conditions}"),
+            params_refs.as_slice(),
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .context("failed to count events")?;
+
+    let archived: i64 = archive::segments(conn)?.iter().map(|s| s.event_count).sum();
+
+    // A ledger agentd has not migrated yet has no verified authorship at all
+    let key = match group_by {
+        StatsGroup::VerifiedActor if !chain::has_column(conn, "verified_actor")? => "'(unverified)'",
+        _ => group_by.column(),
+    };
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {key} AS k, COUNT(*) AS n FROM events WHERE 1=1{conditions} GROUP BY k ORDER BY n DESC, k ASC"
+    ))?;
+    let groups = stmt
+        .query_map(params_refs.as_slice(), |row| {
+            Ok(GroupCount { key: row.get(0)?, count: row.get(1)? })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .context("failed to group events")?;
+    drop(stmt);
+
+    let mut histogram: Vec<HistogramBucket> = Vec::new();
+    if let Some(bucket) = bucket {
+        let start = bucket.expression();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {start} AS b, {key} AS k, COUNT(*) FROM events WHERE 1=1{conditions}
+             GROUP BY b, k ORDER BY b ASC, k ASC"
+        ))?;
+        let rows = stmt.query_map(params_refs.as_slice(), |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, i64>(2)?))
+        })?;
+        for row in rows {
+            let (start, key, count) = row.context("failed to bucket events")?;
+            if histogram.last().is_none_or(|b| b.start != start) {
+                histogram.push(HistogramBucket { start, total: 0, counts: BTreeMap::new() });
+            }
+            let current = histogram.last_mut().expect("bucket just pushed");
+            current.total += count;
+            current.counts.insert(key, count);
+        }
+    }
+
+    Ok(LedgerStats { total, archived, first_ts, last_ts, group_by, groups, bucket, histogram })
+}
+
@@ -0,0 +1,101 @@
+use anyhow::{Context, Result};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+/// Default number of events between signed head checkpoints. A verifier holding the
+/// trusted key fails a ledger whose head is further than this past its newest one.
+pub const DEFAULT_SIGNED_CHECKPOINT_EVERY: i64 = 100;
+
+/// Columns read by `SignedCheckpoint::from_row`, in order.
+pub const CHECKPOINT_COLUMNS: &str = "id, event_id, head_hash, signed_at, public_key, signature";
+
+/// A signed attestation of the ledger head at a point in time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedCheckpoint {
+    pub id: i64,
+    pub event_id: i64,
+    pub head_hash: String,
+    pub signed_at: String,
+    pub public_key: String,
+    pub signature: String,
+}
+
+impl SignedCheckpoint {
+    /// Read a checkpoint from a row selected with `CHECKPOINT_COLUMNS`.
+    pub fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            event_id: row.get(1)?,
+            head_hash: row.get(2)?,
+            signed_at: row.get(3)?,
+            public_key: row.get(4)?,
+            signature: row.get(5)?,
+        })
+    }
+
+    /// Why the checkpoint is not a valid signature by `trusted_key`, if it is not.
+    /// Says nothing about whether the chain still has `head_hash` at `event_id`.
+    pub fn signature_problem(&self, trusted_key: &str) -> Option<String> {
+        if !self.public_key.eq_ignore_ascii_case(trusted_key) {
+            return Some(format!("checkpoint #{} signed by untrusted key {}", self.id, self.public_key));
+        }
+        let message = checkpoint_message(self.event_id, &self.head_hash, &self.signed_at);
+        if !verify_signature(trusted_key, &message, &self.signature) {
+            return Some(format!("bad signature on checkpoint #{}", self.id));
+        }
+        None
+    }
+}
+
+/// Signed checkpoints over events after `after_event_id`, oldest first. Empty for a
+/// ledger from before signed checkpoints.
+pub fn signed_checkpoints(conn: &Connection, after_event_id: i64) -> Result<Vec<SignedCheckpoint>> {
+    let has_table: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'checkpoints'",
+        [],
+        |row| row.get(0),
+    )?;
+    if has_table == 0 {
+        return Ok(Vec::new());
+    }
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {CHECKPOINT_COLUMNS} FROM checkpoints WHERE event_id > ?1 ORDER BY id ASC"
+    ))?;
+    let checkpoints = stmt
+        .query_map([after_event_id], SignedCheckpoint::from_row)?
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .context("failed to read signed checkpoints")?;
+    Ok(checkpoints)
+}
+
+/// Canonical message signed for a ledger checkpoint.
+pub fn checkpoint_message(event_id: i64, head_hash: &str, signed_at: &str) -> String {
+    format!("osmoda-ledger-checkpoint|{event_id}|{head_hash}|{signed_at}")
+}
+
+/// Canonical message signed for a sealed Merkle root.
+pub fn merkle_root_message(first_event_id: i64, last_event_id: i64, leaf_count: i64, root: &str) -> String {
+    format!("osmoda-ledger-merkle|{first_event_id}|{last_event_id}|{leaf_count}|{root}")
+}
+
+/// Canonical message signed for the incremental-verification watermark.
+pub fn watermark_message(event_id: i64, hash: &str, verified_at: &str) -> String {
+    format!("osmoda-ledger-watermark|{event_id}|{hash}|{verified_at}")
+}
+
+/// Verify a hex signature over `message` with a hex public key.
+pub fn verify_signature(public_key_hex: &str, message: &str, signature_hex: &str) -> bool {
+    let Some(key_bytes) = hex::decode(public_key_hex).ok().and_then(|b| <[u8; 32]>::try_from(b).ok()) else {
+        return false;
+    };
+    let Some(sig_bytes) = hex::decode(signature_hex).ok().and_then(|b| <[u8; 64]>::try_from(b).ok()) else {
+        return false;
+    };
+    let Ok(verifying_key) = VerifyingKey::from_bytes(&key_bytes) else {
+        return false;
+    };
+    verifying_key
+        .verify(message.as_bytes(), &Signature::from_bytes(&sig_bytes))
+        .is_ok()
+}
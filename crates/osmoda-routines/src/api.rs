@@ -128,6 +128,9 @@ pub async fn routine_trigger_handler(
     drop(st);
 
     let result = crate::routine::execute_action(&action, &agentd_socket).await;
+    if crate::routine::outcome_changed(&mut state.lock().await.failing, &id, result.is_ok()) {
+        crate::routine::report_outcome(&agentd_socket, &id, &name, result.as_ref().err().map(String::as_str)).await;
+    }
     match &result {
         Ok(output) => {
             tracing::info!(routine = %name, "manual trigger succeeded");
@@ -2,6 +2,7 @@ mod api;
 mod routine;
 mod scheduler;
 
+use std::collections::HashSet;
 use std::path::Path;
 use std::sync::Arc;
 
@@ -38,6 +39,8 @@ pub struct RoutinesState {
     pub routines: Vec<Routine>,
     pub agentd_socket: String,
     pub routines_dir: String,
+    /// Ids of routines whose last run failed.
+    pub failing: HashSet<String>,
 }
 
 #[tokio::main]
@@ -64,6 +67,7 @@ async fn main() {
         routines,
         agentd_socket: args.agentd_socket,
         routines_dir: args.routines_dir,
+        failing: HashSet::new(),
     }));
 
     let cancel = CancellationToken::new();
@@ -164,19 +168,19 @@ async fn scheduler_loop(state: Arc<Mutex<RoutinesState>>, cancel: CancellationTo
 
                 // Execute due routines (clone action + socket to release lock)
                 let agentd_socket = st.agentd_socket.clone();
-                let actions: Vec<(usize, String, routine::RoutineAction)> = due
+                let actions: Vec<(usize, String, String, routine::RoutineAction)> = due
                     .iter()
                     .filter_map(|&i| {
                         st.routines
                             .get(i)
-                            .map(|r| (i, r.name.clone(), r.action.clone()))
+                            .map(|r| (i, r.id.clone(), r.name.clone(), r.action.clone()))
                     })
                     .collect();
 
                 // Release lock for execution
                 drop(st);
 
-                for (idx, name, action) in actions {
+                for (idx, id, name, action) in actions {
                     let result = routine::execute_action(&action, &agentd_socket).await;
                     match &result {
                         Ok(output) => {
@@ -193,6 +197,13 @@ async fn scheduler_loop(state: Arc<Mutex<RoutinesState>>, cancel: CancellationTo
                         r.last_run = Some(now.to_rfc3339());
                         r.run_count += 1;
                     }
+                    if routine::outcome_changed(&mut st.failing, &id, result.is_ok()) {
+                        let error = result.as_ref().err().cloned();
+                        let socket = agentd_socket.clone();
+                        tokio::spawn(async move {
+                            routine::report_outcome(&socket, &id, &name, error.as_deref()).await;
+                        });
+                    }
                 }
             }
         }
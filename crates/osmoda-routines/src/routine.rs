@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::time::Duration;
 use serde::{Deserialize, Serialize};
 
@@ -253,11 +254,43 @@ async fn memory_maintenance(socket: &str) -> Result<String, String> {
         "tags": ["memory", "maintenance", "routine"]
     });
 
-    let _ = agentd_post(socket, "/memory/store", &store_body.to_string()).await;
+    if let Err(e) = agentd_post(socket, "/memory/store", &store_body.to_string()).await {
+        tracing::warn!(error = %e, "failed to store consolidation summary in agentd");
+    }
 
     Ok(summary)
 }
 
+/// Whether a run is worth reporting to agentd: every failure, and the first success
+/// after one. Tracks which routines are failing in `failing`.
+pub fn outcome_changed(failing: &mut HashSet<String>, routine_id: &str, ok: bool) -> bool {
+    if ok {
+        failing.remove(routine_id)
+    } else {
+        failing.insert(routine_id.to_string());
+        true
+    }
+}
+
+/// Report a failed run (`routine.failed`) or a recovery (`routine.recovered`) to agentd,
+/// whose correlation engine opens and resolves incidents from them. Best-effort.
+pub async fn report_outcome(socket: &str, routine_id: &str, name: &str, error: Option<&str>) {
+    let category = if error.is_some() { "routine.failed" } else { "routine.recovered" };
+    let body = serde_json::json!({
+        "source": "osmoda-routines",
+        "content": serde_json::json!({
+            "routine": name,
+            "routine_id": routine_id,
+            "error": error,
+        }).to_string(),
+        "category": category,
+        "tags": ["routine", category, name],
+    });
+    if let Err(e) = agentd_post(socket, "/memory/ingest", &body.to_string()).await {
+        tracing::debug!(error = %e, routine = %name, "failed to report routine outcome to agentd (non-fatal)");
+    }
+}
+
 /// HTTP GET over Unix socket to agentd.
 async fn agentd_get(socket_path: &str, path: &str) -> Result<String, String> {
     use hyper::body::Bytes;
@@ -321,6 +354,16 @@ async fn agentd_post(socket_path: &str, path: &str, body: &str) -> Result<String
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_outcome_changed_reports_failures_and_first_recovery() {
+        let mut failing = HashSet::new();
+        assert!(!outcome_changed(&mut failing, "r1", true));
+        assert!(outcome_changed(&mut failing, "r1", false));
+        assert!(outcome_changed(&mut failing, "r1", false));
+        assert!(outcome_changed(&mut failing, "r1", true));
+        assert!(!outcome_changed(&mut failing, "r1", true));
+    }
+
     #[test]
     fn test_validate_command_requires_absolute_path() {
         assert!(validate_command("relative/path").is_err());
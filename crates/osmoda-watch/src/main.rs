@@ -221,12 +221,20 @@ async fn watcher_loop(state: Arc<Mutex<WatchState>>, check_interval: u64, cancel
                 let mut st = state.lock().await;
                 let agentd_socket = st.agentd_socket.clone();
                 for watcher in &mut st.watchers {
+                    let was_degraded = watcher.is_degraded();
                     let actions = watcher::run_watcher_cycle(watcher).await;
                     for action in &actions {
                         tracing::info!(watcher = %watcher.name, action = %action, "watcher action");
                     }
-                    // Log escalation events to agentd (best-effort)
-                    if !actions.is_empty() {
+                    // State changes open and resolve incidents in agentd's correlation engine
+                    let event_type = match (was_degraded, watcher.is_degraded()) {
+                        (false, true) => Some("watch.watcher.degraded"),
+                        (true, false) => Some("watch.watcher.recovered"),
+                        _ if !actions.is_empty() => Some("watch.watcher.escalation"),
+                        _ => None,
+                    };
+                    // Log watcher events to agentd (best-effort)
+                    if let Some(event_type) = event_type {
                         let watcher_name = watcher.name.clone();
                         let payload = serde_json::json!({
                             "watcher": watcher_name,
@@ -234,8 +242,8 @@ async fn watcher_loop(state: Arc<Mutex<WatchState>>, check_interval: u64, cancel
                         }).to_string();
                         let sock = agentd_socket.clone();
                         tokio::spawn(async move {
-                            if let Err(e) = agentd_post_event(&sock, "watch.watcher.escalation", &payload).await {
-                                tracing::debug!(error = %e, "failed to log watcher escalation to agentd (non-fatal)");
+                            if let Err(e) = agentd_post_event(&sock, event_type, &payload).await {
+                                tracing::debug!(error = %e, event_type, "failed to log watcher event to agentd (non-fatal)");
                             }
                         });
                     }
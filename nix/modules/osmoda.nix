@@ -300,6 +300,27 @@ in {
       ];
       description = "Operations requiring explicit user approval";
     };
+
+    approvalPolicyFile = mkOption {
+      type = types.nullOr types.str;
+      default = null;
+      example = "/var/lib/osmoda/approval-policy.json";
+      description = "JSON approval policy (ordered rules deciding allow, require approval, require N approvers or deny). agentd reloads it when the file changes.";
+    };
+
+    approvalNotify = {
+      webhooks = mkOption {
+        type = types.listOf types.str;
+        default = [];
+        example = [ "https://hooks.example.com/osmoda/approvals" ];
+        description = "https:// URLs on port 443 POSTed for each new approval request, through the egress proxy (add their domains to sandbox.egressProxy.defaultAllow)";
+      };
+      meshPeers = mkOption {
+        type = types.listOf types.str;
+        default = [];
+        description = "Mesh peer IDs sent an alert for each new approval request";
+      };
+    };
   };
 
   config = mkIf cfg.enable {
@@ -364,11 +385,16 @@ in {
       after = [ "network-online.target" ];
       wants = [ "network-online.target" ];
 
+      # Approval webhooks are delivered with curl
+      path = optionals (cfg.approvalNotify.webhooks != []) [ pkgs.curl ];
+
       serviceConfig = {
         Type = "simple";
         ExecStart = let
           approvalPatterns = builtins.concatStringsSep "," cfg.approvalRequired;
-        in "${cfg.agentd.package}/bin/agentd --socket ${cfg.agentd.socketPath} --state-dir ${cfg.stateDir} --approval-required --approval-patterns '${approvalPatterns}' --sandbox-enabled";
+          approvalNotify = concatMapStrings (url: " --approval-webhook '${url}'") cfg.approvalNotify.webhooks
+            + concatMapStrings (peer: " --approval-notify-peer ${peer}") cfg.approvalNotify.meshPeers;
+        in "${cfg.agentd.package}/bin/agentd --socket ${cfg.agentd.socketPath} --state-dir ${cfg.stateDir} --approval-required --approval-patterns '${approvalPatterns}'${optionalString (cfg.approvalPolicyFile != null) " --approval-policy ${cfg.approvalPolicyFile}"}${approvalNotify} --egress-proxy http://127.0.0.1:${toString cfg.sandbox.egressProxy.port} --mesh-socket ${cfg.mesh.socketPath} --sandbox-enabled";
         Restart = "always";
         RestartSec = 3;
 
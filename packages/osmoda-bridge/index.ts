@@ -51,6 +51,11 @@ import { teachdRequest } from "./teachd-client";
 const AGENTD_SOCKET = process.env.OSMODA_SOCKET || "/run/osmoda/agentd.sock";
 
 function agentdRequest(method: string, reqPath: string, body?: unknown): Promise<string> {
+  return agentdCall(method, reqPath, body).then((r) => r.body);
+}
+
+/** Like agentdRequest, but also returns the HTTP status code. */
+function agentdCall(method: string, reqPath: string, body?: unknown): Promise<{ status: number; body: string }> {
   return new Promise((resolve, reject) => {
     const payload = body ? JSON.stringify(body) : undefined;
     const req = http.request({
@@ -62,7 +67,7 @@ function agentdRequest(method: string, reqPath: string, body?: unknown): Promise
     }, (res) => {
       let data = "";
       res.on("data", (c: Buffer) => { data += c.toString(); });
-      res.on("end", () => { resolve(data); });
+      res.on("end", () => { resolve({ status: res.statusCode || 0, body: data }); });
     });
     req.on("error", (e) => reject(e));
     if (payload) req.write(payload);
@@ -442,6 +447,7 @@ export default function register(api: any) {
       properties: {
         command: { type: "string", description: "Shell command to execute" },
         timeout: { type: "number", description: "Timeout in ms (default 30000)" },
+        approval_id: { type: "string", description: "ID of an approved request for exactly this command, when it needed approval" },
       },
       required: ["command"],
     },
@@ -457,24 +463,71 @@ export default function register(api: any) {
       if (shellExecTimestamps.length > SHELL_EXEC_RATE_LIMIT) {
         return { output: JSON.stringify({ error: "Rate limit exceeded: max 30 shell_exec calls per minute" }) };
       }
-      // Approval gate enforcement — check with agentd before executing
-      // If the approval gate is enabled, it will block destructive commands
-      // and auto-approve safe ones. Falls back to static blocklist if gate unavailable.
+      // A previously approved command: spend the approval's single-use execution
+      // token with agentd before running it. Only the exact approved command runs.
+      if (params.approval_id !== undefined) {
+        const blocked = (error: string) => ({ output: JSON.stringify({ error, approval_id: params.approval_id }) });
+        let approval: any;
+        try {
+          const check = await agentdCall("GET", `/approval/${encodeURIComponent(String(params.approval_id))}`);
+          approval = JSON.parse(check.body);
+          if (check.status !== 200) return blocked(`Approval lookup failed: ${approval.error || `HTTP ${check.status}`}`);
+        } catch (e: any) {
+          return blocked(`Approval lookup failed: ${e.message}`);
+        }
+        if (approval.status !== "approved") return blocked(`Approval is ${approval.status}, not approved`);
+        if (approval.command !== cmd) return blocked("Approval was granted for a different command");
+        if (!approval.execution_token) return blocked("No execution token for this approval: it was already used, has expired, or was requested by someone else");
+        try {
+          const spend = await agentdCall("POST", "/approval/execute", {
+            command: cmd, target: approval.target, actor: "openclaw.agent", approval_token: approval.execution_token,
+          });
+          if (spend.status < 200 || spend.status >= 300) {
+            let detail: any = {};
+            try { detail = JSON.parse(spend.body); } catch { /* status only */ }
+            return blocked(`Execution token refused: ${detail.error || `HTTP ${spend.status}`}`);
+          }
+        } catch (e: any) {
+          return blocked(`Execution token refused: ${e.message}`);
+        }
+        const approvedResult = runShell(cmd, timeout);
+        agentdRequest("POST", "/memory/ingest", {
+          event: { category: "system", subcategory: "shell_exec", actor: "openclaw.agent",
+            summary: "Shell (approved): " + cmd.substring(0, 100), detail: "output_length=" + approvedResult.length,
+            metadata: { command: cmd, approval_id: approval.id } },
+        }).catch(() => {});
+        return { output: approvedResult };
+      }
+      // Approval gate enforcement — check with agentd before executing.
+      // Only an "auto_approved" answer runs the command; a pending request, a policy
+      // denial or any other error blocks it. Falls back to the static blocklist only
+      // when agentd is unreachable or runs without the gate.
+      let gate: { status: number; body: string } | undefined;
       try {
-        const approvalResp = await agentdRequest("POST", "/approval/request", {
+        gate = await agentdCall("POST", "/approval/request", {
           command: cmd, actor: "openclaw.agent", reason: "shell_exec",
         });
-        const approval = JSON.parse(approvalResp);
+      } catch {
+        gate = undefined;
+      }
+      if (gate && gate.status !== 503) {
+        let approval: any = {};
+        try { approval = JSON.parse(gate.body); } catch { /* blocked below */ }
         if (approval.status === "pending") {
           return { output: JSON.stringify({
             error: `Command requires approval: ${cmd.substring(0, 80)}`,
             approval_id: approval.id,
             status: "pending",
-            hint: "Use approval_check to poll status, or approval_approve/approval_deny to decide.",
+            hint: "Use approval_check (with wait_secs to block) for status. Once approved, call shell_exec again with the same command and this approval_id.",
           })};
         }
-        // "auto_approved" or "approved" — proceed to execution
-      } catch {
+        if (gate.status < 200 || gate.status >= 300 || approval.status !== "auto_approved") {
+          return { output: JSON.stringify({
+            error: `Command blocked by the approval gate: ${approval.error || `HTTP ${gate.status}`}`,
+            rule: approval.rule,
+          })};
+        }
+      } else {
         // Approval gate unavailable — fall back to static blocklist
         const norm = normalizeCmd(cmd);
         const matched = DANGEROUS_COMMANDS.find((d) => norm.includes(d));
@@ -1992,13 +2045,14 @@ export default function register(api: any) {
       properties: {
         command: { type: "string", description: "The command or operation identifier (e.g. 'rm -rf /data' or 'nix.rebuild')" },
         reason: { type: "string", description: "Why this operation is needed" },
+        target: { type: "string", description: "Path the operation acts on (optional; part of what gets approved)" },
       },
       required: ["command", "reason"],
     },
     async execute(_id: string, params: Record<string, unknown>) {
       try {
         return { output: await agentdRequest("POST", "/approval/request", {
-          command: params.command, reason: params.reason,
+          command: params.command, reason: params.reason, target: params.target,
         }) };
       } catch (e: any) {
         return { output: JSON.stringify({ error: e.message }) };
@@ -2046,16 +2100,20 @@ export default function register(api: any) {
   api.registerTool(() => ({
     name: "approval_check",
     label: "Check Approval",
-    description: "Check the status of an approval request (pending, approved, denied, expired).",
+    description: "Check the status of an approval request (pending, approved, denied, expired). Pass wait_secs to block until it is decided or expires instead of polling.",
     parameters: {
       type: "object",
       properties: {
         id: { type: "string", description: "Approval request ID" },
+        wait_secs: { type: "number", description: "Wait up to this many seconds (max 300) for a decision" },
       },
       required: ["id"],
     },
     async execute(_id: string, params: Record<string, unknown>) {
       try {
+        if (params.wait_secs !== undefined) {
+          return { output: await agentdRequest("GET", `/approval/${params.id}/wait?timeout=${Number(params.wait_secs)}`) };
+        }
         return { output: await agentdRequest("GET", `/approval/${params.id}`) };
       } catch (e: any) {
         return { output: JSON.stringify({ error: e.message }) };
@@ -2082,6 +2140,11 @@ export default function register(api: any) {
           description: "Capability strings (e.g. 'network', 'fs:/var/lib/myapp'). Only applies to Ring 1.",
         },
         timeout_secs: { type: "number", description: "Execution timeout in seconds. Default: 60" },
+        target: { type: "string", description: "Path the command acts on, exactly as given in the approval request" },
+        approval_token: {
+          type: "object",
+          description: "execution_token from an approved request, required for commands that need approval. Valid once, for exactly the approved command and target.",
+        },
       },
       required: ["command"],
     },
@@ -2092,6 +2155,8 @@ export default function register(api: any) {
           ring: params.ring || 2,
           capabilities: params.capabilities || [],
           timeout_secs: params.timeout_secs || 60,
+          target: params.target,
+          approval_token: params.approval_token,
         }) };
       } catch (e: any) {
         return { output: JSON.stringify({ error: e.message }) };